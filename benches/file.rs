@@ -0,0 +1,69 @@
+//! Compares log calls writing to the file themselves with log calls handing
+//! the line to the background writer thread (`async_file`), with several
+//! threads logging at once.
+//!
+//! ```bash
+//! cargo bench --bench file
+//! ```
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use fstdout_logger::{FStdoutLogger, FileMode, LoggerConfig};
+use log::{Level, LevelFilter, Log, Record};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Number of threads logging at the same time.
+const THREADS: u64 = 4;
+
+fn logger(path: &Path, async_file: bool) -> FStdoutLogger {
+    let config = LoggerConfig::builder()
+        .stdout_enabled(false)
+        .file_mode(FileMode::Truncate)
+        .async_file(async_file)
+        .build();
+    FStdoutLogger::with_config(Some(path), config).expect("Failed to create logger")
+}
+
+fn bench_file(c: &mut Criterion) {
+    log::set_max_level(LevelFilter::Trace);
+    let path = std::env::temp_dir().join("fstdout_logger_bench_file.log");
+
+    let mut group = c.benchmark_group("file");
+    for (name, async_file) in [("sync", false), ("async", true)] {
+        let logger = logger(&path, async_file);
+        // Time spent in the log calls only; queued lines are written
+        // afterwards, outside the measurement
+        group.bench_function(name, |b| {
+            b.iter_custom(|iters| {
+                let per_thread = iters.div_ceil(THREADS);
+                let elapsed: Duration = std::thread::scope(|scope| {
+                    let threads: Vec<_> = (0..THREADS)
+                        .map(|_| {
+                            scope.spawn(|| {
+                                let start = Instant::now();
+                                for _ in 0..per_thread {
+                                    logger.log(
+                                        &Record::builder()
+                                            .args(format_args!("benchmark line"))
+                                            .level(Level::Info)
+                                            .build(),
+                                    );
+                                }
+                                start.elapsed()
+                            })
+                        })
+                        .collect();
+                    threads.into_iter().map(|t| t.join().unwrap()).sum()
+                });
+                logger.flush();
+                elapsed / THREADS as u32
+            })
+        });
+    }
+    group.finish();
+
+    let _ = std::fs::remove_file(&path);
+}
+
+criterion_group!(benches, bench_file);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+//! Measures the cost of a log call that formats a record, including the
+//! read lock on the settings that makes `LoggerHandle::set_config` possible,
+//! next to the cost of that lock alone.
+//!
+//! ```bash
+//! cargo bench --bench settings
+//! ```
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{Level, LevelFilter, Record};
+use std::hint::black_box;
+use std::sync::{Arc, RwLock};
+
+fn bench_settings(c: &mut Criterion) {
+    log::set_max_level(LevelFilter::Trace);
+
+    let mut group = c.benchmark_group("settings");
+
+    // The lock and reference count update done once per log call
+    let settings = RwLock::new(Arc::new(LoggerConfig::default()));
+    group.bench_function("read_lock", |b| {
+        b.iter(|| black_box(Arc::clone(&settings.read().unwrap())))
+    });
+
+    let config = LoggerConfig::builder()
+        .show_file_info(false)
+        .use_colors(false)
+        .build();
+    let logger =
+        FStdoutLogger::with_writer(std::io::sink(), config).expect("Failed to create logger");
+    group.bench_function("log_record", |b| {
+        b.iter(|| {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("benchmark line"))
+                    .level(Level::Info)
+                    .build(),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_settings);
+criterion_main!(benches);
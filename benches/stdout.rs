@@ -0,0 +1,44 @@
+//! Compares writing log lines through Rust's stdout handle with writing to
+//! the raw stdout file descriptor (`fast_stdout`).
+//!
+//! Redirect stdout to keep the terminal usable while the benchmark runs:
+//!
+//! ```bash
+//! cargo bench --bench stdout > /dev/null
+//! ```
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{Level, LevelFilter, Log, Record};
+
+fn logger(fast_stdout: bool) -> FStdoutLogger {
+    let config = LoggerConfig::builder()
+        .show_file_info(false)
+        .use_colors(false)
+        .fast_stdout(fast_stdout)
+        .build();
+    FStdoutLogger::with_config(None::<String>, config).expect("Failed to create logger")
+}
+
+fn bench_stdout(c: &mut Criterion) {
+    log::set_max_level(LevelFilter::Trace);
+
+    let mut group = c.benchmark_group("stdout");
+    for (name, fast_stdout) in [("locked", false), ("raw_fd", true)] {
+        let logger = logger(fast_stdout);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                logger.log(
+                    &Record::builder()
+                        .args(format_args!("benchmark line"))
+                        .level(Level::Info)
+                        .build(),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_stdout);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+// This example demonstrates how to deliver formatted log lines to a channel,
+// e.g. to render them in a TUI widget, while also writing them to a file.
+//
+// Key features shown:
+// - Sending every line to a bounded channel with `channel_sink`
+// - Turning off stdout output, so only the receiver shows the lines
+// - Draining the channel on a background thread
+// - Counting lines dropped when the receiver falls behind
+
+use fstdout_logger::{ErrorHandler, LogError, LoggerConfig, init_logger_with_config};
+use log::{LevelFilter, info, warn};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    // A bounded channel: the logger never blocks when it is full
+    let (sender, receiver) = mpsc::sync_channel::<String>(64);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Debug)
+        .show_file_info(false)
+        .use_colors(false) // A widget usually doesn't understand escape codes
+        .stdout_enabled(false) // The widget takes the place of stdout
+        .channel_sink(Some(sender))
+        .on_error(ErrorHandler::new(|error| {
+            if let LogError::ChannelDropped(count) = error {
+                eprintln!("log widget is behind, {count} lines dropped");
+            }
+        }))
+        .build();
+
+    if let Err(e) = init_logger_with_config(Some("channel_sink.log"), config) {
+        eprintln!("Failed to initialize logger: {e}");
+        return;
+    }
+
+    // Stand-in for the UI thread that renders the lines
+    let widget = thread::spawn(move || {
+        for line in receiver {
+            println!("[widget] {line}");
+        }
+    });
+
+    for step in 1..=5 {
+        info!("Processing step {step}");
+        thread::sleep(Duration::from_millis(100));
+    }
+    warn!("All steps done");
+
+    // The installed logger keeps the sender alive, so the widget thread
+    // would wait forever; give it a moment to render the last lines instead
+    thread::sleep(Duration::from_millis(100));
+    drop(widget);
+
+    println!("The same lines were written to channel_sink.log");
+}
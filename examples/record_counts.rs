@@ -0,0 +1,43 @@
+// This example demonstrates how to observe every record the logger writes,
+// here to count log lines per level for metrics.
+//
+// Key features shown:
+// - Registering a `RecordObserver` in the configuration
+// - Tallying records in an `AtomicU64` array indexed by level
+// - Records below the configured level are not observed
+
+use fstdout_logger::{LoggerConfig, RecordObserver, init_stdout_logger};
+use log::{Level, LevelFilter, debug, error, info, trace, warn};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of records written per level, indexed by `Level as usize - 1`
+static COUNTS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+
+fn main() {
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Debug)
+        .show_file_info(false)
+        .observer(RecordObserver::new(|record| {
+            COUNTS[record.level() as usize - 1].fetch_add(1, Ordering::Relaxed);
+        }))
+        .build();
+
+    if let Err(e) = init_stdout_logger(config) {
+        eprintln!("Failed to initialize logger: {e}");
+        return;
+    }
+
+    for request in 1..=3 {
+        info!("Handling request {request}");
+        debug!("Request {request} took {}ms", request * 12);
+    }
+    warn!("Cache is almost full");
+    error!("Upstream service unavailable");
+    trace!("Not written, so not counted either");
+
+    println!("\nRecords written per level:");
+    for level in Level::iter() {
+        let count = COUNTS[level as usize - 1].load(Ordering::Relaxed);
+        println!("  {level:<5} {count}");
+    }
+}
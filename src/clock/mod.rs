@@ -0,0 +1,366 @@
+//! Wall-clock time for timestamps, file names, rotation and metadata.
+//!
+//! With the `chrono` feature (enabled by default), [`Timestamp`] is a thin
+//! wrapper around a local `chrono` date-time. Without it, a minimal built-in
+//! implementation works in UTC and understands a small subset of `strftime`
+//! specifiers, which is enough for the logger's own formats.
+//!
+//! The [`Clock`] trait lets a [`LogFormatter`](crate::LogFormatter) read the
+//! time from somewhere other than the system clock, e.g. a fixed time in tests.
+
+use crate::config::SecondsPrecision;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, FixedOffset, Local, NaiveTime, SecondsFormat, Utc};
+use std::time::SystemTime;
+#[cfg(not(feature = "chrono"))]
+use std::time::UNIX_EPOCH;
+
+/// Source of the current time for log timestamps.
+///
+/// The formatter asks its clock for the time of every record, see
+/// [`LogFormatter::with_clock`](crate::LogFormatter::with_clock). The time
+/// is rendered in the configured [`TimeZoneMode`](crate::TimeZoneMode).
+///
+/// # Example
+///
+/// ```
+/// use fstdout_logger::Clock;
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+///
+/// /// Always 2024-06-01 12:00:00 UTC
+/// struct FixedClock;
+///
+/// impl Clock for FixedClock {
+///     fn now(&self) -> SystemTime {
+///         UNIX_EPOCH + Duration::from_secs(1_717_243_200)
+///     }
+/// }
+/// ```
+pub trait Clock: Send + Sync {
+    /// Get the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// The system's real-time clock, used by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A point in wall-clock time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Timestamp(
+    #[cfg(feature = "chrono")] DateTime<FixedOffset>,
+    #[cfg(not(feature = "chrono"))] SystemTime,
+);
+
+#[cfg(feature = "chrono")]
+impl Timestamp {
+    /// Get the current local time.
+    pub(crate) fn now() -> Self {
+        Self(Local::now().fixed_offset())
+    }
+
+    /// Convert a system time to local time, or to UTC if `utc` is set.
+    pub(crate) fn from_system_time(time: SystemTime, utc: bool) -> Self {
+        if utc {
+            Self(DateTime::<Utc>::from(time).fixed_offset())
+        } else {
+            Self(DateTime::<Local>::from(time).fixed_offset())
+        }
+    }
+
+    /// Format the time with a `chrono` format string.
+    pub(crate) fn format(&self, format: &str) -> String {
+        self.0.format(format).to_string()
+    }
+
+    /// Format the time as RFC 3339 with nanoseconds and the UTC offset.
+    pub(crate) fn to_rfc3339(self) -> String {
+        self.0.to_rfc3339()
+    }
+
+    /// Format the time as RFC 3339 with as few fractional digits as needed
+    /// and `Z` for UTC.
+    pub(crate) fn to_rfc3339_short(self) -> String {
+        self.0.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    /// Format the time as RFC 3339 with milliseconds and `Z` for UTC.
+    #[cfg(feature = "syslog")]
+    pub(crate) fn to_rfc3339_millis(self) -> String {
+        self.0.to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    /// Format the time as RFC 3339 with the given precision and `Z` for UTC.
+    pub(crate) fn to_rfc3339_precise(self, precision: SecondsPrecision) -> String {
+        let format = match precision {
+            SecondsPrecision::Seconds => SecondsFormat::Secs,
+            SecondsPrecision::Millis => SecondsFormat::Millis,
+            SecondsPrecision::Micros => SecondsFormat::Micros,
+            SecondsPrecision::Nanos => SecondsFormat::Nanos,
+        };
+        self.0.to_rfc3339_opts(format, true)
+    }
+
+    /// Get the number of whole seconds since the Unix epoch.
+    pub(crate) fn unix_seconds(&self) -> i64 {
+        self.0.timestamp()
+    }
+
+    /// Get the time of day.
+    pub(crate) fn time(&self) -> NaiveTime {
+        self.0.time()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime<Local>> for Timestamp {
+    fn from(time: DateTime<Local>) -> Self {
+        Self(time.fixed_offset())
+    }
+}
+
+#[cfg(all(test, feature = "chrono"))]
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        Self::from_system_time(time, false)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for DateTime<FixedOffset> {
+    fn from(time: Timestamp) -> Self {
+        time.0
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for SystemTime {
+    fn from(time: Timestamp) -> Self {
+        SystemTime::from(time.0)
+    }
+}
+
+#[cfg(not(feature = "chrono"))]
+impl Timestamp {
+    /// Get the current time.
+    pub(crate) fn now() -> Self {
+        Self(SystemTime::now())
+    }
+
+    /// Convert a system time, which is always shown in UTC without `chrono`.
+    pub(crate) fn from_system_time(time: SystemTime, _utc: bool) -> Self {
+        Self(time)
+    }
+
+    /// Format the time in UTC with a `strftime`-like format.
+    ///
+    /// Only the specifiers accepted by [`is_valid_format`] are replaced;
+    /// any other `%` sequence is copied as is.
+    pub(crate) fn format(&self, format: &str) -> String {
+        let elapsed = self.0.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = elapsed.as_secs() as i64;
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let second_of_day = seconds.rem_euclid(86_400);
+        let (hour, minute, second) = (
+            second_of_day / 3600,
+            second_of_day / 60 % 60,
+            second_of_day % 60,
+        );
+
+        let mut output = String::new();
+        let mut rest = format;
+        while let Some(start) = rest.find('%') {
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let (text, len) = match SPECIFIERS.iter().find(|s| rest.starts_with(**s)) {
+                Some(&"%Y") => (format!("{year:04}"), 2),
+                Some(&"%m") => (format!("{month:02}"), 2),
+                Some(&"%d") => (format!("{day:02}"), 2),
+                Some(&"%H") => (format!("{hour:02}"), 2),
+                Some(&"%M") => (format!("{minute:02}"), 2),
+                Some(&"%S") => (format!("{second:02}"), 2),
+                Some(&"%F") => (format!("{year:04}-{month:02}-{day:02}"), 2),
+                Some(&"%T") => (format!("{hour:02}:{minute:02}:{second:02}"), 2),
+                Some(&"%.3f") => (format!(".{:03}", elapsed.subsec_millis()), 4),
+                Some(_) => ("%".to_string(), 2),
+                None => ("%".to_string(), 1),
+            };
+            output.push_str(&text);
+            rest = &rest[len.min(rest.len())..];
+        }
+        output.push_str(rest);
+        output
+    }
+
+    /// Format the time as RFC 3339 in UTC, with milliseconds.
+    pub(crate) fn to_rfc3339(self) -> String {
+        self.format("%Y-%m-%dT%H:%M:%S%.3fZ")
+    }
+
+    /// Format the time as RFC 3339, the same as [`Timestamp::to_rfc3339`].
+    pub(crate) fn to_rfc3339_short(self) -> String {
+        self.to_rfc3339()
+    }
+
+    /// Format the time as RFC 3339, the same as [`Timestamp::to_rfc3339`].
+    #[cfg(feature = "syslog")]
+    pub(crate) fn to_rfc3339_millis(self) -> String {
+        self.to_rfc3339()
+    }
+
+    /// Format the time as RFC 3339 in UTC with the given precision.
+    pub(crate) fn to_rfc3339_precise(self, precision: SecondsPrecision) -> String {
+        let nanos = self
+            .0
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos();
+        let mut output = self.format("%Y-%m-%dT%H:%M:%S");
+        let digits = match precision {
+            SecondsPrecision::Seconds => 0,
+            SecondsPrecision::Millis => 3,
+            SecondsPrecision::Micros => 6,
+            SecondsPrecision::Nanos => 9,
+        };
+        if digits > 0 {
+            let fraction = format!("{nanos:09}");
+            output.push('.');
+            output.push_str(&fraction[..digits]);
+        }
+        output.push('Z');
+        output
+    }
+
+    /// Get the number of whole seconds since the Unix epoch.
+    pub(crate) fn unix_seconds(&self) -> i64 {
+        self.0
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64)
+    }
+}
+
+#[cfg(all(test, not(feature = "chrono")))]
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        Self::from_system_time(time, true)
+    }
+}
+
+#[cfg(not(feature = "chrono"))]
+impl From<Timestamp> for SystemTime {
+    fn from(time: Timestamp) -> Self {
+        time.0
+    }
+}
+
+/// Specifiers understood by the built-in formatter.
+#[cfg(not(feature = "chrono"))]
+const SPECIFIERS: [&str; 10] = ["%Y", "%m", "%d", "%H", "%M", "%S", "%F", "%T", "%.3f", "%%"];
+
+/// Check that a format only uses valid specifiers.
+#[cfg(feature = "chrono")]
+pub(crate) fn is_valid_format(format: &str) -> bool {
+    chrono::format::StrftimeItems::new(format).parse().is_ok()
+}
+
+/// Check that a format only uses specifiers the built-in formatter supports.
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn is_valid_format(format: &str) -> bool {
+    let mut rest = format;
+    while let Some(start) = rest.find('%') {
+        rest = &rest[start..];
+        match SPECIFIERS.iter().find(|s| rest.starts_with(**s)) {
+            Some(specifier) => rest = &rest[specifier.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Check whether a time format shows fractions of a second.
+#[cfg(feature = "chrono")]
+pub(crate) fn has_subsecond_precision(format: &str) -> bool {
+    use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+
+    StrftimeItems::new(format).any(|item| {
+        matches!(
+            item,
+            Item::Numeric(Numeric::Nanosecond, _)
+                | Item::Fixed(
+                    Fixed::Nanosecond
+                        | Fixed::Nanosecond3
+                        | Fixed::Nanosecond6
+                        | Fixed::Nanosecond9
+                        | Fixed::RFC3339
+                        // `%3f`, `%6f` and `%9f`
+                        | Fixed::Internal(_)
+                )
+        )
+    })
+}
+
+/// Check whether a time format shows fractions of a second.
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn has_subsecond_precision(format: &str) -> bool {
+    format.contains("%.3f")
+}
+
+/// Convert a number of days since 1970-01-01 to a `(year, month, day)` date.
+///
+/// This is the proleptic Gregorian calendar algorithm by Howard Hinnant.
+#[cfg(not(feature = "chrono"))]
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_validation() {
+        assert!(is_valid_format("%Y-%m-%d %H:%M:%S%.3f"));
+        assert!(is_valid_format("100%% at %T"));
+        assert!(!is_valid_format("%H:%Q"));
+        assert!(has_subsecond_precision("%T%.3f"));
+        assert!(!has_subsecond_precision("%F %T"));
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[test]
+    fn test_builtin_formatting() {
+        // 2024-02-29 23:59:58.042 UTC
+        let time =
+            Timestamp::from(UNIX_EPOCH + std::time::Duration::from_millis(1_709_251_198_042));
+
+        assert_eq!(time.format("%F %T%.3f"), "2024-02-29 23:59:58.042");
+        assert_eq!(
+            time.format("%d/%m/%Y %H:%M:%S 100%%"),
+            "29/02/2024 23:59:58 100%"
+        );
+        assert_eq!(time.to_rfc3339(), "2024-02-29T23:59:58.042Z");
+        assert_eq!(time.unix_seconds(), 1_709_251_198);
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+}
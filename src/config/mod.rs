@@ -3,7 +3,572 @@
 //! This module provides the [`LoggerConfig`] struct and [`LoggerConfigBuilder`]
 //! for configuring the behavior of the logger.
 
-use log::LevelFilter;
+#[cfg(feature = "chrono")]
+use chrono::NaiveTime;
+use log::{Level, LevelFilter, Record};
+#[cfg(feature = "regex")]
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::SyncSender;
+use std::thread::LocalKey;
+use std::time::Duration;
+
+use crate::LogError;
+use crate::clock::Timestamp;
+use crate::formatter::ColorScheme;
+
+/// Time-based rotation policy for log files.
+///
+/// When a boundary is crossed, the active file (e.g. `app.log`) is renamed
+/// to a date-stamped file for the period it covered (e.g. `app-2024-06-01.log`)
+/// and a fresh `app.log` is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationPolicy {
+    /// Never rotate the log file
+    #[default]
+    Never,
+
+    /// Rotate when the local date changes (`app-YYYY-MM-DD.log`)
+    Daily,
+
+    /// Rotate when the local hour changes (`app-YYYY-MM-DD-HH.log`)
+    Hourly,
+}
+
+impl RotationPolicy {
+    /// Get the label of the rotation period containing `time`.
+    ///
+    /// Two timestamps belong to the same period when their labels are equal.
+    /// Returns `None` for [`RotationPolicy::Never`].
+    pub(crate) fn period_label(&self, time: &Timestamp) -> Option<String> {
+        match self {
+            Self::Never => None,
+            Self::Daily => Some(time.format("%Y-%m-%d")),
+            Self::Hourly => Some(time.format("%Y-%m-%d-%H")),
+        }
+    }
+}
+
+/// What happens to an existing log file when the logger opens it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileMode {
+    /// Archive the previous contents to a `.tar.xz` file, then start empty
+    #[default]
+    Archive,
+
+    /// Keep the previous contents and append new records to them
+    Append,
+
+    /// Discard the previous contents without archiving them
+    Truncate,
+}
+
+/// When file writes are synced to disk with `fsync`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsyncPolicy {
+    /// Leave syncing to the operating system
+    #[default]
+    Never,
+
+    /// Sync after every `Error` record
+    OnError,
+
+    /// Sync after every record
+    Always,
+}
+
+/// What a log call does when the queue of an asynchronous file writer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackpressurePolicy {
+    /// Wait until the writer thread has made room
+    #[default]
+    Block,
+
+    /// Drop the line and report it to the error handler
+    Drop,
+}
+
+impl FsyncPolicy {
+    /// Check whether a record at `level` must be synced to disk.
+    pub(crate) fn applies_to(&self, level: Level) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnError => level == Level::Error,
+            Self::Always => true,
+        }
+    }
+}
+
+/// Limits on rotated log files, applied after every rotation.
+///
+/// Every limit is optional and all set limits apply at once, so whichever is
+/// hit first decides. Rotated files are considered newest first; once a file
+/// breaks the count or total size limit, it and all older files are deleted.
+/// Only files named like the logger's own rotated files (`app-2024-06-01.log`
+/// for `app.log`) are counted and deleted; other files in the directory,
+/// such as `app-server.log`, are left alone.
+///
+/// # Example
+///
+/// ```
+/// use fstdout_logger::{LoggerConfig, RetentionPolicy, RotationPolicy};
+/// use std::time::Duration;
+///
+/// // Keep at most 10 files of up to 100 MB each, none older than 30 days
+/// let config = LoggerConfig::builder()
+///     .rotation(RotationPolicy::Daily)
+///     .retention(RetentionPolicy {
+///         max_files: Some(10),
+///         max_file_size: Some(100 * 1024 * 1024),
+///         max_age: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+///         max_total_size: None,
+///     })
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    /// Maximum number of rotated files to keep
+    pub max_files: Option<usize>,
+
+    /// Size in bytes at which the active file is rotated, even mid-period
+    pub max_file_size: Option<u64>,
+
+    /// Maximum age of rotated files, based on their modification time
+    pub max_age: Option<Duration>,
+
+    /// Maximum combined size in bytes of the active and rotated files
+    pub max_total_size: Option<u64>,
+}
+
+/// Output format used for log file lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum FileFormat {
+    /// Human-readable text: `[YYYY-MM-DD HH:MM:SS LEVEL file:line] message`
+    #[default]
+    Text,
+
+    /// One JSON object per line with `timestamp`, `level`, `target`, `file`,
+    /// `line` and `message` keys, plus record key-values under `fields`
+    /// (a `_type` key-value is promoted to a top-level `type` key) and
+    /// `level_num` if numeric levels are enabled
+    Json,
+
+    /// Both representations for every record: the text line prefixed with
+    /// `TXT: ` followed by the JSON line prefixed with `JSN: `
+    Dual,
+
+    /// Flat `key=value` pairs in the style of logfmt: `time`, `level`,
+    /// `target`, `file`, `line` and `msg`, followed by the record key-values.
+    /// The assignment and pair separators are configurable.
+    Logfmt,
+}
+
+/// Stream that warnings and errors are written to on the console.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorStream {
+    /// Write all records to stdout
+    #[default]
+    Stdout,
+
+    /// Write `Warn` and `Error` records to stderr, everything else to stdout
+    Stderr,
+}
+
+/// How the logger decides whether to emit colors on the console.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// Follow the `use_colors` setting
+    #[default]
+    Explicit,
+
+    /// Emit colors only if the output stream is a terminal
+    Auto,
+}
+
+/// Weight applied to all level labels in colored output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorIntensity {
+    /// Use the weight set per level in the color scheme
+    #[default]
+    Scheme,
+
+    /// Plain colors, never bold
+    Normal,
+
+    /// Bold colors for every level, for higher contrast
+    Bold,
+
+    /// Dimmed colors for every level, for a softer look
+    Dim,
+}
+
+/// Length of the level labels in text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LevelStyle {
+    /// The full level name, e.g. `ERROR` or `INFO`
+    #[default]
+    Full,
+
+    /// Three-letter abbreviations: `ERR`, `WRN`, `INF`, `DBG` and `TRC`
+    Short,
+
+    /// A single letter: `E`, `W`, `I`, `D` and `T`
+    Single,
+}
+
+impl LevelStyle {
+    /// Get the label of `level` in this style.
+    pub(crate) fn label(&self, level: Level) -> &'static str {
+        match (self, level) {
+            (Self::Full, level) => level.as_str(),
+            (Self::Short, Level::Error) => "ERR",
+            (Self::Short, Level::Warn) => "WRN",
+            (Self::Short, Level::Info) => "INF",
+            (Self::Short, Level::Debug) => "DBG",
+            (Self::Short, Level::Trace) => "TRC",
+            (Self::Single, Level::Error) => "E",
+            (Self::Single, Level::Warn) => "W",
+            (Self::Single, Level::Info) => "I",
+            (Self::Single, Level::Debug) => "D",
+            (Self::Single, Level::Trace) => "T",
+        }
+    }
+
+    /// Get the length of the longest label in this style.
+    pub(crate) fn width(&self) -> usize {
+        match self {
+            Self::Full => 5,
+            Self::Short => 3,
+            Self::Single => 1,
+        }
+    }
+}
+
+/// Part of a stdout line that is painted in the level's color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorStyle {
+    /// Only the level label, with the timestamp and file info dimmed
+    #[default]
+    LevelOnly,
+
+    /// The whole line, for maximum visibility
+    FullLine,
+}
+
+/// Time zone used for log timestamps.
+///
+/// The default is `Local` with the `chrono` feature and `Utc` without it,
+/// as the built-in clock can't read the local time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeZoneMode {
+    /// Use the local time zone of the machine (requires the `chrono` feature)
+    #[cfg_attr(feature = "chrono", default)]
+    Local,
+
+    /// Use UTC, marked with a `Z` suffix in file output
+    #[cfg_attr(not(feature = "chrono"), default)]
+    Utc,
+}
+
+/// Source of the timestamps in text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampSource {
+    /// Wall-clock time, formatted with the configured time formats
+    #[default]
+    WallClock,
+
+    /// Monotonic time elapsed since the logger was created, e.g. `+1.234s`
+    SinceStart,
+}
+
+/// Number of fractional second digits in RFC 3339 timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecondsPrecision {
+    /// Whole seconds, e.g. `12:00:00+02:00`
+    Seconds,
+
+    /// Milliseconds, e.g. `12:00:00.123+02:00`
+    #[default]
+    Millis,
+
+    /// Microseconds, e.g. `12:00:00.123456+02:00`
+    Micros,
+
+    /// Nanoseconds, e.g. `12:00:00.123456789+02:00`
+    Nanos,
+}
+
+/// Rendering of `Duration` key-value values in JSON and logfmt output.
+///
+/// Durations captured with `:?` (e.g. `info!(elapsed:? = start.elapsed(); "done")`)
+/// are written in their `Debug` form by default, such as `1.500023s` or
+/// `250.4µs`. The other variants normalize them to a single unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DurationFormat {
+    /// Keep the `Debug` form of the value
+    #[default]
+    Debug,
+
+    /// Seconds with up to three decimals, e.g. `1.5s`
+    Seconds,
+
+    /// Milliseconds with up to three decimals, e.g. `1500ms`
+    Millis,
+}
+
+/// Line ending of the records written to log files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`, as used on Unix
+    #[default]
+    Lf,
+
+    /// `\r\n`, as expected by Windows tools like Notepad
+    CrLf,
+
+    /// `\r\n` on Windows and `\n` elsewhere
+    Platform,
+}
+
+impl LineEnding {
+    /// Check whether lines end with `\r\n` on the current platform.
+    fn is_crlf(self) -> bool {
+        match self {
+            Self::Lf => false,
+            Self::CrLf => true,
+            Self::Platform => cfg!(windows),
+        }
+    }
+}
+
+/// Scale used to map log levels to numeric codes in structured output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LevelScale {
+    /// Ascending severity: `Trace` = 0, `Debug` = 1, `Info` = 2, `Warn` = 3, `Error` = 4
+    #[default]
+    Ascending,
+
+    /// Syslog severities: `Error` = 3, `Warn` = 4, `Info` = 6, `Debug` and `Trace` = 7
+    Syslog,
+}
+
+impl LevelScale {
+    /// Get the numeric code of `level` on this scale.
+    pub(crate) fn code(&self, level: Level) -> u8 {
+        match (self, level) {
+            (Self::Ascending, Level::Trace) => 0,
+            (Self::Ascending, Level::Debug) => 1,
+            (Self::Ascending, Level::Info) => 2,
+            (Self::Ascending, Level::Warn) => 3,
+            (Self::Ascending, Level::Error) => 4,
+            (Self::Syslog, Level::Error) => 3,
+            (Self::Syslog, Level::Warn) => 4,
+            (Self::Syslog, Level::Info) => 6,
+            (Self::Syslog, Level::Debug | Level::Trace) => 7,
+        }
+    }
+}
+
+/// Where and how records are sent to a syslog daemon.
+///
+/// Messages are formatted according to RFC 5424, with the priority derived
+/// from `facility` and the record level (`Error` = 3, `Warn` = 4, `Info` = 6,
+/// `Debug` and `Trace` = 7).
+///
+/// # Example
+///
+/// ```
+/// use fstdout_logger::{LoggerConfig, SyslogFacility, SyslogTarget, SyslogTransport};
+///
+/// let config = LoggerConfig::builder()
+///     .syslog(Some(SyslogTarget {
+///         facility: SyslogFacility::Daemon,
+///         app_name: "myapp".to_string(),
+///         transport: SyslogTransport::Udp("127.0.0.1:514".parse().unwrap()),
+///     }))
+///     .build();
+/// ```
+#[cfg(feature = "syslog")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyslogTarget {
+    /// Kind of program sending the messages
+    pub facility: SyslogFacility,
+
+    /// Name of the application, the `APP-NAME` field of every message
+    pub app_name: String,
+
+    /// Socket the messages are sent over
+    pub transport: SyslogTransport,
+}
+
+/// Syslog facility, the kind of program sending a message.
+#[cfg(feature = "syslog")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyslogFacility {
+    /// User-level messages (1)
+    #[default]
+    User,
+
+    /// System daemons (3)
+    Daemon,
+
+    /// Locally defined facilities `local0` to `local7` (16 to 23)
+    Local(u8),
+}
+
+#[cfg(feature = "syslog")]
+impl SyslogFacility {
+    /// Get the numeric code of the facility.
+    ///
+    /// Local facilities above `local7` are clamped to it.
+    pub(crate) fn code(&self) -> u8 {
+        match self {
+            Self::User => 1,
+            Self::Daemon => 3,
+            Self::Local(n) => 16 + (*n).min(7),
+        }
+    }
+}
+
+/// Socket used to reach the syslog daemon.
+#[cfg(feature = "syslog")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyslogTransport {
+    /// UDP datagrams to the given address, usually port 514
+    Udp(std::net::SocketAddr),
+
+    /// Datagrams to a Unix domain socket, usually `/dev/log`
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Callback invoked when writing to or flushing the log file fails.
+///
+/// The logger never fails a log call because of an I/O error. Without a
+/// handler such errors are dropped silently; with one, the application can
+/// count them or warn about them.
+///
+/// # Example
+///
+/// ```
+/// use fstdout_logger::{ErrorHandler, LoggerConfig};
+///
+/// let config = LoggerConfig::builder()
+///     .on_error(ErrorHandler::new(|error| eprintln!("log file error: {error}")))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct ErrorHandler(Arc<dyn Fn(&LogError) + Send + Sync>);
+
+impl ErrorHandler {
+    /// Wrap a callback to be used as an error handler.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&LogError) + Send + Sync + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    /// Pass an error to the callback.
+    ///
+    /// Errors raised while the callback is already running on this thread,
+    /// e.g. because it logs a message that fails to be written again, are
+    /// dropped instead of recursing.
+    pub(crate) fn report(&self, error: LogError) {
+        thread_local! {
+            static REPORTING: Cell<bool> = const { Cell::new(false) };
+        }
+
+        if let Some(_reporting) = ReentryGuard::enter(&REPORTING) {
+            (self.0)(&error);
+        }
+    }
+}
+
+impl fmt::Debug for ErrorHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ErrorHandler(..)")
+    }
+}
+
+/// Callback invoked for every record the logger writes.
+///
+/// # Example
+///
+/// ```
+/// use fstdout_logger::{LoggerConfig, RecordObserver};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// static RECORDS: AtomicU64 = AtomicU64::new(0);
+///
+/// let config = LoggerConfig::builder()
+///     .observer(RecordObserver::new(|_record| {
+///         RECORDS.fetch_add(1, Ordering::Relaxed);
+///     }))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct RecordObserver(Arc<dyn Fn(&Record) + Send + Sync>);
+
+impl RecordObserver {
+    /// Wrap a callback to be used as a record observer.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&Record) + Send + Sync + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    /// Pass a record to the callback.
+    ///
+    /// Records logged while the callback is already running on this thread
+    /// are written without being observed, instead of recursing.
+    pub(crate) fn observe(&self, record: &Record) {
+        thread_local! {
+            static OBSERVING: Cell<bool> = const { Cell::new(false) };
+        }
+
+        if let Some(_observing) = ReentryGuard::enter(&OBSERVING) {
+            (self.0)(record);
+        }
+    }
+}
+
+/// Marks a callback as running on this thread until dropped.
+///
+/// The flag is cleared even if the callback panics, so a panic caught
+/// further up doesn't disable the callback for the rest of the thread.
+struct ReentryGuard(&'static LocalKey<Cell<bool>>);
+
+impl ReentryGuard {
+    /// Set the flag, or return `None` if it is already set.
+    fn enter(flag: &'static LocalKey<Cell<bool>>) -> Option<Self> {
+        (!flag.replace(true)).then_some(Self(flag))
+    }
+}
+
+impl Drop for ReentryGuard {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+impl fmt::Debug for RecordObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RecordObserver(..)")
+    }
+}
 
 /// Configuration for the logger.
 ///
@@ -31,176 +596,2381 @@ use log::LevelFilter;
 /// // Create using presets
 /// let prod_config = LoggerConfig::production();
 /// let dev_config = LoggerConfig::development();
+/// let compact_config = LoggerConfig::compact();
 /// ```
 #[derive(Debug, Clone)]
 pub struct LoggerConfig {
     /// Whether to show file and line information in log messages
     pub show_file_info: bool,
 
-    /// Whether to show date in stdout logs (always shown in file logs)
-    pub show_date_in_stdout: bool,
+    /// Text shown in place of the file of records without one
+    pub missing_file_placeholder: String,
+
+    /// Text shown in place of the line of records without one
+    pub missing_line_placeholder: String,
+
+    /// Whether to leave out the file info of records without a file
+    pub skip_file_info_when_missing: bool,
+
+    /// Whether to show the target (usually the module path) in text logs
+    pub show_target: bool,
+
+    /// Whether to show the module path of each record in text file logs
+    pub show_module_path: bool,
+
+    /// Text opening the prefix of text log lines
+    pub prefix_open: String,
+
+    /// Text closing the prefix of text log lines
+    pub prefix_close: String,
+
+    /// Text between the prefix and the message in text log lines
+    pub prefix_message_separator: String,
+
+    /// Whether to show date in stdout logs (always shown in file logs)
+    pub show_date_in_stdout: bool,
+
+    /// Whether to pad level labels to a common width in text output
+    pub align_levels: bool,
+
+    /// Length of the level labels in stdout logs
+    pub level_style: LevelStyle,
+
+    /// Length of the level labels in text file logs
+    pub file_level_style: LevelStyle,
+
+    /// Icons shown before the level label in stdout logs
+    pub level_icons: Option<HashMap<Level, String>>,
+
+    /// Whether to show the process ID in file logs
+    pub show_pid: bool,
+
+    /// Whether to show the process ID in stdout logs
+    pub show_pid_in_stdout: bool,
+
+    /// Whether to number records with a sequence number
+    pub show_sequence: bool,
+
+    /// Minimum number of digits in sequence numbers, padded with zeros
+    pub sequence_width: usize,
+
+    /// Whether to use colors in stdout logs
+    pub use_colors: bool,
+
+    /// Levels whose records are colored when colors are enabled
+    pub color_levels: Vec<Level>,
+
+    /// Whether colors follow `use_colors` or are detected from the terminal
+    pub color_mode: ColorMode,
+
+    /// Colors used for each level label in stdout logs
+    pub color_scheme: ColorScheme,
+
+    /// Weight applied to all level labels, overriding the color scheme
+    pub color_intensity: ColorIntensity,
+
+    /// Whether the level's color applies to the label or the whole line
+    pub color_style: ColorStyle,
+
+    /// Whether to disable colors when running under `cargo test`
+    pub detect_test_mode: bool,
+
+    /// Whether to strip escape sequences from console output that isn't a terminal
+    pub strip_colors_on_redirect: bool,
+
+    /// Minimum log level to display
+    pub level: LevelFilter,
+
+    /// Per-module levels as `(target prefix, level)` pairs, overriding `level`
+    pub module_levels: Vec<(String, LevelFilter)>,
+
+    /// Daily `(start, end)` time windows during which `quiet_level` applies
+    #[cfg(feature = "chrono")]
+    pub quiet_windows: Vec<(NaiveTime, NaiveTime)>,
+
+    /// Most verbose level shown during a quiet window
+    #[cfg(feature = "chrono")]
+    pub quiet_level: LevelFilter,
+
+    /// Whether the `FSTDOUT_MAX_LEVEL` environment variable caps the level
+    pub respect_env_level: bool,
+
+    /// Window in which consecutive identical messages are collapsed into one
+    pub dedup_window: Option<Duration>,
+
+    /// Custom `chrono` format string for stdout timestamps
+    pub stdout_time_format: Option<String>,
+
+    /// Custom `chrono` format string for file timestamps
+    pub file_time_format: Option<String>,
+
+    /// Precision of RFC 3339 file timestamps, if file timestamps use RFC 3339
+    pub file_rfc3339: Option<SecondsPrecision>,
+
+    /// Time zone used for timestamps
+    pub timezone: TimeZoneMode,
+
+    /// Whether text timestamps show wall-clock time or time since start
+    pub timestamp_source: TimestampSource,
+
+    /// Whether second-resolution timestamps get a counter ordering records within a second
+    pub intra_second_counter: bool,
+
+    /// Whether text lines show the time elapsed since the previous record
+    pub show_delta: bool,
+
+    /// Custom layout of stdout lines, e.g. `"{time} {level} {target} - {message}"`
+    pub stdout_template: Option<String>,
+
+    /// Custom layout of text file lines
+    pub file_template: Option<String>,
+
+    /// Additional log files, each with the most verbose level written to it
+    pub files: Vec<(PathBuf, LevelFilter)>,
+
+    /// Additional log files, each with the most verbose and the most severe
+    /// level written to it
+    pub file_ranges: Vec<(PathBuf, LevelFilter, Level)>,
+
+    /// What happens to an existing log file when the logger opens it
+    pub file_mode: FileMode,
+
+    /// Whether log files are locked against other processes when opened
+    pub file_locking: bool,
+
+    /// How long to wait for another process to release a log file's lock
+    pub file_lock_timeout: Duration,
+
+    /// Whether log files are opened on their first write instead of at init
+    pub lazy_open: bool,
+
+    /// Whether file writes are buffered instead of written through immediately
+    pub buffered: bool,
+
+    /// How often a background thread flushes buffered file writes
+    pub flush_interval: Option<Duration>,
+
+    /// Which records are synced to disk after being written
+    pub fsync: FsyncPolicy,
+
+    /// Whether file writes are handed to a background writer thread
+    pub async_file: bool,
+
+    /// Number of lines queued for the writer thread before backpressure applies
+    pub async_file_capacity: usize,
+
+    /// What happens to a line when the writer thread's queue is full
+    pub async_file_backpressure: BackpressurePolicy,
+
+    /// Callback invoked when writing to or flushing the log file fails
+    pub on_error: Option<ErrorHandler>,
+
+    /// Callback invoked for every record that is written
+    pub observer: Option<RecordObserver>,
+
+    /// When the log file is rolled over to a date-stamped file
+    pub rotation: RotationPolicy,
+
+    /// Limits on the size and number of rotated files
+    pub retention: RetentionPolicy,
+
+    /// Format of the lines written to the log file
+    pub file_format: FileFormat,
+
+    /// Scale of the numeric `level_num` field in JSON output, if written at all
+    pub numeric_levels: Option<LevelScale>,
+
+    /// String between a key and its value in logfmt output
+    pub kv_separator: String,
+
+    /// String between two key-value pairs in logfmt output
+    pub kv_pair_delimiter: String,
+
+    /// Rendering of `Duration` key-value values in structured output
+    pub duration_format: DurationFormat,
+
+    /// Key-value pairs added to every record
+    pub global_fields: Vec<(String, String)>,
+
+    /// Character that replaces line breaks inside messages in text output
+    pub collapse_newlines: Option<char>,
+
+    /// Delimiter written after every record in stdout and file output
+    pub record_separator: String,
+
+    /// Line ending of the records in log files
+    pub line_ending: LineEnding,
+
+    /// Patterns whose matches in messages are replaced by `***REDACTED***`
+    #[cfg(feature = "regex")]
+    pub redactions: Vec<Regex>,
+
+    /// Maximum length of a message in bytes before it is truncated
+    pub max_message_len: Option<usize>,
+
+    /// Maximum message length for file output, if different from `max_message_len`
+    pub max_file_message_len: Option<usize>,
+
+    /// Whether to turn `file:line` in colored stdout logs into a clickable OSC 8 hyperlink
+    pub file_hyperlinks: bool,
+
+    /// URL template for file hyperlinks, with `{path}` and `{line}` placeholders
+    pub hyperlink_template: Option<String>,
+
+    /// Whether records are written to the console at all
+    pub stdout_enabled: bool,
+
+    /// Console stream that warnings and errors are written to
+    pub error_stream: ErrorStream,
+
+    /// Whether console lines start with a `<N>` severity prefix for the systemd journal
+    pub systemd_stdout: bool,
+
+    /// Whether to record rotated files in a `<file>.index` JSON-lines index
+    pub write_index: bool,
+
+    /// Whether to describe the logging session in a `<file>.meta.json` sidecar
+    pub write_metadata_sidecar: bool,
+
+    /// Version of the application, recorded in the metadata sidecar and init banner
+    pub app_version: Option<String>,
+
+    /// Whether console output is written straight to the stdout file descriptor
+    pub fast_stdout: bool,
+
+    /// Line written to the log file when appending to a previous, non-empty log
+    pub restart_separator: Option<String>,
+
+    /// Whether to log a one-line summary of the configuration at init
+    pub log_startup_banner: bool,
+
+    /// Line written to each log file right after it is opened, if any
+    pub init_banner: Option<String>,
+
+    /// Whether the init banner is also printed to the console
+    pub init_banner_stdout: bool,
+
+    /// Separate file that audit events are written to instead of the log file
+    pub audit_file: Option<PathBuf>,
+
+    /// Number of recent formatted lines kept in memory, if any
+    pub ring_buffer_capacity: Option<usize>,
+
+    /// Channel that receives every formatted stdout line, if any
+    pub channel_sink: Option<SyncSender<String>>,
+
+    /// Syslog daemon that records are also sent to, if any
+    #[cfg(feature = "syslog")]
+    pub syslog: Option<SyslogTarget>,
+}
+
+impl Default for LoggerConfig {
+    /// Creates a default configuration with:
+    /// - `show_file_info`: `true` - Show file/line information
+    /// - `missing_file_placeholder`: `"unknown"` - Shown for records without a file
+    /// - `missing_line_placeholder`: `"0"` - Shown for records without a line
+    /// - `skip_file_info_when_missing`: `false` - Show placeholders instead
+    /// - `show_target`: `false` - No target in text logs
+    /// - `show_module_path`: `false` - No module path in text file logs
+    /// - `prefix_open` / `prefix_close`: `"["` / `"]"` - Brackets around the prefix
+    /// - `prefix_message_separator`: `" "` - A space before the message
+    /// - `show_date_in_stdout`: `false` - Only show time in stdout
+    /// - `align_levels`: `false` - Don't pad level labels
+    /// - `level_style`: `Full` - Full level names in stdout
+    /// - `file_level_style`: `Full` - Full level names in text files
+    /// - `level_icons`: `None` - No icons before level labels
+    /// - `show_pid`: `false` - No process ID in file logs
+    /// - `show_pid_in_stdout`: `false` - No process ID in stdout
+    /// - `show_sequence`: `false` - No sequence numbers
+    /// - `sequence_width`: `6` - Sequence numbers padded to six digits
+    /// - `use_colors`: `true` - Use colors in stdout output
+    /// - `color_levels`: all levels - Color records of every level
+    /// - `color_mode`: `Explicit` - Follow `use_colors`
+    /// - `color_scheme`: default - Red errors, yellow warnings, blue info, green debug
+    /// - `color_intensity`: `Scheme` - Bold as set in the color scheme
+    /// - `color_style`: `LevelOnly` - Color only the level label
+    /// - `detect_test_mode`: `true` - Disable colors under `cargo test`
+    /// - `strip_colors_on_redirect`: `false` - Keep escape sequences in redirected output
+    /// - `level`: `Info` - Only show Info level and above
+    /// - `module_levels`: empty - Apply `level` to every module
+    /// - `quiet_windows`: empty - No quiet periods
+    /// - `quiet_level`: `Off` - Suppress all logs during quiet windows
+    /// - `respect_env_level`: `false` - Ignore `FSTDOUT_MAX_LEVEL`
+    /// - `dedup_window`: `None` - Log every repeated message
+    /// - `stdout_time_format`: `None` - `%H:%M:%S` (or with date, see `show_date_in_stdout`)
+    /// - `file_time_format`: `None` - `%Y-%m-%d %H:%M:%S`
+    /// - `file_rfc3339`: `None` - Use `file_time_format` in text files
+    /// - `timezone`: `Local` - Use local time
+    /// - `timestamp_source`: `WallClock` - Show the time of day
+    /// - `intra_second_counter`: `false` - Timestamps end at the second
+    /// - `show_delta`: `false` - No time since the previous record
+    /// - `stdout_template`: `None` - `[time level file:line] message`
+    /// - `file_template`: `None` - `[time level file:line] message`
+    /// - `files`: empty - Only write to the file given to the constructor
+    /// - `file_ranges`: empty - No files limited to a range of levels
+    /// - `file_mode`: `Archive` - Archive an existing log file before starting a new one
+    /// - `file_locking`: `false` - Don't lock log files
+    /// - `file_lock_timeout`: 5 seconds - Wait for a locked log file this long
+    /// - `lazy_open`: `false` - Open log files when the logger is created
+    /// - `buffered`: `false` - Write every line to the file immediately
+    /// - `flush_interval`: `None` - Flush buffered writes on error records only
+    /// - `fsync`: `Never` - Leave syncing to the operating system
+    /// - `async_file`: `false` - Write to files from the logging thread
+    /// - `async_file_capacity`: 1024 - Queue up to 1024 lines for the writer thread
+    /// - `async_file_backpressure`: `Block` - Wait for room in a full queue
+    /// - `on_error`: `None` - Ignore log file errors
+    /// - `observer`: `None` - Don't observe records
+    /// - `rotation`: `Never` - Keep writing to the same file
+    /// - `retention`: no limits - Keep every rotated file
+    /// - `file_format`: `Text` - Human-readable file lines
+    /// - `numeric_levels`: `None` - Only write the level name
+    /// - `kv_separator`: `"="` - logfmt assignment
+    /// - `kv_pair_delimiter`: `" "` - logfmt pair separator
+    /// - `duration_format`: `Debug` - Write durations as logged
+    /// - `global_fields`: empty - Only write the fields logged with each record
+    /// - `collapse_newlines`: `None` - Keep line breaks inside messages
+    /// - `record_separator`: `"\n"` - One record per line
+    /// - `line_ending`: `Lf` - Unix line endings in log files
+    /// - `redactions`: empty - Write messages unchanged (`regex` feature)
+    /// - `max_message_len`: `None` - Never truncate messages
+    /// - `max_file_message_len`: `None` - Use `max_message_len` for files too
+    /// - `file_hyperlinks`: `false` - Print `file:line` as plain text
+    /// - `hyperlink_template`: `None` - Link to `file://{path}` when hyperlinks are enabled
+    /// - `stdout_enabled`: `true` - Write records to the console
+    /// - `error_stream`: `Stdout` - Write all records to stdout
+    /// - `systemd_stdout`: `false` - No severity prefixes on console lines
+    /// - `write_index`: `false` - Don't keep an index of rotated files
+    /// - `write_metadata_sidecar`: `false` - Don't write session metadata
+    /// - `app_version`: `None` - The application version is unknown
+    /// - `fast_stdout`: `false` - Write through Rust's synchronized stdout
+    /// - `restart_separator`: `None` - Don't mark restarts in the log file
+    /// - `log_startup_banner`: `false` - Don't log the configuration at init
+    /// - `init_banner`: `None` - Don't start log files with a banner
+    /// - `init_banner_stdout`: `false` - Keep the init banner out of the console
+    /// - `audit_file`: `None` - Write audit events to the log file
+    /// - `ring_buffer_capacity`: `None` - Don't keep recent lines in memory
+    /// - `channel_sink`: `None` - Don't send lines to a channel
+    /// - `syslog`: `None` - Don't send records to syslog
+    fn default() -> Self {
+        Self {
+            show_file_info: true,
+            missing_file_placeholder: "unknown".to_string(),
+            missing_line_placeholder: "0".to_string(),
+            skip_file_info_when_missing: false,
+            show_target: false,
+            show_module_path: false,
+            prefix_open: "[".to_string(),
+            prefix_close: "]".to_string(),
+            prefix_message_separator: " ".to_string(),
+            show_date_in_stdout: false,
+            align_levels: false,
+            level_style: LevelStyle::Full,
+            file_level_style: LevelStyle::Full,
+            level_icons: None,
+            show_pid: false,
+            show_pid_in_stdout: false,
+            show_sequence: false,
+            sequence_width: 6,
+            use_colors: true,
+            color_levels: Level::iter().collect(),
+            color_mode: ColorMode::Explicit,
+            color_scheme: ColorScheme::default(),
+            color_intensity: ColorIntensity::Scheme,
+            color_style: ColorStyle::LevelOnly,
+            detect_test_mode: true,
+            strip_colors_on_redirect: false,
+            level: LevelFilter::Info,
+            module_levels: Vec::new(),
+            #[cfg(feature = "chrono")]
+            quiet_windows: Vec::new(),
+            #[cfg(feature = "chrono")]
+            quiet_level: LevelFilter::Off,
+            respect_env_level: false,
+            dedup_window: None,
+            stdout_time_format: None,
+            file_time_format: None,
+            file_rfc3339: None,
+            timezone: TimeZoneMode::default(),
+            timestamp_source: TimestampSource::WallClock,
+            intra_second_counter: false,
+            show_delta: false,
+            stdout_template: None,
+            file_template: None,
+            files: Vec::new(),
+            file_ranges: Vec::new(),
+            file_mode: FileMode::Archive,
+            file_locking: false,
+            file_lock_timeout: Duration::from_secs(5),
+            lazy_open: false,
+            buffered: false,
+            flush_interval: None,
+            fsync: FsyncPolicy::Never,
+            async_file: false,
+            async_file_capacity: 1024,
+            async_file_backpressure: BackpressurePolicy::Block,
+            on_error: None,
+            observer: None,
+            rotation: RotationPolicy::Never,
+            retention: RetentionPolicy::default(),
+            file_format: FileFormat::Text,
+            numeric_levels: None,
+            kv_separator: "=".to_string(),
+            kv_pair_delimiter: " ".to_string(),
+            duration_format: DurationFormat::Debug,
+            global_fields: Vec::new(),
+            collapse_newlines: None,
+            record_separator: "\n".to_string(),
+            line_ending: LineEnding::Lf,
+            #[cfg(feature = "regex")]
+            redactions: Vec::new(),
+            max_message_len: None,
+            max_file_message_len: None,
+            file_hyperlinks: false,
+            hyperlink_template: None,
+            stdout_enabled: true,
+            error_stream: ErrorStream::Stdout,
+            systemd_stdout: false,
+            write_index: false,
+            write_metadata_sidecar: false,
+            app_version: None,
+            fast_stdout: false,
+            restart_separator: None,
+            log_startup_banner: false,
+            init_banner: None,
+            init_banner_stdout: false,
+            audit_file: None,
+            ring_buffer_capacity: None,
+            channel_sink: None,
+            #[cfg(feature = "syslog")]
+            syslog: None,
+        }
+    }
+}
+
+impl LoggerConfig {
+    /// Create a new logger configuration with default settings.
+    ///
+    /// This is equivalent to calling `LoggerConfig::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new configuration builder.
+    ///
+    /// This returns a [`LoggerConfigBuilder`] that can be used to construct
+    /// a custom configuration with a fluent API.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .level(LevelFilter::Debug)
+    ///     .show_file_info(false)
+    ///     .build();
+    /// ```
+    pub fn builder() -> LoggerConfigBuilder {
+        LoggerConfigBuilder::default()
+    }
+
+    /// Create a new configuration optimized for production use.
+    ///
+    /// Production settings:
+    /// - `show_file_info`: `false` - Hide file/line for cleaner logs
+    /// - `show_date_in_stdout`: `false` - Only show time in stdout
+    /// - `use_colors`: `true` - Keep colors for readability
+    /// - `level`: `Info` - Hide Debug/Trace logs in production
+    pub fn production() -> Self {
+        Self {
+            show_file_info: false,
+            show_date_in_stdout: false,
+            use_colors: true,
+            level: LevelFilter::Info,
+            ..Self::default()
+        }
+    }
+
+    /// Create a new configuration optimized for development use.
+    ///
+    /// Development settings:
+    /// - `show_file_info`: `true` - Show file/line for debugging
+    /// - `show_date_in_stdout`: `false` - Only show time in stdout
+    /// - `use_colors`: `true` - Use colors for readability
+    /// - `level`: `Debug` - Show Debug logs (but not Trace)
+    pub fn development() -> Self {
+        Self {
+            show_file_info: true,
+            show_date_in_stdout: false,
+            use_colors: true,
+            level: LevelFilter::Debug,
+            ..Self::default()
+        }
+    }
+
+    /// Create a new configuration for minimal output, e.g. from cron jobs
+    /// whose output is mailed.
+    ///
+    /// Compact settings:
+    /// - `show_file_info`: `false` - Hide file/line
+    /// - `show_date_in_stdout`: `false` - Only show time in stdout
+    /// - `use_colors`: `false` - No escape codes in mails or pipes
+    /// - `level_style`: `Short` - Three-letter level labels in stdout
+    /// - `file_level_style`: `Short` - Three-letter level labels in log files
+    /// - `level`: `Info` - Hide Debug/Trace logs
+    ///
+    /// Stdout lines look like `[12:00:00 INF] message`.
+    pub fn compact() -> Self {
+        Self {
+            show_file_info: false,
+            show_date_in_stdout: false,
+            use_colors: false,
+            level_style: LevelStyle::Short,
+            file_level_style: LevelStyle::Short,
+            level: LevelFilter::Info,
+            ..Self::default()
+        }
+    }
+
+    /// Create a new configuration with icons before the level labels.
+    ///
+    /// Stdout lines are marked with ❌ for errors, ⚠️ for warnings, ℹ️ for
+    /// info, 🐛 for debug and 🔍 for trace records. Everything else is the
+    /// same as in [`LoggerConfig::default()`].
+    pub fn with_default_icons() -> Self {
+        let icons = [
+            (Level::Error, "❌"),
+            (Level::Warn, "⚠️"),
+            (Level::Info, "ℹ️"),
+            (Level::Debug, "🐛"),
+            (Level::Trace, "🔍"),
+        ];
+        Self {
+            level_icons: Some(
+                icons
+                    .into_iter()
+                    .map(|(level, icon)| (level, icon.to_string()))
+                    .collect(),
+            ),
+            ..Self::default()
+        }
+    }
+
+    /// Create a configuration from a `RUST_LOG`-style filter string.
+    ///
+    /// The string is a comma-separated list of directives, as understood by
+    /// `env_logger`:
+    /// - `level` sets the global level (e.g. `info`)
+    /// - `module=level` sets the level for targets starting with `module`
+    /// - `module` alone enables every level for that module
+    ///
+    /// Whitespace around directives is ignored, as are empty segments. If no
+    /// global level is given, the default `Info` is kept. All other options
+    /// use their default values.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The directive string, e.g. `"info,myapp=debug,hyper=warn"`.
+    ///
+    /// # Returns
+    ///
+    /// The parsed configuration, or [`LogError::InvalidFilter`] if a directive
+    /// is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// let config = LoggerConfig::from_env_filter("warn, myapp=debug").unwrap();
+    /// assert_eq!(config.level, LevelFilter::Warn);
+    /// assert_eq!(config.module_levels, vec![("myapp".to_string(), LevelFilter::Debug)]);
+    ///
+    /// assert!(LoggerConfig::from_env_filter("foo=notalevel").is_err());
+    /// ```
+    pub fn from_env_filter(filter: &str) -> Result<Self, LogError> {
+        let mut config = Self::default();
+
+        for directive in filter.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    let module = module.trim();
+                    if module.is_empty() {
+                        return Err(LogError::InvalidFilter(format!(
+                            "missing module name in `{directive}`"
+                        )));
+                    }
+                    let level = parse_level(level.trim(), directive)?;
+                    config.module_levels.push((module.to_string(), level));
+                }
+                // A bare word is either the global level or a module to enable fully
+                None => match directive.parse::<LevelFilter>() {
+                    Ok(level) => config.level = level,
+                    Err(_) => config
+                        .module_levels
+                        .push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Create a configuration and log file path from a TOML document.
+    ///
+    /// Every key is optional and unset keys keep their default value:
+    ///
+    /// - `file`: path of the log file (returned separately from the configuration)
+    /// - `level`: minimum level, as accepted by [`level_str`](LoggerConfigBuilder::level_str),
+    ///   or its number as an integer (`level = 4`)
+    /// - `file_format`: `"text"`, `"json"`, `"dual"` or `"logfmt"`
+    /// - `use_colors`, `show_file_info`, `show_date_in_stdout`, `show_target`,
+    ///   `show_module_path`, `show_pid`, `show_pid_in_stdout`, `show_sequence`,
+    ///   `stdout_enabled`, `systemd_stdout` and `async_file`: booleans setting
+    ///   the builder option of the same name
+    ///
+    /// Unknown keys are rejected, so typos don't go unnoticed.
+    ///
+    /// # Returns
+    ///
+    /// The configuration and the `file` path, if any, ready to be passed to
+    /// [`init_logger_with_config`](crate::init_logger_with_config), or
+    /// [`LogError::InvalidConfig`] if the document can't be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// let (config, path) = LoggerConfig::from_toml_str(
+    ///     r#"
+    ///     level = "debug"
+    ///     use_colors = false
+    ///     file = "app.log"
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(config.level, LevelFilter::Debug);
+    /// assert_eq!(path.unwrap().to_str(), Some("app.log"));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn from_toml_str(toml: &str) -> Result<(Self, Option<PathBuf>), LogError> {
+        toml::from_str::<ConfigFile>(toml)
+            .map(ConfigFile::into_config)
+            .map_err(|error| LogError::InvalidConfig(error.to_string()))
+    }
+
+    /// Create a configuration and log file path from a JSON object.
+    ///
+    /// The object takes the same keys as [`from_toml_str`](Self::from_toml_str).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// let (config, path) =
+    ///     LoggerConfig::from_json_str(r#"{"level": "warn", "file_format": "json"}"#).unwrap();
+    /// assert_eq!(config.level, LevelFilter::Warn);
+    /// assert!(path.is_none());
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn from_json_str(json: &str) -> Result<(Self, Option<PathBuf>), LogError> {
+        serde_json::from_str::<ConfigFile>(json)
+            .map(ConfigFile::into_config)
+            .map_err(|error| LogError::InvalidConfig(error.to_string()))
+    }
+
+    /// Look up the level registered for the module a record comes from.
+    ///
+    /// A prefix only matches at a `::` boundary, so `hyper` matches
+    /// `hyper` and `hyper::client` but not `hyperlocal`. When several
+    /// prefixes match `target`, the longest one wins, so `hyper::client`
+    /// takes precedence over `hyper`.
+    ///
+    /// # Returns
+    ///
+    /// The matching module level, or `None` if no registered prefix matches.
+    pub(crate) fn module_level(&self, target: &str) -> Option<LevelFilter> {
+        self.module_levels
+            .iter()
+            .filter(|(prefix, _)| {
+                target.strip_prefix(prefix.as_str()).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with("::") || prefix.ends_with("::")
+                })
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, level)| *level)
+    }
+
+    /// Get the quiet level if `time` falls inside one of the quiet windows.
+    ///
+    /// Windows include their start and exclude their end. A window whose end
+    /// is before its start wraps around midnight, e.g. `22:00`-`02:00`.
+    #[cfg(feature = "chrono")]
+    pub(crate) fn quiet_level_at(&self, time: NaiveTime) -> Option<LevelFilter> {
+        self.quiet_windows
+            .iter()
+            .any(|&(start, end)| {
+                if start <= end {
+                    start <= time && time < end
+                } else {
+                    time >= start || time < end
+                }
+            })
+            .then_some(self.quiet_level)
+    }
+
+    /// Get the delimiter ending records in log files.
+    ///
+    /// This is the record separator with its newlines converted to the
+    /// configured line ending.
+    pub(crate) fn file_record_separator(&self) -> String {
+        if self.line_ending.is_crlf() {
+            self.record_separator
+                .replace("\r\n", "\n")
+                .replace('\n', "\r\n")
+        } else {
+            self.record_separator.clone()
+        }
+    }
+}
+
+/// Builder method setting a boolean option.
+#[cfg(feature = "serde")]
+type BoolSetter = fn(LoggerConfigBuilder, bool) -> LoggerConfigBuilder;
+
+/// Settings read from a TOML or JSON document.
+#[cfg(feature = "serde")]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    file: Option<PathBuf>,
+    #[serde(default, deserialize_with = "deserialize_level")]
+    level: Option<LevelFilter>,
+    file_format: Option<FileFormat>,
+    use_colors: Option<bool>,
+    show_file_info: Option<bool>,
+    show_date_in_stdout: Option<bool>,
+    show_target: Option<bool>,
+    show_module_path: Option<bool>,
+    show_pid: Option<bool>,
+    show_pid_in_stdout: Option<bool>,
+    show_sequence: Option<bool>,
+    stdout_enabled: Option<bool>,
+    systemd_stdout: Option<bool>,
+    async_file: Option<bool>,
+}
+
+#[cfg(feature = "serde")]
+impl ConfigFile {
+    /// Apply the settings that are present on top of the defaults.
+    fn into_config(self) -> (LoggerConfig, Option<PathBuf>) {
+        let mut builder = LoggerConfig::builder();
+        let options: [(Option<bool>, BoolSetter); 11] = [
+            (self.use_colors, LoggerConfigBuilder::use_colors),
+            (self.show_file_info, LoggerConfigBuilder::show_file_info),
+            (
+                self.show_date_in_stdout,
+                LoggerConfigBuilder::show_date_in_stdout,
+            ),
+            (self.show_target, LoggerConfigBuilder::show_target),
+            (self.show_module_path, LoggerConfigBuilder::show_module_path),
+            (self.show_pid, LoggerConfigBuilder::show_pid),
+            (
+                self.show_pid_in_stdout,
+                LoggerConfigBuilder::show_pid_in_stdout,
+            ),
+            (self.show_sequence, LoggerConfigBuilder::show_sequence),
+            (self.stdout_enabled, LoggerConfigBuilder::stdout_enabled),
+            (self.systemd_stdout, LoggerConfigBuilder::systemd_stdout),
+            (self.async_file, LoggerConfigBuilder::async_file),
+        ];
+        for (value, set) in options {
+            if let Some(value) = value {
+                builder = set(builder, value);
+            }
+        }
+        if let Some(level) = self.level {
+            builder = builder.level(level);
+        }
+        if let Some(file_format) = self.file_format {
+            builder = builder.file_format(file_format);
+        }
+        (builder.build(), self.file)
+    }
+}
+
+/// Deserialize a level from its name or number, as accepted by `level_str`.
+///
+/// Numbers may be given as integers or as strings, e.g. `level = 2` or
+/// `level = "2"`.
+#[cfg(feature = "serde")]
+fn deserialize_level<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<LevelFilter>, D::Error> {
+    struct LevelVisitor;
+
+    impl serde::de::Visitor<'_> for LevelVisitor {
+        type Value = LevelFilter;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a level name or a number from 0 to 5")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<LevelFilter, E> {
+            parse_level_filter(value).map_err(E::custom)
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<LevelFilter, E> {
+            self.visit_str(&value.to_string())
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<LevelFilter, E> {
+            self.visit_str(&value.to_string())
+        }
+    }
+
+    deserializer.deserialize_any(LevelVisitor).map(Some)
+}
+
+/// Parse a level from its name, in any case, or its number from 0 (off) to
+/// 5 (trace).
+fn parse_level_filter(level: &str) -> Result<LevelFilter, LogError> {
+    let value = level.trim();
+    let level = match value.parse::<usize>() {
+        Ok(number) => LevelFilter::iter().nth(number),
+        Err(_) => value.parse().ok(),
+    };
+    level.ok_or_else(|| {
+        LogError::InvalidLevel(format!(
+            "`{value}` (expected off, error, warn, info, debug, trace or 0-5)"
+        ))
+    })
+}
+
+/// Parse the level part of a filter directive.
+fn parse_level(level: &str, directive: &str) -> Result<LevelFilter, LogError> {
+    level
+        .parse()
+        .map_err(|_| LogError::InvalidFilter(format!("unknown level `{level}` in `{directive}`")))
+}
+
+/// Builder for constructing a [`LoggerConfig`] using a fluent API.
+///
+/// This follows the builder pattern to provide a clean way to create
+/// custom logger configurations.
+///
+/// # Example
+///
+/// ```
+/// use fstdout_logger::LoggerConfigBuilder;
+/// use log::LevelFilter;
+///
+/// let config = LoggerConfigBuilder::default()
+///     .level(LevelFilter::Warn)
+///     .show_file_info(true)
+///     .show_date_in_stdout(true)
+///     .use_colors(false)
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct LoggerConfigBuilder {
+    config: LoggerConfig,
+}
+
+impl LoggerConfigBuilder {
+    /// Set whether to show file and line information in log messages.
+    ///
+    /// When enabled, each log message will include the source file and line
+    /// number where the log was created. This is useful for debugging but
+    /// can make logs more verbose for production use.
+    ///
+    /// Default: `true`
+    pub fn show_file_info(mut self, show: bool) -> Self {
+        self.config.show_file_info = show;
+        self
+    }
+
+    /// Set the text shown in place of the file of records without one.
+    ///
+    /// Records built by hand or passed on from other logging systems may
+    /// have no source location. This is also used for the `{file}`
+    /// placeholder of templates.
+    ///
+    /// Default: `"unknown"`
+    pub fn missing_file_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.config.missing_file_placeholder = placeholder.into();
+        self
+    }
+
+    /// Set the text shown in place of the line of records without one.
+    ///
+    /// This is also used for the `{line}` placeholder of templates.
+    ///
+    /// Default: `"0"`
+    pub fn missing_line_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.config.missing_line_placeholder = placeholder.into();
+        self
+    }
+
+    /// Set whether to leave out the file info of records without a file.
+    ///
+    /// When enabled, text lines of such records have no `file:line` part
+    /// at all, e.g. `[2024-06-01 12:00:00 INFO] message` instead of
+    /// `[2024-06-01 12:00:00 INFO unknown:0] message`. Templates are not
+    /// affected, as they choose their own parts.
+    ///
+    /// Default: `false`
+    pub fn skip_file_info_when_missing(mut self, skip: bool) -> Self {
+        self.config.skip_file_info_when_missing = skip;
+        self
+    }
+
+    /// Set whether to show the target of each record in text logs.
+    ///
+    /// The target is the module path the record was logged from (e.g.
+    /// `myapp::db`) unless set explicitly with `target:`, which is often
+    /// more telling than the source file. It is shown after the level in
+    /// both stdout and text file logs, before the file info if that is
+    /// shown too: `[12:00:00 INFO myapp::db src/db.rs:42] message`.
+    /// JSON and logfmt lines always include the target, and custom
+    /// templates use the `{target}` token instead.
+    ///
+    /// Default: `false`
+    pub fn show_target(mut self, show: bool) -> Self {
+        self.config.show_target = show;
+        self
+    }
+
+    /// Set whether to show the module path of each record in text file logs.
+    ///
+    /// Unlike the target, the module path can't be overridden with
+    /// `target:`, so it always points at the module that logged the record,
+    /// even across inlined code. It is shown right before the file info,
+    /// which is still written: `[... INFO myapp::db src/db.rs:42] message`.
+    /// Records without a module path show their file path instead, or
+    /// `unknown` if that is missing too.
+    ///
+    /// Default: `false`
+    pub fn show_module_path(mut self, show: bool) -> Self {
+        self.config.show_module_path = show;
+        self
+    }
+
+    /// Set the text around the prefix of text log lines.
+    ///
+    /// The prefix (timestamp, level and file info) is enclosed in `open` and
+    /// `close`, e.g. `"<"` and `">"` for `<12:00:00 INFO> message`. Applies
+    /// to stdout and text file logs, but not to custom templates. Either may
+    /// be empty.
+    ///
+    /// Default: `"["` and `"]"`
+    pub fn prefix_delimiters(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.config.prefix_open = open.into();
+        self.config.prefix_close = close.into();
+        self
+    }
+
+    /// Set the text between the prefix and the message of text log lines.
+    ///
+    /// Log parsers that split on a field separator can use e.g. `" | "`,
+    /// which gives `[12:00:00 INFO] | message`. Applies to stdout and text
+    /// file logs, but not to custom templates.
+    ///
+    /// Default: `" "`
+    pub fn prefix_message_separator(mut self, separator: impl Into<String>) -> Self {
+        self.config.prefix_message_separator = separator.into();
+        self
+    }
+
+    /// Set whether to show date in stdout logs.
+    ///
+    /// When enabled, stdout logs will include the full date (YYYY-MM-DD).
+    /// When disabled, only the time (HH:MM:SS) will be shown.
+    /// Note: Log files always include the full date regardless of this setting.
+    ///
+    /// Default: `false`
+    pub fn show_date_in_stdout(mut self, show: bool) -> Self {
+        self.config.show_date_in_stdout = show;
+        self
+    }
+
+    /// Set whether to pad level labels to the same width.
+    ///
+    /// `INFO` and `WARN` are one character shorter than the other levels,
+    /// so messages start in different columns. When enabled, the shorter
+    /// labels are followed by a space in stdout and text file output
+    /// (including templates). The padding is added after any color codes,
+    /// so the columns line up in the terminal too.
+    ///
+    /// Default: `false`
+    pub fn align_levels(mut self, align: bool) -> Self {
+        self.config.align_levels = align;
+        self
+    }
+
+    /// Set the length of the level labels in stdout logs.
+    ///
+    /// `LevelStyle::Short` shortens the labels to three letters (`ERR`,
+    /// `WRN`, ...) and `LevelStyle::Single` to one (`E`, `W`, ...), for
+    /// dense logs on narrow terminals. The shortened labels are colored like
+    /// the full ones. Use [`file_level_style`](Self::file_level_style) for
+    /// text file logs; JSON and logfmt lines always use the full names.
+    ///
+    /// Default: `LevelStyle::Full`
+    pub fn level_style(mut self, style: LevelStyle) -> Self {
+        self.config.level_style = style;
+        self
+    }
+
+    /// Set the length of the level labels in text file logs.
+    ///
+    /// This works like [`level_style`](Self::level_style), so the console
+    /// can use short labels while the file keeps the full names, or the
+    /// other way around.
+    ///
+    /// Default: `LevelStyle::Full`
+    pub fn file_level_style(mut self, style: LevelStyle) -> Self {
+        self.config.file_level_style = style;
+        self
+    }
+
+    /// Set icons shown before the level label in stdout logs.
+    ///
+    /// Each level with an icon gets it, followed by a space, in front of its
+    /// label, e.g. `[12:00:00 ❌ ERROR main.rs:10]`. File output is not
+    /// affected. With [`align_levels`](Self::align_levels), icons are padded
+    /// to the width of the widest one, estimating emoji as two terminal
+    /// columns. See [`LoggerConfig::with_default_icons`] for a ready-made set.
+    ///
+    /// Default: `None`
+    pub fn level_icons(mut self, icons: Option<HashMap<Level, String>>) -> Self {
+        self.config.level_icons = icons;
+        self
+    }
+
+    /// Set whether to show the process ID in file logs.
+    ///
+    /// When enabled, text file lines include the ID after the timestamp,
+    /// e.g. `[2024-06-01 12:00:00 pid:1234 INFO main.rs:10]`, and JSON and
+    /// logfmt lines get a `pid` field. This tells apart the lines of several
+    /// processes writing to the same log directory.
+    ///
+    /// Default: `false`
+    pub fn show_pid(mut self, show: bool) -> Self {
+        self.config.show_pid = show;
+        self
+    }
+
+    /// Set whether to show the process ID in stdout logs.
+    ///
+    /// Works like [`show_pid`](Self::show_pid) for the console, independently
+    /// of the file setting.
+    ///
+    /// Default: `false`
+    pub fn show_pid_in_stdout(mut self, show: bool) -> Self {
+        self.config.show_pid_in_stdout = show;
+        self
+    }
+
+    /// Set whether to number records with a sequence number.
+    ///
+    /// When enabled, the logger gives each record the next number of a
+    /// counter shared by all threads, starting at 1. Text lines show it after
+    /// the timestamp, e.g. `[2024-06-01 12:00:00 #000042 INFO main.rs:10]`,
+    /// and JSON and logfmt lines get a `seq` field. This orders records whose
+    /// timestamps are equal; a record keeps the same number on the console
+    /// and in the files.
+    ///
+    /// The numbers are assigned by the logger, so a [`LogFormatter`](crate::LogFormatter)
+    /// used on its own doesn't show them. Templates don't show them either.
+    ///
+    /// Default: `false`
+    pub fn show_sequence(mut self, show: bool) -> Self {
+        self.config.show_sequence = show;
+        self
+    }
+
+    /// Set the minimum number of digits in sequence numbers.
+    ///
+    /// Shorter numbers are padded with zeros; `0` turns padding off.
+    ///
+    /// Default: `6`
+    pub fn sequence_width(mut self, width: usize) -> Self {
+        self.config.sequence_width = width;
+        self
+    }
+
+    /// Set whether to use colors in stdout logs.
+    ///
+    /// When enabled, different log levels will be displayed in different colors:
+    /// - ERROR: Red
+    /// - WARN: Yellow
+    /// - INFO: Blue
+    /// - DEBUG: Green
+    /// - TRACE: Default terminal color
+    ///
+    /// Note: Log files never include color codes regardless of this setting.
+    ///
+    /// Calling this forces the setting and overrides [`auto_colors`](Self::auto_colors).
+    /// It leaves [`color_levels`](Self::color_levels) alone, so the two can
+    /// be set in any order. Colors chosen this way are
+    /// kept under `cargo test` too: it turns off
+    /// [`detect_test_mode`](Self::detect_test_mode), unless that is enabled
+    /// again afterwards.
+    ///
+    /// Default: `true`
+    pub fn use_colors(mut self, use_colors: bool) -> Self {
+        self.config.use_colors = use_colors;
+        self.config.detect_test_mode = false;
+        self.config.color_mode = ColorMode::Explicit;
+        self
+    }
+
+    /// Only color records of the given levels.
+    ///
+    /// Records of other levels are printed without any escape codes, even
+    /// when colors are enabled, e.g. to highlight warnings and errors while
+    /// keeping the more frequent info and debug lines plain. This narrows
+    /// [`use_colors`](Self::use_colors) and [`auto_colors`](Self::auto_colors)
+    /// but doesn't turn colors on by itself. It is independent of both, so
+    /// the order they are set in doesn't matter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::Level;
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .color_levels(&[Level::Error, Level::Warn])
+    ///     .build();
+    /// ```
+    ///
+    /// Default: all levels
+    pub fn color_levels(mut self, levels: &[Level]) -> Self {
+        self.config.color_levels = levels.to_vec();
+        self
+    }
+
+    /// Emit colors only when the console output is a terminal.
+    ///
+    /// Whether stdout and stderr are terminals is checked once when the logger
+    /// is created. When output is redirected to a file or a pipe, no ANSI
+    /// escape codes are written. With `ErrorStream::Stderr`, warnings and
+    /// errors are colored based on stderr rather than stdout.
+    ///
+    /// A later call to [`use_colors`](Self::use_colors) forces the setting again.
+    pub fn auto_colors(mut self) -> Self {
+        self.config.color_mode = ColorMode::Auto;
+        self
+    }
+
+    /// Set the colors used for level labels in stdout logs.
+    ///
+    /// Start from [`ColorScheme::default()`] and override the levels that
+    /// don't fit your terminal theme, e.g. `ColorScheme::default().info(Color::Cyan)`.
+    /// Only takes effect when colors are enabled.
+    ///
+    /// Default: `ColorScheme::default()`
+    pub fn color_scheme(mut self, scheme: ColorScheme) -> Self {
+        self.config.color_scheme = scheme;
+        self
+    }
+
+    /// Set the weight of all level labels at once.
+    ///
+    /// `ColorIntensity::Bold` makes every level bold for higher contrast,
+    /// `ColorIntensity::Dim` softens them, and `ColorIntensity::Normal` turns
+    /// bold off everywhere. `ColorIntensity::Scheme` keeps the per-level
+    /// weight of the [`color_scheme`](Self::color_scheme).
+    ///
+    /// Default: `ColorIntensity::Scheme`
+    pub fn color_intensity(mut self, intensity: ColorIntensity) -> Self {
+        self.config.color_intensity = intensity;
+        self
+    }
+
+    /// Set which part of a stdout line is painted in the level's color.
+    ///
+    /// `ColorStyle::FullLine` paints the whole line, e.g. every error line in
+    /// red. The timestamp and file info are then not dimmed, so their color
+    /// resets don't end the line's color early. The
+    /// [`color_intensity`](Self::color_intensity) applies to the whole line
+    /// too. Only takes effect when colors are enabled.
+    ///
+    /// Default: `ColorStyle::LevelOnly`
+    pub fn color_style(mut self, style: ColorStyle) -> Self {
+        self.config.color_style = style;
+        self
+    }
+
+    /// Set whether colors are disabled when running under `cargo test`.
+    ///
+    /// `cargo test` captures the output of each test and only prints it when
+    /// the test fails, where color codes just add noise. When enabled, the
+    /// logger disables colors if it detects a test run: either the
+    /// `FSTDOUT_TEST_MODE` environment variable is set to `1`/`true`, or the
+    /// executable is named like a cargo test binary (`<name>-<hash>` in a
+    /// `deps` directory). Setting `FSTDOUT_TEST_MODE=0` turns detection off.
+    ///
+    /// Detection only applies to the default colors:
+    /// [`use_colors`](Self::use_colors) turns it off, so call this after it
+    /// to have detection override an explicit choice.
+    ///
+    /// Console output is written with `print!`/`eprint!`, so the test harness
+    /// captures it and logs only show up for failing tests.
+    ///
+    /// Default: `true`
+    pub fn detect_test_mode(mut self, enabled: bool) -> Self {
+        self.config.detect_test_mode = enabled;
+        self
+    }
+
+    /// Set whether to strip escape sequences from redirected console output.
+    ///
+    /// Unlike [`auto_colors`](Self::auto_colors), this keeps the configured
+    /// colors and removes them afterwards with
+    /// [`strip_ansi`](crate::formatter::strip_ansi), so escape sequences that
+    /// are part of a message are removed as well. It applies to each console
+    /// stream (stdout, or stderr with `ErrorStream::Stderr`) that is not a
+    /// terminal when the logger is created, e.g. when stdout is piped to `tee`.
+    ///
+    /// Default: `false`
+    pub fn strip_colors_on_redirect(mut self, enabled: bool) -> Self {
+        self.config.strip_colors_on_redirect = enabled;
+        self
+    }
+
+    /// Set the minimum log level to display.
+    ///
+    /// This filters log messages based on their level:
+    /// - `Error`: Only errors
+    /// - `Warn`: Errors and warnings
+    /// - `Info`: Errors, warnings, and info
+    /// - `Debug`: Errors, warnings, info, and debug
+    /// - `Trace`: All log levels
+    ///
+    /// Default: `Info`
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.config.level = level;
+        self
+    }
+
+    /// Set the minimum log level from counted `-v` and `-q` flags.
+    ///
+    /// Starting from `Info`, each `verbose` step makes the level more
+    /// verbose (`Debug`, then `Trace`) and each `quiet` step less so (`Warn`,
+    /// `Error`, then `Off`). The steps cancel each other out, and the level
+    /// stays at `Trace` or `Off` once it reaches either end, so
+    /// `verbosity(0, 0)` is `Info` and `verbosity(5, 0)` is `Trace`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// // e.g. the number of times `-v` and `-q` were passed
+    /// let (verbose, quiet) = (2, 0);
+    /// let config = LoggerConfig::builder().verbosity(verbose, quiet).build();
+    /// assert_eq!(config.level, LevelFilter::Trace);
+    /// ```
+    pub fn verbosity(self, verbose: u8, quiet: u8) -> Self {
+        let info = LevelFilter::Info as i32;
+        let step = (info + i32::from(verbose) - i32::from(quiet))
+            .clamp(LevelFilter::Off as i32, LevelFilter::max() as i32);
+        self.level(
+            LevelFilter::iter()
+                .nth(step as usize)
+                .unwrap_or(LevelFilter::Info),
+        )
+    }
+
+    /// Set the minimum log level from a string, e.g. a `--log-level` argument.
+    ///
+    /// Accepts the level names `off`, `error`, `warn`, `info`, `debug` and
+    /// `trace` in any case, or the numbers `0` (off) to `5` (trace).
+    /// Surrounding whitespace is ignored.
+    ///
+    /// # Returns
+    ///
+    /// The builder with the level set, or [`LogError::InvalidLevel`] if the
+    /// string is not a level.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// let config = LoggerConfig::builder().level_str("DEBUG").unwrap().build();
+    /// assert_eq!(config.level, LevelFilter::Debug);
+    /// ```
+    pub fn level_str(self, level: &str) -> Result<Self, LogError> {
+        parse_level_filter(level).map(|level| self.level(level))
+    }
+
+    /// Set the minimum log level for records whose target starts with `target_prefix`.
+    ///
+    /// This works like `env_logger`'s `module=level` filters: a record's
+    /// target (usually its module path) is matched against every registered
+    /// prefix, the longest match decides the level, and records from modules
+    /// without a match fall back to [`level`](Self::level). Prefixes match
+    /// whole path segments: `hyper` covers `hyper::client` but not
+    /// `hyperlocal`. Can be called several times to register more modules.
+    ///
+    /// Module levels override the global level in both directions: a module
+    /// can be more verbose than [`level`](Self::level) as well as quieter.
+    /// When the logger is initialized, the global `log` maximum level is set
+    /// to the most verbose level across all of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .level(LevelFilter::Debug)
+    ///     .module_level("hyper", LevelFilter::Warn)
+    ///     .build();
+    /// ```
+    ///
+    /// Default: no module levels
+    pub fn module_level(mut self, target_prefix: impl Into<String>, level: LevelFilter) -> Self {
+        self.config
+            .module_levels
+            .push((target_prefix.into(), level));
+        self
+    }
+
+    /// Add a daily time window during which logging is quieted.
+    ///
+    /// Between `start` (inclusive) and `end` (exclusive) every day, records
+    /// more verbose than [`quiet_level`](Self::quiet_level) are dropped, e.g.
+    /// to avoid alert fatigue during a maintenance window with known noisy
+    /// errors. A window may wrap around midnight (`start` after `end`). Times
+    /// are in the configured [`timezone`](Self::timezone). Can be called
+    /// several times to add more windows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveTime;
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// // Only show errors during the nightly maintenance window
+    /// let config = LoggerConfig::builder()
+    ///     .quiet_window(
+    ///         NaiveTime::from_hms_opt(2, 0, 0).unwrap(),
+    ///         NaiveTime::from_hms_opt(3, 30, 0).unwrap(),
+    ///     )
+    ///     .quiet_level(LevelFilter::Error)
+    ///     .build();
+    /// ```
+    ///
+    /// Requires the `chrono` feature.
+    ///
+    /// Default: no quiet windows
+    #[cfg(feature = "chrono")]
+    pub fn quiet_window(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.config.quiet_windows.push((start, end));
+        self
+    }
+
+    /// Set the most verbose level shown during quiet windows.
+    ///
+    /// `LevelFilter::Off` suppresses logging entirely, while e.g.
+    /// `LevelFilter::Error` only lets errors through. The quiet level never
+    /// makes logging more verbose than it is outside the window.
+    ///
+    /// Default: `LevelFilter::Off`
+    #[cfg(feature = "chrono")]
+    pub fn quiet_level(mut self, level: LevelFilter) -> Self {
+        self.config.quiet_level = level;
+        self
+    }
+
+    /// Set whether the `FSTDOUT_MAX_LEVEL` environment variable caps the level.
+    ///
+    /// This is a kill switch for operations: setting e.g.
+    /// `FSTDOUT_MAX_LEVEL=error` drops every less severe record, whatever the
+    /// configured global and per-module levels say. The variable is read when
+    /// the logger is created and checked again at most once per second while
+    /// logging, so changes made by the process at runtime take effect without
+    /// a restart. Unset or unparsable values don't cap anything.
+    ///
+    /// Default: `false`
+    pub fn respect_env_level(mut self, enabled: bool) -> Self {
+        self.config.respect_env_level = enabled;
+        self
+    }
+
+    /// Collapse consecutive identical messages logged within `window`.
+    ///
+    /// A record with the same level and message text as the previous record
+    /// is dropped if it arrives within `window` of the last one that was
+    /// logged. Once a different message arrives, or the same message after
+    /// the window has passed, a summary line such as
+    /// `disk almost full (repeated 250 times)` is logged first. Only the last
+    /// message is remembered, so memory use stays constant. A pending summary
+    /// is also written when the logger is flushed (including when a
+    /// [`LoggerHandle`](crate::LoggerHandle) is dropped) and, for the
+    /// installed logger, once the window has passed without further records,
+    /// also when collapsing is only turned on later with
+    /// [`LoggerHandle::set_config`](crate::LoggerHandle::set_config).
+    ///
+    /// Default: `None`
+    pub fn dedup_window(mut self, window: Option<Duration>) -> Self {
+        self.config.dedup_window = window;
+        self
+    }
+
+    /// Set a custom timestamp format for stdout logs.
+    ///
+    /// The string is passed to `chrono`'s `format()`, e.g. `"%H:%M:%S%.3f"` for
+    /// millisecond precision. Without the `chrono` feature, only the
+    /// specifiers listed in [`validate_time_format`](crate::formatter::validate_time_format)
+    /// are available. When set, it takes precedence over
+    /// [`show_date_in_stdout`](Self::show_date_in_stdout). The format is
+    /// validated when the logger is created, and an invalid specifier makes
+    /// creation fail with [`LogError::InvalidTimeFormat`](crate::LogError::InvalidTimeFormat).
+    ///
+    /// Default: `None`
+    pub fn stdout_time_format(mut self, format: Option<String>) -> Self {
+        self.config.stdout_time_format = format;
+        self
+    }
+
+    /// Set a custom timestamp format for text file logs.
+    ///
+    /// The string is passed to `chrono`'s `format()` and validated when the
+    /// logger is created, like [`stdout_time_format`](Self::stdout_time_format).
+    /// JSON file output always uses RFC3339 timestamps.
+    ///
+    /// Default: `None`
+    pub fn file_time_format(mut self, format: Option<String>) -> Self {
+        self.config.file_time_format = format;
+        self
+    }
+
+    /// Write file timestamps as RFC 3339 with the given sub-second precision.
+    ///
+    /// Log shippers often require strict RFC 3339 / ISO 8601 timestamps with
+    /// a time zone offset, e.g. `2024-06-01T12:00:00.123+02:00`. When set,
+    /// text file lines (including the `{time}` token of a
+    /// [`file_template`](Self::file_template)) use this format instead of
+    /// [`file_time_format`](Self::file_time_format), and JSON and logfmt
+    /// timestamps, which are always RFC 3339, get the chosen precision. UTC
+    /// timestamps end in `Z`, see [`timezone`](Self::timezone). With
+    /// `TimestampSource::SinceStart`, text lines keep the elapsed time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::{LoggerConfig, SecondsPrecision};
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .file_rfc3339(Some(SecondsPrecision::Millis))
+    ///     .build();
+    /// ```
+    ///
+    /// Default: `None`
+    pub fn file_rfc3339(mut self, precision: Option<SecondsPrecision>) -> Self {
+        self.config.file_rfc3339 = precision;
+        self
+    }
+
+    /// Set the time zone used for timestamps.
+    ///
+    /// With `TimeZoneMode::Utc`, timestamps in stdout and file output use UTC
+    /// instead of the local time, which makes logs from servers in different
+    /// time zones easy to correlate. Text file timestamps get a `Z` suffix,
+    /// unless the file time format already writes the offset (`%z`, `%:z`,
+    /// `%Z` or `%+`), and JSON timestamps are written as RFC3339 with `Z`.
+    ///
+    /// **Without the `chrono` feature, only UTC is available**: the default
+    /// is then `TimeZoneMode::Utc`, and creating a logger with
+    /// `TimeZoneMode::Local` fails with [`LogError::InvalidConfig`].
+    ///
+    /// Default: `TimeZoneMode::Local` (`TimeZoneMode::Utc` without `chrono`)
+    pub fn timezone(mut self, timezone: TimeZoneMode) -> Self {
+        self.config.timezone = timezone;
+        self
+    }
+
+    /// Set the source of the timestamps in stdout and text file lines.
+    ///
+    /// With `TimestampSource::SinceStart`, every line is prefixed with the
+    /// monotonic time elapsed since the logger was created, with millisecond
+    /// precision (e.g. `+1.234s`), instead of the wall-clock time. This suits
+    /// benchmark-style logging where durations matter more than the time of
+    /// day. The time formats and time zone are ignored for text output; JSON
+    /// timestamps stay RFC3339 wall-clock times.
+    ///
+    /// Default: `TimestampSource::WallClock`
+    pub fn timestamp_source(mut self, source: TimestampSource) -> Self {
+        self.config.timestamp_source = source;
+        self
+    }
+
+    /// Set whether to number records logged within the same second.
+    ///
+    /// Many records can share a timestamp with second resolution, so their
+    /// order is lost once lines from several sources are merged and sorted.
+    /// When enabled, a counter starting at `.001` for the first record of
+    /// every second is appended to the seconds (e.g. `12:00:00.002`) in
+    /// stdout and text file lines. Stdout and the files are counted
+    /// separately. Time formats that already show fractions of a second
+    /// (`%f`, `%.3f`, ...) are left unchanged.
+    ///
+    /// Default: `false`
+    pub fn intra_second_counter(mut self, enabled: bool) -> Self {
+        self.config.intra_second_counter = enabled;
+        self
+    }
+
+    /// Set whether text lines show the time elapsed since the previous record.
+    ///
+    /// When enabled, the milliseconds since the record before it are shown
+    /// after the timestamp, e.g. `[12:00:00 (+12ms) INFO main.rs:10]`, for
+    /// quick profiling without a profiler. The first record shows `(+0ms)`.
+    /// Unlike `TimestampSource::SinceStart`, which replaces the timestamp
+    /// with the time since the start, this is added to the timestamp.
+    ///
+    /// The delta is measured by the logger across all records it writes, so
+    /// a record shows the same delta on the console and in the files, and
+    /// replacing the configuration with
+    /// [`LoggerHandle::set_config`](crate::LoggerHandle::set_config) doesn't
+    /// restart it. A [`LogFormatter`](crate::LogFormatter) used on its own
+    /// doesn't show it. Only text lines show it: stdout, text files and the
+    /// text line of `FileFormat::Dual`; JSON and logfmt lines have no delta
+    /// field, and templates don't show it either.
+    ///
+    /// Default: `false`
+    pub fn show_delta(mut self, show: bool) -> Self {
+        self.config.show_delta = show;
+        self
+    }
+
+    /// Add a log file that receives records at `min_level` or more severe.
+    ///
+    /// The file is written in addition to the file passed to the logger's
+    /// constructor, which receives every record. Each record is formatted
+    /// once and written to every file whose level it meets, e.g. a combined
+    /// log plus an errors-only file. All files share the remaining file
+    /// options such as [`file_mode`](Self::file_mode),
+    /// [`file_format`](Self::file_format) and [`rotation`](Self::rotation).
+    /// Can be called several times to add more files.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::LevelFilter;
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .add_file("errors.log", LevelFilter::Error)
+    ///     .build();
+    /// ```
+    ///
+    /// Default: no additional files
+    pub fn add_file(mut self, path: impl Into<PathBuf>, min_level: LevelFilter) -> Self {
+        self.config.files.push((path.into(), min_level));
+        self
+    }
+
+    /// Add a log file that only receives records between two levels.
+    ///
+    /// This works like [`add_file`](Self::add_file), but records more severe
+    /// than `max_level` are left out too, so the file takes the closed range
+    /// from `min_level` up to `max_level`. For example, `Info` to `Warn`
+    /// writes info and warning records but neither errors nor debug output.
+    /// `add_file(path, level)` is the same as
+    /// `add_file_range(path, level, Level::Error)`.
+    ///
+    /// The range only selects among the records the logger accepts: records
+    /// more verbose than the logger's [`level`](Self::level) (or its
+    /// per-module level) are dropped before reaching any file, so a
+    /// `min_level` more verbose than that has no effect. Creating the logger
+    /// fails with [`LogError::InvalidLevel`] if `max_level` is more verbose
+    /// than `min_level`, as no record could match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    /// use log::{Level, LevelFilter};
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .add_file_range("routine.log", LevelFilter::Info, Level::Warn)
+    ///     .build();
+    /// ```
+    ///
+    /// Default: no additional files
+    pub fn add_file_range(
+        mut self,
+        path: impl Into<PathBuf>,
+        min_level: LevelFilter,
+        max_level: Level,
+    ) -> Self {
+        self.config
+            .file_ranges
+            .push((path.into(), min_level, max_level));
+        self
+    }
+
+    /// Set what happens to an existing log file when the logger opens it.
+    ///
+    /// By default the previous contents are archived to a `.tar.xz` file in
+    /// the working directory and the log starts empty. `FileMode::Append`
+    /// keeps adding to the same file across runs, while `FileMode::Truncate`
+    /// starts every run with an empty file and discards the old contents.
+    ///
+    /// Default: `FileMode::Archive`
+    pub fn file_mode(mut self, mode: FileMode) -> Self {
+        self.config.file_mode = mode;
+        self
+    }
+
+    /// Set whether log files are locked when the logger opens them.
+    ///
+    /// When enabled, the logger takes an exclusive lock on each log file
+    /// right after opening it, before archiving or truncating the previous
+    /// contents, and holds it as long as the file is open. If another process
+    /// holds the lock for longer than the
+    /// [`file_lock_timeout`](Self::file_lock_timeout), creating the logger
+    /// fails with [`LogError::FileLocked`](crate::LogError::FileLocked). This
+    /// stops two instances from clobbering the same file.
+    ///
+    /// Caveats:
+    /// - On Unix the lock is advisory (`flock`): it only keeps out processes
+    ///   that also lock the file, and may not work on network file systems
+    ///   such as NFS. On Windows it is mandatory and blocks other writers.
+    /// - Files opened by [`rotation`](Self::rotation) and files passed in
+    ///   already open are not locked.
+    /// - Files opened by [`lazy_open`](Self::lazy_open) don't wait for the
+    ///   lock, so a log call is never stalled: if the file is locked on the
+    ///   first write, [`LogError::FileLocked`](crate::LogError::FileLocked)
+    ///   is reported to [`on_error`](Self::on_error) and the file stays
+    ///   closed.
+    /// - On platforms without file locking, opening fails with an
+    ///   [`io::ErrorKind::Unsupported`](std::io::ErrorKind::Unsupported) error.
+    ///
+    /// Default: `false`
+    pub fn file_locking(mut self, enabled: bool) -> Self {
+        self.config.file_locking = enabled;
+        self
+    }
+
+    /// Set how long to wait for another process to release a log file's lock.
+    ///
+    /// Only used with [`file_locking`](Self::file_locking), and not for files
+    /// opened by [`lazy_open`](Self::lazy_open), which give up right away.
+    ///
+    /// Default: 5 seconds
+    pub fn file_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.config.file_lock_timeout = timeout;
+        self
+    }
+
+    /// Open log files on their first write instead of when the logger is
+    /// created.
+    ///
+    /// Useful for programs that may never log to a file, so no empty file
+    /// (or archive of the previous one) is left behind. Since the file is
+    /// opened after init returned, a failure to open it is reported to
+    /// [`on_error`](Self::on_error) on the first write; the file then stays
+    /// closed and its records are dropped. The file is opened only once,
+    /// even if several threads race to write the first record. The audit
+    /// file and files passed in already open are never opened lazily.
+    ///
+    /// Default: `false`
+    pub fn lazy_open(mut self, enabled: bool) -> Self {
+        self.config.lazy_open = enabled;
+        self
+    }
+
+    /// Set a custom layout for stdout lines.
+    ///
+    /// The template is literal text with placeholder tokens: `{time}`,
+    /// `{level}`, `{file}`, `{line}`, `{target}`, `{thread}` and `{message}`.
+    /// Write `{{` and `}}` for literal braces. The template is parsed once
+    /// when the logger is created, and an unknown token makes creation fail
+    /// with [`LogError::InvalidTemplate`](crate::LogError::InvalidTemplate).
+    /// When set, it replaces the default layout and
+    /// [`show_file_info`](Self::show_file_info); `{time}` still follows
+    /// [`stdout_time_format`](Self::stdout_time_format).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .stdout_template(Some("{time} {level} {target} - {message}".to_string()))
+    ///     .build();
+    /// ```
+    ///
+    /// Default: `None`
+    pub fn stdout_template(mut self, template: Option<String>) -> Self {
+        self.config.stdout_template = template;
+        self
+    }
+
+    /// Set a custom layout for text file lines.
+    ///
+    /// Uses the same tokens as [`stdout_template`](Self::stdout_template).
+    /// Only affects [`FileFormat::Text`] output, and colors are never used.
+    ///
+    /// Default: `None`
+    pub fn file_template(mut self, template: Option<String>) -> Self {
+        self.config.file_template = template;
+        self
+    }
+
+    /// Set whether file writes are buffered.
+    ///
+    /// When enabled, lines written to the log file are kept in memory and
+    /// written out in larger chunks, which is faster for high-volume logging.
+    /// Buffered lines may be lost if the process exits without flushing.
+    /// The mode can be changed later on the installed logger with
+    /// [`set_buffered`](crate::set_buffered).
+    ///
+    /// Default: `false`
+    pub fn buffered(mut self, buffered: bool) -> Self {
+        self.config.buffered = buffered;
+        self
+    }
+
+    /// Set how often buffered file writes are flushed in the background.
+    ///
+    /// With `Some(interval)`, a background thread flushes the file buffer
+    /// every `interval`, so lines reach the file even when the application
+    /// goes idle. With `None`, the buffer is flushed whenever an `Error`
+    /// record is written, so the lines leading up to a failure are on disk
+    /// if the process crashes right after. This only matters when
+    /// [`buffered`](Self::buffered) writes are enabled.
+    ///
+    /// Either way, lines written since the last flush are lost if the process
+    /// dies without flushing. A shorter interval narrows that window at the
+    /// cost of more write syscalls; `Log::flush` and [`LoggerHandle::flush`](crate::LoggerHandle::flush)
+    /// always write out everything.
+    ///
+    /// Default: `None`
+    pub fn flush_interval(mut self, interval: Option<Duration>) -> Self {
+        self.config.flush_interval = interval;
+        self
+    }
+
+    /// Set which records are synced to disk with `fsync` after being written.
+    ///
+    /// Flushing only hands data to the operating system, which may still lose
+    /// it on a power failure. For audit logs that must survive that, use
+    /// `FsyncPolicy::Always` (or `FsyncPolicy::OnError` for crash-relevant
+    /// records only) to flush the buffer and call `File::sync_all` after the
+    /// record is written.
+    ///
+    /// Syncing waits for the disk and is very slow compared to a normal
+    /// write, often by orders of magnitude, so `Always` can become the
+    /// bottleneck of a busy application.
+    ///
+    /// Default: `FsyncPolicy::Never`
+    pub fn fsync(mut self, policy: FsyncPolicy) -> Self {
+        self.config.fsync = policy;
+        self
+    }
+
+    /// Set whether file writes are handed to a background writer thread.
+    ///
+    /// Normally the logging thread writes to the file itself, so under heavy
+    /// load every thread waits for the disk and for the others. With this
+    /// enabled, log calls only format the line and queue it; a single writer
+    /// thread writes the queued lines in order, including rotation and
+    /// syncing. Console output is not affected.
+    ///
+    /// When the queue is full, [`async_file_backpressure`](Self::async_file_backpressure)
+    /// decides whether the log call waits or the line is dropped.
+    /// `Log::flush`, [`LoggerHandle::flush`](crate::LoggerHandle::flush) and
+    /// dropping the logger wait until the queue is drained. Lines still
+    /// queued when the process exits without flushing are lost.
+    ///
+    /// Default: `false`
+    pub fn async_file(mut self, enabled: bool) -> Self {
+        self.config.async_file = enabled;
+        self
+    }
+
+    /// Set how many lines can be queued for the writer thread.
+    ///
+    /// Only used with [`async_file`](Self::async_file). The capacity must be
+    /// at least 1; initialization fails with [`LogError::InvalidConfig`]
+    /// otherwise.
+    ///
+    /// Default: 1024
+    pub fn async_file_capacity(mut self, capacity: usize) -> Self {
+        self.config.async_file_capacity = capacity;
+        self
+    }
+
+    /// Set what happens to a line when the writer thread's queue is full.
+    ///
+    /// `BackpressurePolicy::Block` slows logging down to the speed of the
+    /// disk but keeps every line. `BackpressurePolicy::Drop` never waits;
+    /// dropped lines are counted and reported to [`on_error`](Self::on_error)
+    /// as [`LogError::FileQueueFull`](crate::LogError::FileQueueFull). Lines
+    /// logged from the error handler itself are always dropped instead of
+    /// waiting, as the writer thread would otherwise wait for itself.
+    ///
+    /// Only used with [`async_file`](Self::async_file).
+    ///
+    /// Default: `BackpressurePolicy::Block`
+    pub fn async_file_backpressure(mut self, policy: BackpressurePolicy) -> Self {
+        self.config.async_file_backpressure = policy;
+        self
+    }
+
+    /// Set a callback for errors when writing to or flushing the log file.
+    ///
+    /// Log calls never fail, so without a handler a full disk or a removed
+    /// log directory silently drops file output. The handler is called after
+    /// the file lock has been released, so it may log itself; errors raised
+    /// from inside the handler are not reported again.
+    ///
+    /// Default: `None`
+    pub fn on_error(mut self, handler: ErrorHandler) -> Self {
+        self.config.on_error = Some(handler);
+        self
+    }
+
+    /// Set a callback invoked for every record the logger writes.
+    ///
+    /// The observer sees each record that passed the filters, right before
+    /// it is written, e.g. to count log lines per level for metrics. It is
+    /// not called for records suppressed as repeats or for the logger's own
+    /// summary lines. Unlike [`on_error`](Self::on_error), it runs on every
+    /// log call, so it should be cheap. The observer may log itself; those
+    /// records are written but not observed again.
+    ///
+    /// Default: `None`
+    pub fn observer(mut self, observer: RecordObserver) -> Self {
+        self.config.observer = Some(observer);
+        self
+    }
+
+    /// Set the time-based rotation policy for the log file.
+    ///
+    /// With `Daily` or `Hourly`, the first write after a date or hour boundary
+    /// renames the current file to a date-stamped name and starts a new one.
+    /// This also happens when the process was idle across the boundary.
+    ///
+    /// Default: `RotationPolicy::Never`
+    pub fn rotation(mut self, rotation: RotationPolicy) -> Self {
+        self.config.rotation = rotation;
+        self
+    }
+
+    /// Set limits on the active and rotated log files.
+    ///
+    /// `max_file_size` rotates the active file once it reaches that size,
+    /// in addition to the time-based [`rotation`](Self::rotation). The other
+    /// limits delete old rotated files (named `<stem>-*.<ext>` next to the
+    /// log file) after every rotation. See [`RetentionPolicy`] for details.
+    ///
+    /// Default: `RetentionPolicy::default()` (no limits)
+    pub fn retention(mut self, retention: RetentionPolicy) -> Self {
+        self.config.retention = retention;
+        self
+    }
+
+    /// Set the format of lines written to the log file.
+    ///
+    /// `FileFormat::Json` writes newline-delimited JSON objects that log
+    /// aggregation pipelines can ingest without regex parsing. Stdout output
+    /// stays human-readable text regardless of this setting.
+    ///
+    /// Default: `FileFormat::Text`
+    pub fn file_format(mut self, file_format: FileFormat) -> Self {
+        self.config.file_format = file_format;
+        self
+    }
+
+    /// Set the string written between a key and its value in logfmt output.
+    ///
+    /// Together with [`kv_pair_delimiter`](Self::kv_pair_delimiter), this
+    /// adapts `FileFormat::Logfmt` to in-house formats, e.g. `key:value;`
+    /// instead of `key=value `. Values containing whitespace, quotes or
+    /// either string are quoted.
+    ///
+    /// Default: `"="`
+    pub fn kv_separator(mut self, separator: impl Into<String>) -> Self {
+        self.config.kv_separator = separator.into();
+        self
+    }
+
+    /// Set the string written between two key-value pairs in logfmt output.
+    ///
+    /// See [`kv_separator`](Self::kv_separator).
+    ///
+    /// Default: `" "`
+    pub fn kv_pair_delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.config.kv_pair_delimiter = delimiter.into();
+        self
+    }
+
+    /// Set how `Duration` key-values are rendered in JSON and logfmt output.
+    ///
+    /// Applies to values captured with `:?`, whose `Debug` form is parsed
+    /// back into a duration, so `elapsed:? = Duration::from_millis(1500)`
+    /// becomes `1.5s` with `DurationFormat::Seconds` or `1500ms` with
+    /// `DurationFormat::Millis`. Other values are left untouched.
+    ///
+    /// Default: `DurationFormat::Debug`
+    pub fn duration_format(mut self, format: DurationFormat) -> Self {
+        self.config.duration_format = format;
+        self
+    }
+
+    /// Add a key-value pair to every record, e.g. the service name.
+    ///
+    /// Global fields are written after the record's own key-values, in the
+    /// order they were added: as `key=value` after the message in text
+    /// output on stdout and in files, in the `fields` object in JSON, and
+    /// as extra pairs in logfmt. A key-value logged with a record overrides
+    /// a global field with the same key. Can be called several times to add
+    /// more fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .add_global_field("service", "checkout")
+    ///     .add_global_field("env", "prod")
+    ///     .build();
+    /// ```
+    ///
+    /// Default: no global fields
+    pub fn add_global_field(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.global_fields.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set whether JSON file output includes a numeric level.
+    ///
+    /// With `Some(scale)`, every JSON line gets a `level_num` field next to
+    /// `level`, which makes it easy to sort or filter by severity. Use
+    /// [`LevelScale::Ascending`] for `Trace` = 0 through `Error` = 4, or
+    /// [`LevelScale::Syslog`] for syslog severities (lower is more severe).
+    ///
+    /// Default: `None`
+    pub fn numeric_levels(mut self, scale: Option<LevelScale>) -> Self {
+        self.config.numeric_levels = scale;
+        self
+    }
+
+    /// Set a character to replace line breaks inside log messages.
+    ///
+    /// Log systems that treat each physical line as a record break on
+    /// multi-line messages. With `Some(c)`, every line break in the message is
+    /// replaced by `c` (for example `'⏎'` or `' '`) in stdout and text file
+    /// output. JSON output already escapes line breaks and is unaffected.
+    ///
+    /// Default: `None`
+    pub fn collapse_newlines(mut self, replacement: Option<char>) -> Self {
+        self.config.collapse_newlines = replacement;
+        self
+    }
+
+    /// Set the delimiter written after every record.
+    ///
+    /// Records end with a newline by default. Consumers that expect other
+    /// delimiters can use e.g. `"\0"` for NUL-delimited records or `"\r\n"`
+    /// for Windows line endings. The separator applies to stdout and to all
+    /// file formats, including the restart separator line. In
+    /// `FileFormat::Dual` output it ends both the text and the JSON line.
+    ///
+    /// Default: `"\n"`
+    pub fn record_separator(mut self, separator: impl Into<String>) -> Self {
+        self.config.record_separator = separator.into();
+        self
+    }
 
-    /// Whether to use colors in stdout logs
-    pub use_colors: bool,
+    /// Set the line ending of the records written to log files.
+    ///
+    /// With `LineEnding::CrLf`, or `LineEnding::Platform` on Windows, the
+    /// newline in the [`record_separator`](Self::record_separator) becomes
+    /// `\r\n` in every file format, so the files display correctly in
+    /// Windows tools. Separators without a newline are left alone, and
+    /// stdout output is unaffected.
+    ///
+    /// Default: `LineEnding::Lf`
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.config.line_ending = line_ending;
+        self
+    }
 
-    /// Minimum log level to display
-    pub level: LevelFilter,
-}
+    /// Add a pattern whose matches are masked in log messages.
+    ///
+    /// Every match of `pattern` in a rendered message is replaced by
+    /// `***REDACTED***` before the record is written to stdout or a file, or
+    /// sent to a processor, as a last line of defense against leaking tokens or passwords. Only
+    /// the message is scrubbed, not structured key-values. Patterns are
+    /// compiled by the caller, so invalid ones are caught up front and no
+    /// compilation happens while logging.
+    ///
+    /// Only available with the `regex` feature.
+    ///
+    /// Default: no patterns
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fstdout_logger::{LoggerConfig, Regex};
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .redact(Regex::new(r"token=\w+").unwrap())
+    ///     .build();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn redact(mut self, pattern: Regex) -> Self {
+        self.config.redactions.push(pattern);
+        self
+    }
 
-impl Default for LoggerConfig {
-    /// Creates a default configuration with:
-    /// - `show_file_info`: `true` - Show file/line information
-    /// - `show_date_in_stdout`: `false` - Only show time in stdout
-    /// - `use_colors`: `true` - Use colors in stdout output
-    /// - `level`: `Info` - Only show Info level and above
-    fn default() -> Self {
-        Self {
-            show_file_info: true,
-            show_date_in_stdout: false,
-            use_colors: true,
-            level: LevelFilter::Info,
-        }
+    /// Set the maximum length of log messages, in bytes.
+    ///
+    /// With `Some(limit)`, longer messages are cut at the last character
+    /// boundary within the limit and end with `…(truncated)`, so a component
+    /// logging a huge blob can't flood the terminal. The limit applies to
+    /// the message only, not to the timestamp, level or key-values, and to
+    /// the log file too unless [`max_file_message_len`](Self::max_file_message_len)
+    /// is set. In text output, the [global fields](Self::add_global_field)
+    /// appended to the message count towards the limit.
+    ///
+    /// Default: `None`
+    pub fn max_message_len(mut self, limit: Option<usize>) -> Self {
+        self.config.max_message_len = limit;
+        self
     }
-}
 
-impl LoggerConfig {
-    /// Create a new logger configuration with default settings.
+    /// Set a separate maximum message length for file output, in bytes.
     ///
-    /// This is equivalent to calling `LoggerConfig::default()`.
-    pub fn new() -> Self {
-        Self::default()
+    /// Log files are often allowed to keep more than the terminal, e.g.
+    /// `max_message_len(Some(1_000))` with `max_file_message_len(Some(100_000))`.
+    /// Use `Some(usize::MAX)` to never truncate file messages.
+    ///
+    /// Default: `None` (use [`max_message_len`](Self::max_message_len))
+    pub fn max_file_message_len(mut self, limit: Option<usize>) -> Self {
+        self.config.max_file_message_len = limit;
+        self
     }
 
-    /// Create a new configuration builder.
+    /// Set whether `file:line` in stdout logs is a clickable hyperlink.
     ///
-    /// This returns a [`LoggerConfigBuilder`] that can be used to construct
-    /// a custom configuration with a fluent API.
+    /// When enabled together with `show_file_info` and `use_colors`, the file
+    /// information is wrapped in an OSC 8 hyperlink escape sequence pointing to
+    /// the absolute source path, so it can be clicked to open the file.
+    /// Records without a source file, whose location is shown as a
+    /// placeholder, are not linked.
+    ///
+    /// OSC 8 is supported by most modern terminals (iTerm2, WezTerm, kitty,
+    /// Windows Terminal, GNOME Terminal and other VTE-based terminals, foot).
+    /// Terminals without support usually just print the plain `file:line`,
+    /// but some older ones may show stray characters. Log files are never
+    /// affected.
+    ///
+    /// Default: `false`
+    pub fn file_hyperlinks(mut self, enabled: bool) -> Self {
+        self.config.file_hyperlinks = enabled;
+        self
+    }
+
+    /// Set the URL template used for file hyperlinks.
+    ///
+    /// The `{path}` placeholder is replaced by the absolute source path and
+    /// `{line}` by the line number. This allows opening files directly in an
+    /// editor, e.g. `"vscode://file{path}:{line}"`. When `None`, a plain
+    /// `file://{path}` URL is used.
+    ///
+    /// Default: `None`
+    pub fn hyperlink_template(mut self, template: Option<String>) -> Self {
+        self.config.hyperlink_template = template;
+        self
+    }
+
+    /// Set whether records are written to the console.
+    ///
+    /// When disabled, nothing is printed to stdout or stderr and records only
+    /// go to the log files, e.g. for background services. This is cheaper
+    /// than redirecting stdout to `/dev/null`, as console lines aren't even
+    /// formatted.
+    ///
+    /// Default: `true`
+    pub fn stdout_enabled(mut self, enabled: bool) -> Self {
+        self.config.stdout_enabled = enabled;
+        self
+    }
+
+    /// Set the console stream for warnings and errors.
+    ///
+    /// With `ErrorStream::Stderr`, records at `Warn` and `Error` level are
+    /// written to stderr while `Info`, `Debug` and `Trace` stay on stdout.
+    /// This is useful in containerized environments that collect the two
+    /// streams separately. File output is not affected.
+    ///
+    /// Default: `ErrorStream::Stdout`
+    pub fn error_stream(mut self, stream: ErrorStream) -> Self {
+        self.config.error_stream = stream;
+        self
+    }
+
+    /// Set whether console lines start with a severity prefix for systemd.
+    ///
+    /// When enabled, each console line starts with the record's syslog
+    /// severity in angle brackets, as described in `sd-daemon(3)`: `<3>` for
+    /// `Error`, `<4>` for `Warn`, `<6>` for `Info` and `<7>` for `Debug` and
+    /// `Trace`. The journal reads the prefix as the entry's priority, so a
+    /// service run by systemd gets correct priorities without a syslog
+    /// socket. Colors are turned off, as the journal doesn't keep them.
+    ///
+    /// Default: `false`
+    pub fn systemd_stdout(mut self, enabled: bool) -> Self {
+        self.config.systemd_stdout = enabled;
+        self
+    }
+
+    /// Set whether to keep a machine-readable index of rotated log files.
+    ///
+    /// When enabled, every rotation appends an entry to `<file>.index` (e.g.
+    /// `app.log.index`) next to the log file. The index holds one JSON object
+    /// per line with the rotated file's `file` name, the `start` and `end` of
+    /// the time range it covers (RFC3339) and its number of `lines`. Log
+    /// viewers can use it to present a timeline without scanning every file.
+    ///
+    /// Default: `false`
+    pub fn write_index(mut self, enabled: bool) -> Self {
+        self.config.write_index = enabled;
+        self
+    }
+
+    /// Set whether to describe each logging session in a sidecar file.
+    ///
+    /// When enabled, `<file>.meta.json` (e.g. `app.log.meta.json`) is written
+    /// next to the log file when the logger starts. It holds a JSON object
+    /// with the `start_time` (RFC3339), the `hostname`, the `app_version`
+    /// (see [`app_version`](Self::app_version), `null` if unset) and the
+    /// main `config` options, including the effective `max_level` once the
+    /// logger is installed. [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown)
+    /// rewrites it with the `end_time` and the number of `records` written
+    /// per level, so tooling can inspect a log file without parsing it.
+    ///
+    /// Default: `false`
+    pub fn write_metadata_sidecar(mut self, enabled: bool) -> Self {
+        self.config.write_metadata_sidecar = enabled;
+        self
+    }
+
+    /// Set the version of the application, as recorded in the metadata
+    /// sidecar and the `{version}` token of the [init banner](Self::init_banner).
+    ///
+    /// The logger can't know the version of the binary it is linked into,
+    /// so pass the application's own, usually `env!("CARGO_PKG_VERSION")`.
     ///
     /// # Example
     ///
     /// ```
     /// use fstdout_logger::LoggerConfig;
-    /// use log::LevelFilter;
     ///
     /// let config = LoggerConfig::builder()
-    ///     .level(LevelFilter::Debug)
-    ///     .show_file_info(false)
+    ///     .write_metadata_sidecar(true)
+    ///     .app_version(env!("CARGO_PKG_VERSION"))
     ///     .build();
     /// ```
-    pub fn builder() -> LoggerConfigBuilder {
-        LoggerConfigBuilder::default()
+    ///
+    /// Default: `None`
+    pub fn app_version(mut self, version: impl Into<String>) -> Self {
+        self.config.app_version = Some(version.into());
+        self
     }
 
-    /// Create a new configuration optimized for production use.
+    /// Set whether console output bypasses Rust's `stdout` handle.
     ///
-    /// Production settings:
-    /// - `show_file_info`: `false` - Hide file/line for cleaner logs
-    /// - `show_date_in_stdout`: `false` - Only show time in stdout
-    /// - `use_colors`: `true` - Keep colors for readability
-    /// - `level`: `Info` - Hide Debug/Trace logs in production
-    pub fn production() -> Self {
-        Self {
-            show_file_info: false,
-            show_date_in_stdout: false,
-            use_colors: true,
-            level: LevelFilter::Info,
-        }
+    /// By default every line goes through `print!`, which takes the global
+    /// stdout lock for each write. In tight logging loops that lock shows up
+    /// in profiles. With `fast_stdout` enabled the logger keeps its own handle
+    /// to the stdout file descriptor and writes each line to it directly.
+    ///
+    /// The tradeoff is that the logger no longer coordinates with the rest of
+    /// the application: output from `println!` is buffered separately and may
+    /// appear out of order relative to log lines, and the test harness can't
+    /// capture log output. Loggers created with
+    /// [`FStdoutLogger::with_writer`](crate::FStdoutLogger::with_writer)
+    /// ignore this option.
+    ///
+    /// Default: `false`
+    pub fn fast_stdout(mut self, enabled: bool) -> Self {
+        self.config.fast_stdout = enabled;
+        self
     }
 
-    /// Create a new configuration optimized for development use.
+    /// Set a separator line that marks where a new run of the application begins.
     ///
-    /// Development settings:
-    /// - `show_file_info`: `true` - Show file/line for debugging
-    /// - `show_date_in_stdout`: `false` - Only show time in stdout
-    /// - `use_colors`: `true` - Use colors for readability
-    /// - `level`: `Debug` - Show Debug logs (but not Trace)
-    pub fn development() -> Self {
-        Self {
-            show_file_info: true,
-            show_date_in_stdout: false,
-            use_colors: true,
-            level: LevelFilter::Debug,
-        }
+    /// When the logger appends to a log file that already has content, the
+    /// separator (e.g. `"===== RESTART ====="`) is written to the file once,
+    /// before any records, so the runs can be told apart. It only applies to
+    /// [`FileMode::Append`]: the other modes start from an empty file, which
+    /// has no previous run to separate. It is not printed to stdout.
+    ///
+    /// Default: `None`
+    pub fn restart_separator(mut self, separator: Option<String>) -> Self {
+        self.config.restart_separator = separator;
+        self
     }
-}
 
-/// Builder for constructing a [`LoggerConfig`] using a fluent API.
-///
-/// This follows the builder pattern to provide a clean way to create
-/// custom logger configurations.
-///
-/// # Example
-///
-/// ```
-/// use fstdout_logger::LoggerConfigBuilder;
-/// use log::LevelFilter;
-///
-/// let config = LoggerConfigBuilder::default()
-///     .level(LevelFilter::Warn)
-///     .show_file_info(true)
-///     .show_date_in_stdout(true)
-///     .use_colors(false)
-///     .build();
-/// ```
-#[derive(Debug, Default)]
-pub struct LoggerConfigBuilder {
-    config: LoggerConfig,
-}
+    /// Set whether to log the effective configuration when the logger is installed.
+    ///
+    /// When enabled, the first record after init is a compact `Info` line
+    /// with the target `fstdout_logger`, such as
+    /// `logger started level=INFO format=Text colors=true destinations=console,app.log`.
+    /// It lists the per-module levels too, if any. The banner is written even
+    /// if the configured level hides `Info` records, so every log file records
+    /// how it was produced.
+    ///
+    /// Default: `false`
+    pub fn log_startup_banner(mut self, enabled: bool) -> Self {
+        self.config.log_startup_banner = enabled;
+        self
+    }
 
-impl LoggerConfigBuilder {
-    /// Set whether to show file and line information in log messages.
+    /// Set a banner line to write to each log file right after opening it.
     ///
-    /// When enabled, each log message will include the source file and line
-    /// number where the log was created. This is useful for debugging but
-    /// can make logs more verbose for production use.
+    /// The banner comes before any records (after the restart separator, if
+    /// any), so every log file records when and how logging started. These
+    /// tokens are replaced:
     ///
-    /// Default: `true`
-    pub fn show_file_info(mut self, show: bool) -> Self {
-        self.config.show_file_info = show;
+    /// - `{version}`: the application version set with
+    ///   [`app_version`](Self::app_version) (`unknown` if unset)
+    /// - `{level}`: the minimum level, e.g. `INFO`; log files are opened
+    ///   before the logger is installed, so they show [`level`](Self::level)
+    ///   even if the logger is installed with another level
+    /// - `{time}`: the current time in RFC 3339 format
+    ///
+    /// The banner is written as is, without a timestamp or
+    /// level prefix, and not printed to the console unless
+    /// [`init_banner_stdout`](Self::init_banner_stdout) is set.
+    ///
+    /// Default: `None`
+    pub fn init_banner(mut self, banner: Option<String>) -> Self {
+        self.config.init_banner = banner;
         self
     }
 
-    /// Set whether to show date in stdout logs.
+    /// Write `banner` to each log file right after opening it.
     ///
-    /// When enabled, stdout logs will include the full date (YYYY-MM-DD).
-    /// When disabled, only the time (HH:MM:SS) will be shown.
-    /// Note: Log files always include the full date regardless of this setting.
+    /// This is a shorthand for [`init_banner`](Self::init_banner)`(Some(banner))`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::LoggerConfig;
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .with_banner("myapp {version} started at {time}, level {level}")
+    ///     .app_version(env!("CARGO_PKG_VERSION"))
+    ///     .build();
+    /// ```
+    pub fn with_banner(self, banner: impl Into<String>) -> Self {
+        self.init_banner(Some(banner.into()))
+    }
+
+    /// Set whether the init banner is also printed to the console.
+    ///
+    /// When enabled, the banner is printed once when the logger is installed,
+    /// which also gives loggers without a log file a banner. It has no effect
+    /// without an [`init_banner`](Self::init_banner) or when
+    /// [`stdout_enabled`](Self::stdout_enabled) is off.
     ///
     /// Default: `false`
-    pub fn show_date_in_stdout(mut self, show: bool) -> Self {
-        self.config.show_date_in_stdout = show;
+    pub fn init_banner_stdout(mut self, enabled: bool) -> Self {
+        self.config.init_banner_stdout = enabled;
         self
     }
 
-    /// Set whether to use colors in stdout logs.
+    /// Write audit events to a separate file.
     ///
-    /// When enabled, different log levels will be displayed in different colors:
-    /// - ERROR: Red
-    /// - WARN: Yellow
-    /// - INFO: Blue
-    /// - DEBUG: Green
-    /// - TRACE: Default terminal color
+    /// Records logged with [`audit!`](crate::audit) go to this file instead
+    /// of the log file; they are still printed to stdout. The audit file is
+    /// always opened in [`FileMode::Append`] mode, so the trail of previous
+    /// runs is never archived or truncated, and uses the same format as the
+    /// log file. Without an audit file, audit events are written to the log
+    /// file like any other record.
     ///
-    /// Note: Log files never include color codes regardless of this setting.
+    /// Default: `None`
+    pub fn audit_file(mut self, path: Option<PathBuf>) -> Self {
+        self.config.audit_file = path;
+        self
+    }
+
+    /// Keep the most recent formatted lines in memory.
     ///
-    /// Default: `true`
-    pub fn use_colors(mut self, use_colors: bool) -> Self {
-        self.config.use_colors = use_colors;
+    /// With a capacity, the logger retains the last `capacity` records that
+    /// passed its filters, formatted like file lines without the trailing
+    /// separator, and evicts older ones as new records arrive. They can be
+    /// read with [`LoggerHandle::recent_logs`](crate::LoggerHandle::recent_logs),
+    /// e.g. to attach them to a crash report, whether or not the logger writes
+    /// to a file. The capacity is fixed when the logger is created.
+    ///
+    /// Default: `None`
+    pub fn ring_buffer_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.config.ring_buffer_capacity = capacity;
         self
     }
 
-    /// Set the minimum log level to display.
+    /// Send every formatted stdout line to a channel as well.
     ///
-    /// This filters log messages based on their level:
-    /// - `Error`: Only errors
-    /// - `Warn`: Errors and warnings
-    /// - `Info`: Errors, warnings, and info
-    /// - `Debug`: Errors, warnings, info, and debug
-    /// - `Trace`: All log levels
+    /// Each record that passes the filters is formatted like a stdout line,
+    /// without the trailing separator, and sent to `sender`, e.g. for a TUI
+    /// that renders the log in a widget. This works whether or not the lines
+    /// are also printed, see [`stdout_enabled`](Self::stdout_enabled); the
+    /// lines contain color codes if colors are enabled. The logger never
+    /// waits for the receiver: when the channel is full or the receiver is
+    /// gone, the line is dropped and the [`on_error`](Self::on_error) handler
+    /// gets a [`LogError::ChannelDropped`] with the number of lines dropped so
+    /// far. Size the channel for the bursts you expect.
     ///
-    /// Default: `Info`
-    pub fn level(mut self, level: LevelFilter) -> Self {
-        self.config.level = level;
+    /// Default: `None`
+    pub fn channel_sink(mut self, sender: Option<SyncSender<String>>) -> Self {
+        self.config.channel_sink = sender;
+        self
+    }
+
+    /// Also send records to a syslog daemon.
+    ///
+    /// Every record that passes the filters is sent as an RFC 5424 message
+    /// over the [`SyslogTarget`]'s transport, in addition to the console and
+    /// file output. The socket is opened with the first record and reopened
+    /// if sending fails, e.g. after the daemon restarted. It never blocks: if
+    /// the daemon falls behind and its queue is full, records are dropped.
+    /// Errors, including dropped records, are passed to the
+    /// [`on_error`](Self::on_error) handler. Requires the `syslog` feature.
+    ///
+    /// Default: `None`
+    #[cfg(feature = "syslog")]
+    pub fn syslog(mut self, target: Option<SyslogTarget>) -> Self {
+        self.config.syslog = target;
         self
     }
 
@@ -211,3 +2981,206 @@ impl LoggerConfigBuilder {
         self.config
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_toml_and_json() {
+        let toml = r#"
+            file = "logs/app.log"
+            level = "DEBUG"
+            file_format = "logfmt"
+            use_colors = false
+            show_file_info = false
+            show_date_in_stdout = true
+            show_target = true
+            show_module_path = true
+            show_pid = true
+            show_pid_in_stdout = true
+            show_sequence = true
+            stdout_enabled = false
+            systemd_stdout = true
+            async_file = true
+        "#;
+        let (config, path) = LoggerConfig::from_toml_str(toml).unwrap();
+        assert_eq!(path, Some(PathBuf::from("logs/app.log")));
+        assert_eq!(config.level, LevelFilter::Debug);
+        assert_eq!(config.file_format, FileFormat::Logfmt);
+        assert!(!config.use_colors);
+        assert!(!config.show_file_info);
+        assert!(config.show_date_in_stdout);
+        assert!(config.show_target);
+        assert!(config.show_module_path);
+        assert!(config.show_pid);
+        assert!(config.show_pid_in_stdout);
+        assert!(config.show_sequence);
+        assert!(!config.stdout_enabled);
+        assert!(config.systemd_stdout);
+        assert!(config.async_file);
+
+        // The same document as JSON gives the same configuration
+        let value: toml::Value = toml::from_str(toml).unwrap();
+        let json = serde_json::to_string(&value).unwrap();
+        let (from_json, json_path) = LoggerConfig::from_json_str(&json).unwrap();
+        assert_eq!(json_path, path);
+        assert_eq!(from_json.level, config.level);
+        assert_eq!(from_json.file_format, config.file_format);
+        let options = |config: &LoggerConfig| {
+            [
+                config.use_colors,
+                config.show_file_info,
+                config.show_date_in_stdout,
+                config.show_target,
+                config.show_module_path,
+                config.show_pid,
+                config.show_pid_in_stdout,
+                config.show_sequence,
+                config.stdout_enabled,
+                config.systemd_stdout,
+                config.async_file,
+            ]
+        };
+        assert_eq!(options(&from_json), options(&config));
+
+        // Missing keys keep their defaults
+        let (config, path) = LoggerConfig::from_json_str("{}").unwrap();
+        let default = LoggerConfig::default();
+        assert!(path.is_none());
+        assert_eq!(config.level, default.level);
+        assert_eq!(config.use_colors, default.use_colors);
+        assert_eq!(config.file_format, default.file_format);
+
+        // Levels may be numbers, as integers or in a string like `level_str`
+        let (config, _) = LoggerConfig::from_toml_str("level = \"2\"").unwrap();
+        assert_eq!(config.level, LevelFilter::Warn);
+        let (config, _) = LoggerConfig::from_toml_str("level = 4").unwrap();
+        assert_eq!(config.level, LevelFilter::Debug);
+        let (config, _) = LoggerConfig::from_json_str(r#"{"level": 1}"#).unwrap();
+        assert_eq!(config.level, LevelFilter::Error);
+
+        for invalid in [
+            "level = \"loud\"",
+            "level = 6",
+            "level = -1",
+            "file_format = \"xml\"",
+            "use_colours = true",
+            "use_colors = \"yes\"",
+        ] {
+            let error = LoggerConfig::from_toml_str(invalid).unwrap_err();
+            assert!(matches!(error, LogError::InvalidConfig(_)), "{invalid}");
+        }
+        assert!(matches!(
+            LoggerConfig::from_json_str("{\"level\": \"info\""),
+            Err(LogError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_env_filter() {
+        let config =
+            LoggerConfig::from_env_filter(" info , myapp=debug,,hyper = warn,noisy ").unwrap();
+
+        assert_eq!(config.level, LevelFilter::Info);
+        assert_eq!(
+            config.module_levels,
+            vec![
+                ("myapp".to_string(), LevelFilter::Debug),
+                ("hyper".to_string(), LevelFilter::Warn),
+                ("noisy".to_string(), LevelFilter::Trace),
+            ]
+        );
+        assert_eq!(config.module_level("myapp::core"), Some(LevelFilter::Debug));
+    }
+
+    #[test]
+    fn test_from_env_filter_defaults() {
+        let config = LoggerConfig::from_env_filter("").unwrap();
+        assert_eq!(config.level, LevelFilter::Info);
+        assert!(config.module_levels.is_empty());
+
+        let config = LoggerConfig::from_env_filter("myapp=trace").unwrap();
+        assert_eq!(config.level, LevelFilter::Info);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_quiet_windows() {
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let config = LoggerConfig::builder()
+            .quiet_window(at(2, 0), at(3, 30))
+            .quiet_window(at(23, 0), at(1, 0))
+            .quiet_level(LevelFilter::Error)
+            .build();
+
+        assert_eq!(config.quiet_level_at(at(2, 0)), Some(LevelFilter::Error));
+        assert_eq!(config.quiet_level_at(at(3, 29)), Some(LevelFilter::Error));
+        assert_eq!(config.quiet_level_at(at(3, 30)), None);
+        assert_eq!(config.quiet_level_at(at(12, 0)), None);
+
+        // Wrapping around midnight
+        assert_eq!(config.quiet_level_at(at(23, 30)), Some(LevelFilter::Error));
+        assert_eq!(config.quiet_level_at(at(0, 30)), Some(LevelFilter::Error));
+        assert_eq!(config.quiet_level_at(at(1, 0)), None);
+
+        assert_eq!(LoggerConfig::default().quiet_level_at(at(2, 0)), None);
+    }
+
+    #[test]
+    fn test_verbosity() {
+        let level = |verbose, quiet| {
+            LoggerConfig::builder()
+                .verbosity(verbose, quiet)
+                .build()
+                .level
+        };
+
+        assert_eq!(level(0, 0), LevelFilter::Info);
+        assert_eq!(level(1, 0), LevelFilter::Debug);
+        assert_eq!(level(2, 0), LevelFilter::Trace);
+        assert_eq!(level(0, 1), LevelFilter::Warn);
+        assert_eq!(level(0, 2), LevelFilter::Error);
+        assert_eq!(level(0, 3), LevelFilter::Off);
+        assert_eq!(level(2, 1), LevelFilter::Debug);
+
+        // Clamped at both ends
+        assert_eq!(level(5, 0), LevelFilter::Trace);
+        assert_eq!(level(u8::MAX, 0), LevelFilter::Trace);
+        assert_eq!(level(0, 5), LevelFilter::Off);
+        assert_eq!(level(0, u8::MAX), LevelFilter::Off);
+    }
+
+    #[test]
+    fn test_level_str() {
+        let level = |value| {
+            LoggerConfig::builder()
+                .level_str(value)
+                .map(|builder| builder.build().level)
+        };
+
+        assert_eq!(level("warn").unwrap(), LevelFilter::Warn);
+        assert_eq!(level("WARN").unwrap(), LevelFilter::Warn);
+        assert_eq!(level("Trace").unwrap(), LevelFilter::Trace);
+        assert_eq!(level(" off ").unwrap(), LevelFilter::Off);
+        assert_eq!(level("0").unwrap(), LevelFilter::Off);
+        assert_eq!(level("1").unwrap(), LevelFilter::Error);
+        assert_eq!(level("5").unwrap(), LevelFilter::Trace);
+
+        for invalid in ["6", "-1", "99999999999999999999999", "verbose", ""] {
+            let err = level(invalid).unwrap_err();
+            assert!(matches!(err, LogError::InvalidLevel(_)));
+            assert!(err.to_string().contains(&format!("`{invalid}`")));
+        }
+    }
+
+    #[test]
+    fn test_from_env_filter_malformed() {
+        let err = LoggerConfig::from_env_filter("info,foo=notalevel").unwrap_err();
+        assert!(matches!(err, LogError::InvalidFilter(_)));
+        assert!(err.to_string().contains("notalevel"));
+
+        assert!(LoggerConfig::from_env_filter("=debug").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Owned log events for custom processing.
+//!
+//! A logger created with [`FStdoutLogger::with_processor`](crate::FStdoutLogger::with_processor)
+//! doesn't write anything itself. It turns every accepted record into a
+//! [`LogEvent`] and sends it over a channel to a consumer owned by the
+//! application.
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use log::{Level, Record};
+use std::time::SystemTime;
+
+/// A log record with owned fields, sent to a user-supplied processor.
+///
+/// Unlike [`Record`], which borrows from the logging call site, a
+/// `LogEvent` can be moved to another thread and kept as long as needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEvent {
+    /// Level of the record
+    pub level: Level,
+
+    /// Target of the record, usually its module path
+    pub target: String,
+
+    /// Source file of the logging call, if known
+    pub file: Option<String>,
+
+    /// Source line of the logging call, if known
+    pub line: Option<u32>,
+
+    /// The formatted message, with configured redactions applied
+    pub message: String,
+
+    /// When the record was logged
+    ///
+    /// This is a `SystemTime` with or without the `chrono` feature; use
+    /// [`datetime`](Self::datetime) for a chrono date and time.
+    pub timestamp: SystemTime,
+}
+
+impl LogEvent {
+    /// Capture the fields of a record logged at `timestamp`, with its
+    /// already rendered `message`.
+    pub(crate) fn from_record(record: &Record, message: String, timestamp: SystemTime) -> Self {
+        Self {
+            level: record.level(),
+            target: record.target().to_string(),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            message,
+            timestamp,
+        }
+    }
+
+    /// Get the time the record was logged as a chrono UTC date and time.
+    ///
+    /// Convert it with `with_timezone` for another time zone, e.g.
+    /// `event.datetime().with_timezone(&chrono::Local)`.
+    #[cfg(feature = "chrono")]
+    pub fn datetime(&self) -> DateTime<Utc> {
+        self.timestamp.into()
+    }
+}
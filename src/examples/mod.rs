@@ -1,10 +1,11 @@
+use crate::clock::Timestamp;
 use colored::Colorize;
 
 /// Display example log messages with colors to demonstrate the output format
 pub fn show_colored_log_examples() {
     println!("=== COLORED LOG EXAMPLES ===");
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S");
+    let timestamp = Timestamp::now().format("%H:%M:%S");
     let colored_time = timestamp.to_string().bright_black();
 
     println!(
@@ -47,7 +48,7 @@ pub fn show_colored_log_examples() {
 pub fn show_plain_log_examples() {
     println!("=== PLAIN TEXT LOG EXAMPLES ===");
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S");
+    let timestamp = Timestamp::now().format("%H:%M:%S");
 
     println!("[{timestamp} TRACE] This is a TRACE message");
 
@@ -70,7 +71,7 @@ pub fn show_plain_log_examples() {
 pub fn show_file_info_examples() {
     println!("=== LOGS WITH FILE INFO ===");
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S");
+    let timestamp = Timestamp::now().format("%H:%M:%S");
     let file = "examples/show_colors.rs";
     let line = 42;
 
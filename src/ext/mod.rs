@@ -0,0 +1,85 @@
+//! Extension traits for logging from ordinary code.
+//!
+//! This module provides helpers that reduce boilerplate at call sites, such
+//! as logging the error of a `Result` without breaking a method chain.
+
+use log::{Level, Metadata, Record};
+use std::fmt::Display;
+use std::panic::Location;
+
+/// Target of the records logged by [`LogResultExt::log_err`].
+pub const LOG_ERR_TARGET: &str = "log_err";
+
+/// Log the error of a `Result` through the installed logger.
+///
+/// The [`log_err!`](crate::log_err) macro calls
+/// [`log_err_with_target`](LogResultExt::log_err_with_target) with the
+/// calling module as the target, like the `log` crate's macros do, so the
+/// caller's module level applies.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::{LogResultExt, log_err};
+/// use log::Level;
+///
+/// fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+///     input.parse::<u32>().log_err(Level::Warn)
+/// }
+///
+/// fn parse_here(input: &str) -> Result<u32, std::num::ParseIntError> {
+///     log_err!(input.parse::<u32>(), Level::Warn)
+/// }
+///
+/// assert!(parse("42").is_ok());
+/// assert!(parse_here("forty-two").is_err());
+/// ```
+pub trait LogResultExt {
+    /// Log the error, if any, at the given level and return `self` unchanged.
+    ///
+    /// The record points at the caller's file and line, not at this helper,
+    /// and has the [`LOG_ERR_TARGET`] target. `Ok` values are passed through
+    /// without logging anything, and so are errors the logger's level or
+    /// module levels filter out.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The level to log the error at.
+    fn log_err(self, level: Level) -> Self;
+
+    /// Log the error, if any, like [`log_err`](Self::log_err), with the
+    /// given target.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The level to log the error at.
+    /// * `target` - The target of the record, usually the caller's module path.
+    fn log_err_with_target(self, level: Level, target: &str) -> Self;
+}
+
+impl<T, E: Display> LogResultExt for Result<T, E> {
+    #[track_caller]
+    fn log_err(self, level: Level) -> Self {
+        self.log_err_with_target(level, LOG_ERR_TARGET)
+    }
+
+    #[track_caller]
+    fn log_err_with_target(self, level: Level, target: &str) -> Self {
+        if let Err(error) = &self
+            && level <= log::max_level()
+            && log::logger().enabled(&Metadata::builder().level(level).target(target).build())
+        {
+            let location = Location::caller();
+            log::logger().log(
+                &Record::builder()
+                    .args(format_args!("{error}"))
+                    .level(level)
+                    .target(target)
+                    .file(Some(location.file()))
+                    .line(Some(location.line()))
+                    .build(),
+            );
+        }
+        self
+    }
+}
@@ -4,10 +4,186 @@
 //! and for writing to log files. It handles colored output, timestamp formatting,
 //! and determining which information to include in log messages.
 
-use colored::{ColoredString, Colorize};
+use colored::{Color, ColoredString, Colorize};
+use log::kv::{self, Key, Source, Value, VisitSource, VisitValue};
 use log::{Level, Record};
+use std::fmt::Write;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
-use crate::config::LoggerConfig;
+use crate::LogError;
+use crate::clock::{self, Clock, SystemClock, Timestamp};
+#[cfg(feature = "syslog")]
+use crate::config::SyslogTarget;
+use crate::config::{
+    ColorIntensity, ColorMode, ColorStyle, DurationFormat, ErrorStream, FileFormat, LevelScale,
+    LevelStyle, LoggerConfig, TimeZoneMode, TimestampSource,
+};
+
+/// Colors used for the level labels in terminal output.
+///
+/// Each level has an optional foreground color (`None` keeps the terminal's
+/// default color) and can be shown in bold. Colors are `colored` crate
+/// [`Color`] values, so `Color::TrueColor` can be used for arbitrary RGB
+/// colors on terminals that support them.
+///
+/// The default scheme matches the built-in colors:
+///
+/// - `Error`: Bold Red
+/// - `Warn`: Bold Yellow
+/// - `Info`: Bold Blue
+/// - `Debug`: Green
+/// - `Trace`: Normal terminal color
+///
+/// # Example
+///
+/// ```
+/// use fstdout_logger::{Color, ColorScheme, LoggerConfig};
+///
+/// let config = LoggerConfig::builder()
+///     .color_scheme(ColorScheme::default().info(Color::Cyan))
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorScheme {
+    /// Style of `ERROR` labels
+    error: LabelStyle,
+
+    /// Style of `WARN` labels
+    warn: LabelStyle,
+
+    /// Style of `INFO` labels
+    info: LabelStyle,
+
+    /// Style of `DEBUG` labels
+    debug: LabelStyle,
+
+    /// Style of `TRACE` labels
+    trace: LabelStyle,
+}
+
+/// Color and weight of a single level label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LabelStyle {
+    /// Foreground color, or `None` for the terminal's default
+    color: Option<Color>,
+
+    /// Whether the label is bold
+    bold: bool,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            error: LabelStyle {
+                color: Some(Color::Red),
+                bold: true,
+            },
+            warn: LabelStyle {
+                color: Some(Color::Yellow),
+                bold: true,
+            },
+            info: LabelStyle {
+                color: Some(Color::Blue),
+                bold: true,
+            },
+            debug: LabelStyle {
+                color: Some(Color::Green),
+                bold: false,
+            },
+            trace: LabelStyle {
+                color: None,
+                bold: false,
+            },
+        }
+    }
+}
+
+impl ColorScheme {
+    /// Set the color of `ERROR` labels.
+    pub fn error(mut self, color: Color) -> Self {
+        self.error.color = Some(color);
+        self
+    }
+
+    /// Set the color of `WARN` labels.
+    pub fn warn(mut self, color: Color) -> Self {
+        self.warn.color = Some(color);
+        self
+    }
+
+    /// Set the color of `INFO` labels.
+    pub fn info(mut self, color: Color) -> Self {
+        self.info.color = Some(color);
+        self
+    }
+
+    /// Set the color of `DEBUG` labels.
+    pub fn debug(mut self, color: Color) -> Self {
+        self.debug.color = Some(color);
+        self
+    }
+
+    /// Set the color of `TRACE` labels.
+    pub fn trace(mut self, color: Color) -> Self {
+        self.trace.color = Some(color);
+        self
+    }
+
+    /// Set whether the label of `level` is shown in bold.
+    pub fn bold(mut self, level: Level, bold: bool) -> Self {
+        self.style_mut(level).bold = bold;
+        self
+    }
+
+    /// Get the color used for the label of `level`, if any.
+    pub fn color(&self, level: Level) -> Option<Color> {
+        self.style(level).color
+    }
+
+    /// Get the style of a level.
+    fn style(&self, level: Level) -> &LabelStyle {
+        match level {
+            Level::Error => &self.error,
+            Level::Warn => &self.warn,
+            Level::Info => &self.info,
+            Level::Debug => &self.debug,
+            Level::Trace => &self.trace,
+        }
+    }
+
+    /// Get the style of a level for modification.
+    fn style_mut(&mut self, level: Level) -> &mut LabelStyle {
+        match level {
+            Level::Error => &mut self.error,
+            Level::Warn => &mut self.warn,
+            Level::Info => &mut self.info,
+            Level::Debug => &mut self.debug,
+            Level::Trace => &mut self.trace,
+        }
+    }
+
+    /// Apply the style of `level` to its label or any other text, such as
+    /// a whole line.
+    ///
+    /// Any `intensity` other than `ColorIntensity::Scheme` replaces the
+    /// per-level bold setting.
+    fn paint(&self, level: Level, text: &str, intensity: ColorIntensity) -> ColoredString {
+        let style = self.style(level);
+        let label = match style.color {
+            Some(color) => text.color(color),
+            None => text.normal(),
+        };
+        match intensity {
+            ColorIntensity::Scheme if style.bold => label.bold(),
+            ColorIntensity::Scheme | ColorIntensity::Normal => label,
+            ColorIntensity::Bold => label.bold(),
+            ColorIntensity::Dim => label.dimmed(),
+        }
+    }
+}
 
 /// Handles log formatting for both stdout and file outputs.
 ///
@@ -19,105 +195,691 @@ use crate::config::LoggerConfig;
 pub struct LogFormatter {
     /// The configuration that controls formatting behavior
     config: LoggerConfig,
+
+    /// Directory relative source paths are resolved against for hyperlinks
+    base_dir: Option<PathBuf>,
+
+    /// Whether colors are used for records written to stdout
+    stdout_colors: bool,
+
+    /// Whether colors are used for records written to stderr
+    stderr_colors: bool,
+
+    /// Whether escape sequences are stripped from records written to stdout
+    stdout_stripped: bool,
+
+    /// Whether escape sequences are stripped from records written to stderr
+    stderr_stripped: bool,
+
+    /// Timestamp format used for stdout
+    stdout_time_format: String,
+
+    /// Timestamp format used for text file lines
+    file_time_format: String,
+
+    /// Parsed custom layout for stdout lines
+    stdout_template: Option<Vec<TemplatePart>>,
+
+    /// Parsed custom layout for text file lines
+    file_template: Option<Vec<TemplatePart>>,
+
+    /// When the formatter was created, the origin of elapsed timestamps
+    started: Instant,
+
+    /// Counter of stdout records within the current second, if enabled
+    stdout_counter: Option<SecondCounter>,
+
+    /// Counter of text file records within the current second, if enabled
+    file_counter: Option<SecondCounter>,
+
+    /// Delimiter ending file records, with the configured line ending
+    file_separator: String,
+
+    /// ID of the current process, which never changes
+    pid: u32,
+
+    /// Icon prefix for each level's stdout label, indexed by `Level as usize - 1`
+    icons: [String; 5],
+
+    /// Source of the time of each record
+    clock: Box<dyn Clock>,
+}
+
+/// Numbers the records formatted within the same second.
+#[derive(Default)]
+struct SecondCounter(Mutex<(i64, u32)>);
+
+impl SecondCounter {
+    /// Get the number of the next record in `second`, starting at 1.
+    fn next(&self, second: i64) -> u32 {
+        let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.0 == second {
+            state.1 += 1;
+        } else {
+            *state = (second, 1);
+        }
+        state.1
+    }
+}
+
+/// What the logger adds to the lines of a record beyond the record itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LineStamp {
+    /// Sequence number of the record, if `show_sequence` is enabled
+    pub(crate) sequence: Option<u64>,
+
+    /// Time since the previous record, if `show_delta` is enabled
+    pub(crate) delta: Option<Duration>,
 }
 
 impl LogFormatter {
     /// Create a new formatter with the given configuration.
     ///
+    /// Custom timestamp formats that fail [`validate_time_format`] are
+    /// replaced by the defaults, and templates that fail
+    /// [`validate_template`] are ignored.
+    ///
     /// # Arguments
     ///
     /// * `config` - Configuration options that control formatting behavior
     pub fn new(config: LoggerConfig) -> Self {
-        Self { config }
+        Self::with_clock(config, SystemClock)
     }
 
-    /// Get the appropriate color for a log level.
+    /// Create a new formatter that reads the time from a custom clock.
+    ///
+    /// This works like [`new`](Self::new), but the timestamps of wall-clock
+    /// records come from `clock`, so tests can format records at a fixed
+    /// time and compare the exact output. Timestamps relative to the start
+    /// (`TimestampSource::SinceStart`) still use the monotonic system clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Configuration options that control formatting behavior
+    /// * `clock` - Source of the current time
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::{Clock, LogFormatter, LoggerConfig, TimeZoneMode};
+    /// use log::{Level, Record};
+    /// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    ///
+    /// struct FixedClock;
+    ///
+    /// impl Clock for FixedClock {
+    ///     fn now(&self) -> SystemTime {
+    ///         UNIX_EPOCH + Duration::from_secs(1_717_243_200)
+    ///     }
+    /// }
+    ///
+    /// let config = LoggerConfig::builder()
+    ///     .use_colors(false)
+    ///     .show_file_info(false)
+    ///     .timezone(TimeZoneMode::Utc)
+    ///     .build();
+    /// let formatter = LogFormatter::with_clock(config, FixedClock);
+    ///
+    /// let args = format_args!("ready");
+    /// let record = Record::builder().args(args).level(Level::Info).build();
+    /// assert_eq!(formatter.format_stdout(&record), "[12:00:00 INFO] ready");
+    /// ```
+    pub fn with_clock(config: LoggerConfig, clock: impl Clock + 'static) -> Self {
+        let base_dir = if config.file_hyperlinks {
+            std::env::current_dir().ok()
+        } else {
+            None
+        };
+        let (stdout_colors, stderr_colors) =
+            if config.systemd_stdout || (config.detect_test_mode && running_under_test()) {
+                (false, false)
+            } else {
+                match config.color_mode {
+                    ColorMode::Explicit => (config.use_colors, config.use_colors),
+                    ColorMode::Auto => (io::stdout().is_terminal(), io::stderr().is_terminal()),
+                }
+            };
+        let (stdout_stripped, stderr_stripped) = if config.strip_colors_on_redirect {
+            (!io::stdout().is_terminal(), !io::stderr().is_terminal())
+        } else {
+            (false, false)
+        };
+        let default_stdout_format = if config.show_date_in_stdout {
+            "%Y-%m-%d %H:%M:%S"
+        } else {
+            "%H:%M:%S"
+        };
+        let stdout_time_format =
+            valid_time_format(config.stdout_time_format.as_deref(), default_stdout_format);
+        let file_time_format =
+            valid_time_format(config.file_time_format.as_deref(), "%Y-%m-%d %H:%M:%S");
+        let stdout_template = config
+            .stdout_template
+            .as_deref()
+            .and_then(|template| parse_template(template).ok());
+        let file_template = config
+            .file_template
+            .as_deref()
+            .and_then(|template| parse_template(template).ok());
+        let counter = |format: &str| {
+            (config.intra_second_counter && !clock::has_subsecond_precision(format))
+                .then(SecondCounter::default)
+        };
+        let stdout_counter = counter(&stdout_time_format);
+        let file_counter = counter(&file_time_format);
+        let file_separator = config.file_record_separator();
+        let icons = level_icons(&config);
+        Self {
+            config,
+            base_dir,
+            stdout_colors,
+            stderr_colors,
+            stdout_stripped,
+            stderr_stripped,
+            stdout_time_format,
+            file_time_format,
+            stdout_template,
+            file_template,
+            started: Instant::now(),
+            stdout_counter,
+            file_counter,
+            file_separator,
+            pid: std::process::id(),
+            icons,
+            clock: Box::new(clock),
+        }
+    }
+
+    /// Get the current time from the clock, in the configured time zone.
+    pub(crate) fn now(&self) -> Timestamp {
+        Timestamp::from_system_time(self.clock.now(), self.config.timezone == TimeZoneMode::Utc)
+    }
+
+    /// Get the current time from the clock in local time, which rotation
+    /// boundaries are based on.
+    pub(crate) fn local_now(&self) -> Timestamp {
+        Timestamp::from_system_time(self.clock.now(), false)
+    }
+
+    /// Get the timestamp for a text line.
+    ///
+    /// This is the current time formatted with `format`, numbered with
+    /// `counter` if given, or the time elapsed since the formatter was created
+    /// with `TimestampSource::SinceStart`.
+    fn timestamp(&self, format: &str, counter: Option<&SecondCounter>) -> String {
+        match self.config.timestamp_source {
+            TimestampSource::WallClock => {
+                let now = self.now();
+                let mut timestamp = now.format(format);
+                if let Some(counter) = counter {
+                    let _ = write!(timestamp, ".{:03}", counter.next(now.unix_seconds()));
+                }
+                timestamp
+            }
+            TimestampSource::SinceStart => {
+                format!("+{:.3}s", self.started.elapsed().as_secs_f64())
+            }
+        }
+    }
+
+    /// Format the current time as RFC 3339 for structured file output.
+    ///
+    /// Uses the configured precision, or as few fractional digits as needed.
+    fn rfc3339_now(&self) -> String {
+        match self.config.file_rfc3339 {
+            Some(precision) => self.now().to_rfc3339_precise(precision),
+            None => self.now().to_rfc3339_short(),
+        }
+    }
+
+    /// Get the delimiter ending file records.
+    pub(crate) fn file_separator(&self) -> &str {
+        &self.file_separator
+    }
+
+    /// Append the process ID to a line's timestamp if `show` is set.
+    fn with_pid(&self, mut timestamp: String, show: bool) -> String {
+        if show {
+            let _ = write!(timestamp, " pid:{}", self.pid);
+        }
+        timestamp
+    }
+
+    /// Append the time since the previous record to a line's timestamp, if measured.
+    fn with_delta(&self, mut timestamp: String, delta: Option<Duration>) -> String {
+        if let Some(delta) = delta {
+            let _ = write!(timestamp, " (+{}ms)", delta.as_millis());
+        }
+        timestamp
+    }
+
+    /// Append the record's sequence number to a line's timestamp, if it has one.
+    fn with_sequence(&self, mut timestamp: String, sequence: Option<u64>) -> String {
+        if let Some(sequence) = sequence {
+            let _ = write!(
+                timestamp,
+                " #{sequence:0width$}",
+                width = self.config.sequence_width
+            );
+        }
+        timestamp
+    }
+
+    /// Check whether colors are used for a record at the given level.
+    ///
+    /// This depends on the console stream the record is written to and on
+    /// whether the level is one of the configured color levels.
+    pub(crate) fn colors_enabled(&self, level: Level) -> bool {
+        let stream_colors =
+            if self.config.error_stream == ErrorStream::Stderr && level <= Level::Warn {
+                self.stderr_colors
+            } else {
+                self.stdout_colors
+            };
+        stream_colors && self.config.color_levels.contains(&level)
+    }
+
+    /// Check whether escape sequences are stripped from a record at the given level.
+    ///
+    /// This depends on the console stream the record is written to.
+    fn strips_escapes(&self, level: Level) -> bool {
+        if self.config.error_stream == ErrorStream::Stderr && level <= Level::Warn {
+            self.stderr_stripped
+        } else {
+            self.stdout_stripped
+        }
+    }
+
+    /// Get the configuration used by this formatter.
+    pub(crate) fn config(&self) -> &LoggerConfig {
+        &self.config
+    }
+
+    /// Get the file and line shown for a record, or `None` to leave them out.
     ///
-    /// Returns a `ColoredString` with the appropriate color for the given log level,
-    /// or a plain string if colors are disabled.
+    /// Missing parts are replaced by the configured placeholders, unless the
+    /// file is missing and `skip_file_info_when_missing` is set.
+    fn location<'a>(&'a self, parts: &LineParts<'a>) -> Option<(&'a str, String)> {
+        let file = match parts.file {
+            Some(file) => file,
+            None if self.config.skip_file_info_when_missing => return None,
+            None => &self.config.missing_file_placeholder,
+        };
+        let line = match parts.line {
+            Some(line) => line.to_string(),
+            None => self.config.missing_line_placeholder.clone(),
+        };
+        Some((file, line))
+    }
+
+    /// Wrap `text` in an OSC 8 hyperlink pointing to the given source location.
     ///
-    /// # Colors Used
+    /// Relative paths are resolved against the working directory captured
+    /// when the formatter was created.
+    fn hyperlink(&self, file: &str, line: u32, text: &str) -> String {
+        let path = match &self.base_dir {
+            Some(base) => base.join(file),
+            None => PathBuf::from(file),
+        };
+        let path = path.to_string_lossy();
+        let url = match &self.config.hyperlink_template {
+            Some(template) => template
+                .replace("{path}", &path)
+                .replace("{line}", &line.to_string()),
+            None => format!("file://{path}"),
+        };
+        format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+    }
+
+    /// Get the appropriate color for a log level.
     ///
-    /// - `Error`: Bold Red
-    /// - `Warn`: Bold Yellow
-    /// - `Info`: Bold Blue
-    /// - `Debug`: Green
-    /// - `Trace`: Normal terminal color
+    /// Returns a `ColoredString` styled according to the configured
+    /// [`ColorScheme`], or a plain string if colors are disabled.
     ///
     /// # Arguments
     ///
     /// * `level` - The log level to get the color for
     fn get_level_color(&self, level: Level) -> ColoredString {
-        if !self.config.use_colors {
-            return level.as_str().normal();
+        let label = self.config.level_style.label(level);
+        if !self.colors_enabled(level) {
+            return label.normal();
         }
 
-        match level {
-            Level::Error => "ERROR".red().bold(),
-            Level::Warn => "WARN".yellow().bold(),
-            Level::Info => "INFO".blue().bold(),
-            Level::Debug => "DEBUG".green(),
-            Level::Trace => "TRACE".normal(),
+        self.config
+            .color_scheme
+            .paint(level, label, self.config.color_intensity)
+    }
+
+    /// Get the padding that aligns the label of `level` with the longest one
+    /// in the same style.
+    fn level_padding(&self, level: Level, style: LevelStyle) -> &'static str {
+        const WIDEST: &str = "     ";
+        if self.config.align_levels {
+            &WIDEST[..style.width() - style.label(level).len()]
+        } else {
+            ""
+        }
+    }
+
+    /// Render the message of a log record for text output.
+    ///
+    /// If `collapse_newlines` is configured, line breaks inside the message are
+    /// replaced so that each record stays on a single physical line.
+    ///
+    /// Records logged with the `*_fn!` macros are prefixed with the name of
+    /// the function they were logged from. The global fields are appended
+    /// as `key=value` pairs and count towards `limit`: the message is
+    /// truncated so that it fits together with them, while the fields are
+    /// always written in full.
+    fn render_message(&self, parts: &LineParts, limit: Option<usize>) -> String {
+        let message = match function_name(parts.key_values) {
+            Some(function) => format!("{function}: {}", self.render_args(parts)),
+            None => self.render_args(parts),
+        };
+        let mut fields = String::new();
+        for (key, value) in &self.config.global_fields {
+            fields.push(' ');
+            fields.push_str(key);
+            fields.push('=');
+            write_logfmt_value(&mut fields, value, "=", " ");
+        }
+        let limit = limit.map(|limit| limit.saturating_sub(fields.len()));
+        let message = truncate_message(message, limit) + &fields;
+        match self.config.collapse_newlines {
+            Some(replacement) => message
+                .replace("\r\n", "\n")
+                .replace('\n', replacement.encode_utf8(&mut [0; 4])),
+            None => message,
+        }
+    }
+
+    /// Render the arguments of a record, masking configured secrets.
+    ///
+    /// Redaction runs before truncation, so a secret cut off at the length
+    /// limit is still masked.
+    fn render_args(&self, parts: &LineParts) -> String {
+        self.render_message_text(&parts.args)
+    }
+
+    /// Render a message, masking matches of the configured redaction patterns.
+    pub(crate) fn render_message_text(&self, args: &std::fmt::Arguments) -> String {
+        let message = args.to_string();
+        #[cfg(feature = "regex")]
+        let message = redact(message, &self.config.redactions);
+        message
+    }
+
+    /// Collect the key-values of a record followed by the global fields.
+    ///
+    /// Global fields whose key was also logged with the record are left out.
+    fn collect_fields(&self, parts: &LineParts) -> Vec<(String, FieldValue)> {
+        let mut fields = collect_key_values(parts.key_values, self.config.duration_format);
+        for (key, value) in &self.config.global_fields {
+            if !fields.iter().any(|(k, _)| k == key) {
+                fields.push((key.clone(), FieldValue::String(value.clone())));
+            }
+        }
+        fields
+    }
+
+    /// Get the message length limit for file output.
+    fn file_message_limit(&self) -> Option<usize> {
+        self.config
+            .max_file_message_len
+            .or(self.config.max_message_len)
+    }
+
+    /// Render a record against a parsed template.
+    ///
+    /// The level is written in the given `style`, and colored if `colored`
+    /// is set; all other tokens are plain. The message is truncated to
+    /// `limit` bytes.
+    fn render_template(
+        &self,
+        template: &[TemplatePart],
+        parts: &LineParts,
+        timestamp: &str,
+        style: LevelStyle,
+        colored: bool,
+        limit: Option<usize>,
+    ) -> String {
+        let mut output = String::new();
+        for part in template {
+            match part {
+                TemplatePart::Literal(text) => output.push_str(text),
+                TemplatePart::Time => output.push_str(timestamp),
+                TemplatePart::Level if colored => {
+                    let _ = write!(output, "{}", self.get_level_color(parts.level));
+                    output.push_str(self.level_padding(parts.level, style));
+                }
+                TemplatePart::Level => {
+                    output.push_str(style.label(parts.level));
+                    output.push_str(self.level_padding(parts.level, style));
+                }
+                TemplatePart::File => {
+                    output.push_str(parts.file.unwrap_or(&self.config.missing_file_placeholder))
+                }
+                TemplatePart::Line => match parts.line {
+                    Some(line) => {
+                        let _ = write!(output, "{line}");
+                    }
+                    None => output.push_str(&self.config.missing_line_placeholder),
+                },
+                TemplatePart::Target => output.push_str(parts.target),
+                TemplatePart::Thread => {
+                    let thread = std::thread::current();
+                    match thread.name() {
+                        Some(name) => output.push_str(name),
+                        None => {
+                            let _ = write!(output, "{:?}", thread.id());
+                        }
+                    }
+                }
+                TemplatePart::Message => output.push_str(&self.render_message(parts, limit)),
+            }
+        }
+        output
+    }
+
+    /// Format a message from its parts, without building a [`Record`].
+    ///
+    /// This produces exactly what [`format_stdout`](Self::format_stdout) or
+    /// [`format_file`](Self::format_file) would for a record with the same
+    /// level, target, file, line and message, so the crate's formatting can
+    /// be reused outside of logging, e.g. for a CLI's own output. Stdout
+    /// lines have no trailing separator, file lines end with it.
+    /// Structured key-values can only be passed with a full `Record`.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The level of the message
+    /// * `target` - The target, usually a module path
+    /// * `file` - The source file, if known
+    /// * `line` - The source line, if known
+    /// * `args` - The message, e.g. from `format_args!`
+    /// * `is_stdout` - Whether to format for stdout instead of a file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fstdout_logger::{LogFormatter, LoggerConfig};
+    /// use log::Level;
+    ///
+    /// let formatter = LogFormatter::new(LoggerConfig::builder().use_colors(false).build());
+    /// let line = formatter.format_parts(
+    ///     Level::Warn,
+    ///     "mycli",
+    ///     Some("src/main.rs"),
+    ///     Some(12),
+    ///     format_args!("{} files skipped", 3),
+    ///     true,
+    /// );
+    /// assert!(line.ends_with(" WARN src/main.rs:12] 3 files skipped"));
+    /// ```
+    pub fn format_parts(
+        &self,
+        level: Level,
+        target: &str,
+        file: Option<&str>,
+        line: Option<u32>,
+        args: std::fmt::Arguments,
+        is_stdout: bool,
+    ) -> String {
+        let parts = LineParts {
+            level,
+            target,
+            file,
+            line,
+            module_path: None,
+            args,
+            key_values: &NO_KEY_VALUES,
+        };
+        if is_stdout {
+            self.stdout_line(&parts, LineStamp::default())
+        } else {
+            self.file_line(&parts, LineStamp::default())
         }
     }
 
     /// Format a log record for stdout
     pub fn format_stdout(&self, record: &Record) -> String {
-        let now = chrono::Local::now();
+        self.format_stdout_stamped(record, LineStamp::default())
+    }
+
+    /// Format a log record for stdout with the stamp the logger gave it.
+    pub(crate) fn format_stdout_stamped(&self, record: &Record, stamp: LineStamp) -> String {
+        self.stdout_line(&LineParts::from_record(record), stamp)
+    }
+
+    /// Format the parts of a line for stdout.
+    fn stdout_line(&self, parts: &LineParts, stamp: LineStamp) -> String {
+        let output = self.format_console(parts, stamp);
+        let output = if self.strips_escapes(parts.level) {
+            strip_ansi(&output)
+        } else {
+            output
+        };
+        if self.config.systemd_stdout {
+            format!("<{}>{output}", LevelScale::Syslog.code(parts.level))
+        } else {
+            output
+        }
+    }
 
-        // Format timestamp (HH:MM:SS) without date for stdout
-        let timestamp = if self.config.show_date_in_stdout {
-            now.format("%Y-%m-%d %H:%M:%S").to_string()
+    /// Format a log record for the console, with colors if enabled.
+    fn format_console(&self, parts: &LineParts, stamp: LineStamp) -> String {
+        // Format timestamp (HH:MM:SS by default) without date for stdout
+        let timestamp = self.timestamp(&self.stdout_time_format, self.stdout_counter.as_ref());
+
+        // In full-line mode the parts are left plain and the whole line is
+        // painted at the end, as their color resets would end it early
+        let use_colors = self.colors_enabled(parts.level);
+        let full_line = use_colors && self.config.color_style == ColorStyle::FullLine;
+
+        let output = if let Some(template) = &self.stdout_template {
+            self.render_template(
+                template,
+                parts,
+                &timestamp,
+                self.config.level_style,
+                use_colors && !full_line,
+                self.config.max_message_len,
+            )
+        } else {
+            self.format_console_default(parts, timestamp, stamp, use_colors, full_line)
+        };
+
+        if full_line {
+            self.config
+                .color_scheme
+                .paint(parts.level, &output, self.config.color_intensity)
+                .to_string()
         } else {
-            now.format("%H:%M:%S").to_string()
+            output
+        }
+    }
+
+    /// Format a log record in the default console layout.
+    fn format_console_default(
+        &self,
+        parts: &LineParts,
+        timestamp: String,
+        stamp: LineStamp,
+        use_colors: bool,
+        full_line: bool,
+    ) -> String {
+        let timestamp = self.with_pid(timestamp, self.config.show_pid_in_stdout);
+        let timestamp = self.with_sequence(timestamp, stamp.sequence);
+        let timestamp = self.with_delta(timestamp, stamp.delta);
+        let dim = |text: &str| {
+            if full_line {
+                text.to_string()
+            } else {
+                text.bright_black().to_string()
+            }
         };
 
         // Get colored log level
-        let level_str = self.get_level_color(record.level());
+        let level = if full_line {
+            self.config.level_style.label(parts.level).normal()
+        } else {
+            self.get_level_color(parts.level)
+        };
+        let level_str = format!(
+            "{}{}{}",
+            self.icons[parts.level as usize - 1],
+            level,
+            self.level_padding(parts.level, self.config.level_style)
+        );
+        let message = self.render_message(parts, self.config.max_message_len);
+        let level_str = match (self.config.show_target, use_colors) {
+            (true, true) => format!("{level_str} {}", dim(parts.target)),
+            (true, false) => format!("{level_str} {}", parts.target),
+            (false, _) => level_str,
+        };
 
         // Format with or without file info
-        if self.config.show_file_info {
-            let file = record.file().unwrap_or("unknown");
-            let line = record.line().unwrap_or(0);
-
-            if self.config.use_colors {
-                let file_info = format!("{file}:{line}").bright_black();
-                format!(
-                    "[{} {} {}] {}",
-                    timestamp.bright_black(),
-                    level_str,
-                    file_info,
-                    record.args()
-                )
+        let location = if self.config.show_file_info {
+            self.location(parts)
+        } else {
+            None
+        };
+        let prefix = if let Some((file, line)) = location {
+            if use_colors {
+                let mut file_info = dim(&format!("{file}:{line}"));
+                // A placeholder for a missing location has nothing to link to
+                if self.config.file_hyperlinks && parts.file.is_some() {
+                    file_info = self.hyperlink(file, parts.line.unwrap_or(0), &file_info);
+                }
+                format!("{} {} {}", dim(&timestamp), level_str, file_info)
             } else {
-                format!(
-                    "[{} {} {}:{}] {}",
-                    timestamp,
-                    level_str,
-                    file,
-                    line,
-                    record.args()
-                )
+                format!("{} {} {}:{}", timestamp, level_str, file, line)
             }
         } else {
             // Simpler format without file info
-            if self.config.use_colors {
-                format!(
-                    "[{} {}] {}",
-                    timestamp.bright_black(),
-                    level_str,
-                    record.args()
-                )
+            if use_colors {
+                format!("{} {}", dim(&timestamp), level_str)
             } else {
-                format!("[{} {}] {}", timestamp, level_str, record.args())
+                format!("{} {}", timestamp, level_str)
             }
-        }
+        };
+        self.enclose_prefix(&prefix, &message)
+    }
+
+    /// Put the prefix of a text line between its delimiters, followed by the message.
+    fn enclose_prefix(&self, prefix: &str, message: &str) -> String {
+        format!(
+            "{}{prefix}{}{}{message}",
+            self.config.prefix_open, self.config.prefix_close, self.config.prefix_message_separator
+        )
     }
 
     /// Format a log record for file output.
     ///
-    /// This creates a formatted log message for writing to a log file.
-    /// It always includes:
+    /// This creates a formatted log message for writing to a log file,
+    /// using the [`FileFormat`] selected in the configuration.
+    ///
+    /// In the default text format it always includes:
     /// - Full date and time (YYYY-MM-DD HH:MM:SS)
     /// - File and line information
     /// - Plain text (no color codes)
@@ -126,25 +888,2595 @@ impl LogFormatter {
     ///
     /// `[YYYY-MM-DD HH:MM:SS LEVEL file:line] message\n`
     ///
+    /// In JSON format each line is a complete object:
+    ///
+    /// `{"timestamp":"...","level":"INFO","target":"...","file":"...","line":1,"message":"..."}\n`
+    ///
+    /// Structured key-values attached to the record (`info!(id = 5; "...")`)
+    /// are written to a nested `fields` object, with numbers, booleans and
+    /// null kept as native JSON values. By convention, a `_type`
+    /// key is promoted to a top-level `type` field, so macros that capture
+    /// the type name of a logged value can expose it for structured debugging.
+    /// Likewise, the `_fn` key set by the `*_fn!` macros becomes `function`.
+    ///
     /// # Arguments
     ///
     /// * `record` - The log record to format
     ///
     /// # Returns
     ///
-    /// A formatted string ready for writing to a file (includes trailing newline)
+    /// A formatted string ready for writing to a file (ends with the
+    /// configured record separator and line ending, a newline by default)
     pub fn format_file(&self, record: &Record) -> String {
-        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
-        let file = record.file().unwrap_or("unknown");
-        let line = record.line().unwrap_or(0);
+        self.format_file_stamped(record, LineStamp::default())
+    }
+
+    /// Format a log record for file output with the stamp the logger gave it.
+    pub(crate) fn format_file_stamped(&self, record: &Record, stamp: LineStamp) -> String {
+        self.file_line(&LineParts::from_record(record), stamp)
+    }
 
+    /// Format the parts of a line for file output.
+    fn file_line(&self, parts: &LineParts, stamp: LineStamp) -> String {
+        match self.config.file_format {
+            FileFormat::Text => self.format_file_text(parts, stamp),
+            FileFormat::Json => self.format_file_json(parts, stamp),
+            FileFormat::Dual => format!(
+                "{TEXT_MARKER}{}{JSON_MARKER}{}",
+                self.format_file_text(parts, stamp),
+                self.format_file_json(parts, stamp)
+            ),
+            FileFormat::Logfmt => self.format_file_logfmt(parts, stamp),
+        }
+    }
+
+    /// Format a log record as an RFC 5424 syslog message.
+    ///
+    /// # Format
+    ///
+    /// `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID - - message`
+    ///
+    /// The priority combines the target's facility with the level's syslog
+    /// severity. There is no message ID or structured data.
+    #[cfg(feature = "syslog")]
+    pub(crate) fn format_syslog(
+        &self,
+        record: &Record,
+        target: &SyslogTarget,
+        hostname: &str,
+    ) -> String {
+        let parts = LineParts::from_record(record);
+        let priority = target.facility.code() * 8 + LevelScale::Syslog.code(parts.level);
         format!(
-            "[{} {} {}:{}] {}\n",
-            timestamp,
-            record.level(),
-            file,
-            line,
-            record.args()
+            "<{priority}>1 {} {} {} {} - - {}",
+            self.now().to_rfc3339_millis(),
+            syslog_field(hostname, 255),
+            syslog_field(&target.app_name, 48),
+            self.pid,
+            self.render_message(&parts, self.file_message_limit())
         )
     }
+
+    /// Format a log record as a line of key-value pairs.
+    fn format_file_logfmt(&self, parts: &LineParts, stamp: LineStamp) -> String {
+        let mut pairs = vec![
+            ("time".to_string(), self.rfc3339_now()),
+            ("level".to_string(), parts.level.as_str().to_string()),
+        ];
+        if let Some(scale) = self.config.numeric_levels {
+            pairs.push(("level_num".to_string(), scale.code(parts.level).to_string()));
+        }
+        if self.config.show_pid {
+            pairs.push(("pid".to_string(), self.pid.to_string()));
+        }
+        if let Some(sequence) = stamp.sequence {
+            pairs.push(("seq".to_string(), sequence.to_string()));
+        }
+        pairs.push(("target".to_string(), parts.target.to_string()));
+        if let Some(file) = parts.file {
+            pairs.push(("file".to_string(), file.to_string()));
+        }
+        if let Some(line) = parts.line {
+            pairs.push(("line".to_string(), line.to_string()));
+        }
+        pairs.push((
+            "msg".to_string(),
+            truncate_message(self.render_args(parts), self.file_message_limit()),
+        ));
+
+        let mut fields = self.collect_fields(parts);
+        for (key, name) in [(TYPE_KEY, "type"), (FUNCTION_KEY, "function")] {
+            if let Some(index) = fields.iter().position(|(k, _)| k == key) {
+                let (_, value) = fields.remove(index);
+                pairs.push((name.to_string(), value.as_str().to_string()));
+            }
+        }
+        pairs.extend(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, value.as_str().to_string())),
+        );
+
+        let separator = &self.config.kv_separator;
+        let delimiter = &self.config.kv_pair_delimiter;
+        let mut output = String::new();
+        for (i, (key, value)) in pairs.iter().enumerate() {
+            if i > 0 {
+                output.push_str(delimiter);
+            }
+            output.push_str(key);
+            output.push_str(separator);
+            write_logfmt_value(&mut output, value, separator, delimiter);
+        }
+        output.push_str(&self.file_separator);
+        output
+    }
+
+    /// Format a log record as a plain text file line.
+    fn format_file_text(&self, parts: &LineParts, stamp: LineStamp) -> String {
+        let wall_clock = self.config.timestamp_source == TimestampSource::WallClock;
+        let timestamp = match self.config.file_rfc3339 {
+            Some(precision) if wall_clock => self.now().to_rfc3339_precise(precision),
+            _ => {
+                let mut timestamp =
+                    self.timestamp(&self.file_time_format, self.file_counter.as_ref());
+                if self.config.timezone == TimeZoneMode::Utc
+                    && wall_clock
+                    && !shows_time_zone(&self.file_time_format)
+                {
+                    timestamp.push('Z');
+                }
+                timestamp
+            }
+        };
+        if let Some(template) = &self.file_template {
+            let mut output = self.render_template(
+                template,
+                parts,
+                &timestamp,
+                self.config.file_level_style,
+                false,
+                self.file_message_limit(),
+            );
+            output.push_str(&self.file_separator);
+            return output;
+        }
+        let timestamp = self.with_pid(timestamp, self.config.show_pid);
+        let timestamp = self.with_sequence(timestamp, stamp.sequence);
+        let timestamp = self.with_delta(timestamp, stamp.delta);
+
+        let message = self.render_message(parts, self.file_message_limit());
+        let mut target = if self.config.show_target {
+            format!(" {}", parts.target)
+        } else {
+            String::new()
+        };
+        if self.config.show_module_path {
+            target.push(' ');
+            target.push_str(
+                parts
+                    .module_path
+                    .or(parts.file)
+                    .unwrap_or(&self.config.missing_file_placeholder),
+            );
+        }
+
+        let mut prefix = format!(
+            "{} {}{}{}",
+            timestamp,
+            self.config.file_level_style.label(parts.level),
+            self.level_padding(parts.level, self.config.file_level_style),
+            target,
+        );
+        if let Some((file, line)) = self.location(parts) {
+            let _ = write!(prefix, " {file}:{line}");
+        }
+        self.enclose_prefix(&prefix, &message) + &self.file_separator
+    }
+
+    /// Format a log record as a single-line JSON object.
+    ///
+    /// The timestamp is written in RFC3339 format. Missing file or line
+    /// information is written as `null`.
+    fn format_file_json(&self, parts: &LineParts, stamp: LineStamp) -> String {
+        let timestamp = self.rfc3339_now();
+        let mut output = String::from("{");
+
+        write_json_field(&mut output, "timestamp", &timestamp);
+        output.push(',');
+        write_json_field(&mut output, "level", parts.level.as_str());
+        if let Some(scale) = self.config.numeric_levels {
+            let _ = write!(output, ",\"level_num\":{}", scale.code(parts.level));
+        }
+        if self.config.show_pid {
+            let _ = write!(output, ",\"pid\":{}", self.pid);
+        }
+        if let Some(sequence) = stamp.sequence {
+            let _ = write!(output, ",\"seq\":{sequence}");
+        }
+        output.push(',');
+        write_json_field(&mut output, "target", parts.target);
+        output.push_str(",\"file\":");
+        match parts.file {
+            Some(file) => write_json_string(&mut output, file),
+            None => output.push_str("null"),
+        }
+        output.push_str(",\"line\":");
+        match parts.line {
+            Some(line) => {
+                let _ = write!(output, "{line}");
+            }
+            None => output.push_str("null"),
+        }
+        output.push(',');
+        write_json_field(
+            &mut output,
+            "message",
+            &truncate_message(self.render_args(parts), self.file_message_limit()),
+        );
+
+        let mut fields = self.collect_fields(parts);
+        for (key, name) in [(TYPE_KEY, "type"), (FUNCTION_KEY, "function")] {
+            if let Some(index) = fields.iter().position(|(k, _)| k == key) {
+                let (_, value) = fields.remove(index);
+                output.push(',');
+                write_json_field(&mut output, name, value.as_str());
+            }
+        }
+        if !fields.is_empty() {
+            output.push_str(",\"fields\":{");
+            for (i, (key, value)) in fields.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_json_string(&mut output, key);
+                output.push(':');
+                value.write_json(&mut output);
+            }
+            output.push('}');
+        }
+        output.push('}');
+        output.push_str(&self.file_separator);
+
+        output
+    }
+}
+
+/// Check that a `chrono` format string only contains valid specifiers.
+///
+/// Without the `chrono` feature, only `%Y`, `%m`, `%d`, `%H`, `%M`, `%S`,
+/// `%F`, `%T`, `%.3f` and `%%` are supported.
+///
+/// # Returns
+///
+/// `Ok(())` if the format is valid, or [`LogError::InvalidTimeFormat`] otherwise.
+pub fn validate_time_format(format: &str) -> Result<(), LogError> {
+    if clock::is_valid_format(format) {
+        Ok(())
+    } else {
+        Err(LogError::InvalidTimeFormat(format.to_string()))
+    }
+}
+
+/// A piece of a parsed format template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    /// Text copied to the output verbatim
+    Literal(String),
+
+    /// `{time}`: the formatted timestamp
+    Time,
+
+    /// `{level}`: the record's level
+    Level,
+
+    /// `{file}`: the source file, or `unknown`
+    File,
+
+    /// `{line}`: the source line, or `0`
+    Line,
+
+    /// `{target}`: the record's target, usually its module path
+    Target,
+
+    /// `{thread}`: the name of the logging thread, or its id if unnamed
+    Thread,
+
+    /// `{message}`: the log message
+    Message,
+}
+
+/// Remove ANSI escape sequences from a string.
+///
+/// Control sequences (`ESC [ ... final byte`), such as colors and text styles
+/// like `\x1b[1;31m` and `\x1b[0m`, are removed, as are operating system
+/// commands (`ESC ] ... BEL` or `ESC ] ... ESC \`) like terminal hyperlinks,
+/// keeping the link text. All other text is left unchanged.
+///
+/// # Example
+///
+/// ```
+/// use fstdout_logger::formatter::strip_ansi;
+///
+/// assert_eq!(strip_ansi("\x1b[1;31mERROR\x1b[0m failed"), "ERROR failed");
+/// ```
+pub fn strip_ansi(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Control sequence: parameters and intermediates up to a final byte
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Operating system command: terminated by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.next_if_eq(&'\\').is_some() {
+                        break;
+                    }
+                }
+            }
+            // Other two-character escapes
+            Some(_) | None => {}
+        }
+    }
+    output
+}
+
+/// Check that a format template only contains supported tokens.
+///
+/// Supported tokens are `{time}`, `{level}`, `{file}`, `{line}`, `{target}`,
+/// `{thread}` and `{message}`. Literal braces are written as `{{` and `}}`.
+///
+/// # Returns
+///
+/// `Ok(())` if the template is valid, or [`LogError::InvalidTemplate`] otherwise.
+pub fn validate_template(template: &str) -> Result<(), LogError> {
+    parse_template(template).map(|_| ())
+}
+
+/// Parse a format template into its literal text and tokens.
+fn parse_template(template: &str) -> Result<Vec<TemplatePart>, LogError> {
+    let invalid = |reason: String| LogError::InvalidTemplate(format!("{reason} in `{template}`"));
+
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(invalid("unclosed `{`".to_string())),
+                    }
+                }
+                let part = match name.as_str() {
+                    "time" => TemplatePart::Time,
+                    "level" => TemplatePart::Level,
+                    "file" => TemplatePart::File,
+                    "line" => TemplatePart::Line,
+                    "target" => TemplatePart::Target,
+                    "thread" => TemplatePart::Thread,
+                    "message" => TemplatePart::Message,
+                    _ => return Err(invalid(format!("unknown token `{{{name}}}`"))),
+                };
+                if !literal.is_empty() {
+                    parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(part);
+            }
+            '}' => return Err(invalid("unmatched `}`".to_string())),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Literal(literal));
+    }
+    Ok(parts)
+}
+
+/// Build the icon prefix of each level's stdout label.
+///
+/// Each icon is followed by a space. With `align_levels`, icons are padded
+/// to the display width of the widest one, and levels without an icon get
+/// blanks of the same width.
+fn level_icons(config: &LoggerConfig) -> [String; 5] {
+    let Some(icons) = &config.level_icons else {
+        return Default::default();
+    };
+    let widest = icons.values().map(|icon| display_width(icon)).max();
+    let mut prefixes: [String; 5] = Default::default();
+    for level in Level::iter() {
+        let icon = icons.get(&level).map_or("", String::as_str);
+        let prefix = &mut prefixes[level as usize - 1];
+        if config.align_levels
+            && let Some(widest) = widest
+        {
+            *prefix = format!("{icon}{} ", " ".repeat(widest - display_width(icon)));
+        } else if !icon.is_empty() {
+            *prefix = format!("{icon} ");
+        }
+    }
+    prefixes
+}
+
+/// Estimate the number of terminal columns `text` takes up.
+///
+/// Emoji and East Asian wide characters count as two columns, as does a
+/// character followed by the emoji presentation selector (U+FE0F). Other
+/// selectors and joiners take no space. This is a rough approximation that
+/// matches common terminals for typical icons.
+fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut last = 0;
+    for c in text.chars() {
+        let columns = match c as u32 {
+            0xFE0F => {
+                // Emoji presentation widens the preceding character
+                width += 2usize.saturating_sub(last);
+                last = 2;
+                continue;
+            }
+            0x200B..=0x200D | 0xFE00..=0xFE0E | 0x0300..=0x036F => 0,
+            0x1100..=0x115F
+            | 0x231A..=0x231B
+            | 0x23E9..=0x23EC
+            | 0x23F0
+            | 0x23F3
+            | 0x2614..=0x2615
+            | 0x26A1
+            | 0x26D4
+            | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728
+            | 0x274C
+            | 0x274E
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2B50
+            | 0x2B55
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F000.. => 2,
+            _ => 1,
+        };
+        width += columns;
+        last = columns;
+    }
+    width
+}
+
+/// Pick the configured time format if it is valid, or the default otherwise.
+fn valid_time_format(format: Option<&str>, default: &str) -> String {
+    match format {
+        Some(format) if validate_time_format(format).is_ok() => format.to_string(),
+        _ => default.to_string(),
+    }
+}
+
+/// Check whether a time format writes the time zone itself, e.g. with `%z`,
+/// `%:z`, `%Z` or `%+`.
+fn shows_time_zone(format: &str) -> bool {
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        // Skip the flags and padding modifiers of a specifier
+        if c == '%'
+            && let Some('z' | 'Z' | '+') =
+                chars.find(|c| !matches!(c, ':' | '#' | '-' | '_' | '0'..='9'))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Environment variable that forces test mode detection on (`1`) or off (`0`).
+const TEST_MODE_ENV: &str = "FSTDOUT_TEST_MODE";
+
+/// Check whether the current process looks like a `cargo test` binary.
+fn running_under_test() -> bool {
+    detect_test_mode(
+        std::env::var(TEST_MODE_ENV).ok().as_deref(),
+        std::env::current_exe().ok().as_deref(),
+    )
+}
+
+/// Decide whether to treat the process as a test run.
+///
+/// An explicit `FSTDOUT_TEST_MODE` value wins. Otherwise the process is a
+/// test run if its executable is named like the test binaries cargo builds:
+/// `<name>-<16 hex digits>` in a `deps` directory. Build scripts and other
+/// binaries that merely live under a `deps` path don't match.
+fn detect_test_mode(env_value: Option<&str>, exe: Option<&Path>) -> bool {
+    match env_value.map(str::trim) {
+        Some("1") | Some("true") => return true,
+        Some("0") | Some("false") => return false,
+        _ => {}
+    }
+    let Some(exe) = exe else {
+        return false;
+    };
+    let in_deps = exe
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir == "deps");
+    let hashed = exe
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.rsplit_once('-'))
+        .is_some_and(|(name, hash)| {
+            !name.is_empty() && hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+        });
+    in_deps && hashed
+}
+
+/// Prefix of text lines in `FileFormat::Dual` output.
+const TEXT_MARKER: &str = "TXT: ";
+
+/// Prefix of JSON lines in `FileFormat::Dual` output.
+const JSON_MARKER: &str = "JSN: ";
+
+/// Replacement for the parts of messages matching a redaction pattern.
+#[cfg(feature = "regex")]
+const REDACTED: &str = "***REDACTED***";
+
+/// Replace every match of `patterns` in `message` with [`REDACTED`].
+#[cfg(feature = "regex")]
+fn redact(mut message: String, patterns: &[regex::Regex]) -> String {
+    for pattern in patterns {
+        if let std::borrow::Cow::Owned(redacted) =
+            pattern.replace_all(&message, regex::NoExpand(REDACTED))
+        {
+            message = redacted;
+        }
+    }
+    message
+}
+
+/// Suffix appended to messages cut off at the configured maximum length.
+const TRUNCATION_SUFFIX: &str = "…(truncated)";
+
+/// Key-value key whose value is promoted to the top-level `type` JSON field.
+const TYPE_KEY: &str = "_type";
+
+/// The parts of a record that make up a formatted line.
+///
+/// Every layout is rendered from these, so lines formatted from a [`Record`]
+/// and from [`LogFormatter::format_parts`] are identical.
+struct LineParts<'a> {
+    level: Level,
+    target: &'a str,
+    file: Option<&'a str>,
+    line: Option<u32>,
+    module_path: Option<&'a str>,
+    args: std::fmt::Arguments<'a>,
+    key_values: &'a dyn Source,
+}
+
+impl<'a> LineParts<'a> {
+    /// Take the parts of a record.
+    fn from_record(record: &'a Record<'a>) -> Self {
+        Self {
+            level: record.level(),
+            target: record.target(),
+            file: record.file(),
+            line: record.line(),
+            module_path: record.module_path(),
+            args: *record.args(),
+            key_values: record.key_values(),
+        }
+    }
+}
+
+/// The key-values of a line formatted without a record.
+const NO_KEY_VALUES: Option<(&str, &str)> = None;
+
+/// Key-value key set by the `*_fn!` macros to the calling function's name.
+const FUNCTION_KEY: &str = "_fn";
+
+/// Get the calling function's name attached by the `*_fn!` macros, if any.
+fn function_name(key_values: &dyn Source) -> Option<String> {
+    key_values
+        .get(Key::from_str(FUNCTION_KEY))
+        .map(|value| value.to_string())
+}
+
+/// A key-value value prepared for JSON output.
+enum FieldValue {
+    /// A value written as a JSON string
+    String(String),
+
+    /// A number, boolean or null, already rendered as a JSON literal
+    Literal(String),
+}
+
+impl FieldValue {
+    /// Convert a key-value value, keeping numbers, booleans and null native.
+    ///
+    /// Values captured with `Debug` or `Display` that read as a `Duration`
+    /// are re-rendered according to `durations`.
+    fn from_value(value: &Value, durations: DurationFormat) -> Self {
+        let mut visitor = LiteralVisitor::default();
+        let _ = value.visit(&mut visitor);
+        if let Some(literal) = visitor.literal {
+            return Self::Literal(literal);
+        }
+
+        let text = value.to_string();
+        if visitor.captured
+            && let Some(rendered) = format_duration(&text, durations)
+        {
+            return Self::String(rendered);
+        }
+        Self::String(text)
+    }
+
+    /// Get the value as text, without JSON quoting.
+    fn as_str(&self) -> &str {
+        match self {
+            Self::String(value) | Self::Literal(value) => value,
+        }
+    }
+
+    /// Append the value to a JSON document.
+    fn write_json(&self, output: &mut String) {
+        match self {
+            Self::String(value) => write_json_string(output, value),
+            Self::Literal(literal) => output.push_str(literal),
+        }
+    }
+}
+
+/// Value visitor rendering primitive values as JSON literals.
+///
+/// Anything that isn't a number, boolean or null is left as `None` so the
+/// caller falls back to its string representation. `captured` records
+/// whether the value was captured with `Debug` or `Display` rather than as
+/// a primitive.
+#[derive(Default)]
+struct LiteralVisitor {
+    literal: Option<String>,
+    captured: bool,
+}
+
+impl<'v> VisitValue<'v> for LiteralVisitor {
+    fn visit_any(&mut self, _value: Value) -> Result<(), kv::Error> {
+        self.captured = true;
+        Ok(())
+    }
+
+    fn visit_str(&mut self, _value: &str) -> Result<(), kv::Error> {
+        // Plain strings would otherwise end up in `visit_any`
+        Ok(())
+    }
+
+    fn visit_null(&mut self) -> Result<(), kv::Error> {
+        self.literal = Some("null".to_string());
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), kv::Error> {
+        self.literal = Some(value.to_string());
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<(), kv::Error> {
+        self.literal = Some(value.to_string());
+        Ok(())
+    }
+
+    fn visit_u128(&mut self, value: u128) -> Result<(), kv::Error> {
+        self.literal = Some(value.to_string());
+        Ok(())
+    }
+
+    fn visit_i128(&mut self, value: i128) -> Result<(), kv::Error> {
+        self.literal = Some(value.to_string());
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), kv::Error> {
+        // JSON has no representation for NaN or infinities
+        if value.is_finite() {
+            self.literal = Some(value.to_string());
+        }
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), kv::Error> {
+        self.literal = Some(value.to_string());
+        Ok(())
+    }
+}
+
+/// Visitor collecting the key-value pairs of a record.
+struct KeyValueCollector {
+    fields: Vec<(String, FieldValue)>,
+    durations: DurationFormat,
+}
+
+impl<'kvs> VisitSource<'kvs> for KeyValueCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.fields.push((
+            key.to_string(),
+            FieldValue::from_value(&value, self.durations),
+        ));
+        Ok(())
+    }
+}
+
+/// Collect the structured key-value pairs attached to a record.
+fn collect_key_values(
+    key_values: &dyn Source,
+    durations: DurationFormat,
+) -> Vec<(String, FieldValue)> {
+    let mut collector = KeyValueCollector {
+        fields: Vec::new(),
+        durations,
+    };
+    let _ = key_values.visit(&mut collector);
+    collector.fields
+}
+
+/// Make a value fit an RFC 5424 header field.
+///
+/// Header fields are limited to `max` printable ASCII characters without
+/// spaces; other characters are dropped. An empty field is written as `-`.
+#[cfg(feature = "syslog")]
+fn syslog_field(value: &str, max: usize) -> String {
+    let field: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// Cut a message down to at most `limit` bytes, marking it as truncated.
+///
+/// The message is cut at the last character boundary within the limit, so
+/// multi-byte characters are never split.
+fn truncate_message(mut message: String, limit: Option<usize>) -> String {
+    if let Some(limit) = limit
+        && message.len() > limit
+    {
+        let mut end = limit;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+        message.push_str(TRUNCATION_SUFFIX);
+    }
+    message
+}
+
+/// Re-render the `Debug` form of a `Duration` (`1.5s`, `250µs`, ...).
+///
+/// Returns `None` for `DurationFormat::Debug` and for text that isn't a
+/// duration, so the caller keeps the value as logged.
+fn format_duration(text: &str, format: DurationFormat) -> Option<String> {
+    let (scale, unit) = match format {
+        DurationFormat::Debug => return None,
+        DurationFormat::Seconds => (1e9, "s"),
+        DurationFormat::Millis => (1e6, "ms"),
+    };
+
+    // "ms" and "ns" end with "s", so check the longer suffixes first
+    let (number, nanos_per_unit) = [("ns", 1.0), ("µs", 1e3), ("ms", 1e6), ("s", 1e9)]
+        .into_iter()
+        .find_map(|(suffix, nanos)| text.strip_suffix(suffix).map(|number| (number, nanos)))?;
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let nanos = number.parse::<f64>().ok()? * nanos_per_unit;
+
+    let rendered = format!("{:.3}", nanos / scale);
+    let rendered = rendered.trim_end_matches('0').trim_end_matches('.');
+    Some(format!("{rendered}{unit}"))
+}
+
+/// Append a logfmt value, quoted if it could be confused with the surrounding syntax.
+///
+/// Values that are empty or contain whitespace, quotes, backslashes, or the
+/// configured separator or delimiter are wrapped in double quotes, with
+/// quotes, backslashes and line breaks escaped.
+fn write_logfmt_value(output: &mut String, value: &str, separator: &str, delimiter: &str) {
+    let needs_quotes = value.is_empty()
+        || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\')
+        || (!separator.is_empty() && value.contains(separator))
+        || (!delimiter.is_empty() && value.contains(delimiter));
+    if !needs_quotes {
+        output.push_str(value);
+        return;
+    }
+
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Append a `"key":"value"` pair with the value escaped as a JSON string.
+fn write_json_field(output: &mut String, key: &str, value: &str) {
+    write_json_string(output, key);
+    output.push(':');
+    write_json_string(output, value);
+}
+
+/// Append `value` as a quoted JSON string, escaping characters as required.
+pub(crate) fn write_json_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LineEnding, LoggerConfigBuilder, SecondsPrecision};
+    use std::sync::MutexGuard;
+
+    /// Held by the tests that make `colored` emit escape codes.
+    static COLOR_OVERRIDE: Mutex<()> = Mutex::new(());
+
+    /// Keeps `colored` emitting escape codes until dropped.
+    struct ForcedColors {
+        _guard: MutexGuard<'static, ()>,
+    }
+
+    impl Drop for ForcedColors {
+        fn drop(&mut self) {
+            colored::control::unset_override();
+        }
+    }
+
+    /// Make `colored` emit escape codes, which it doesn't when the output
+    /// isn't a terminal.
+    ///
+    /// The override is global to the test binary, so the tests forcing it
+    /// run one at a time and restore `colored`'s own detection afterwards.
+    fn force_colors() -> ForcedColors {
+        let guard = COLOR_OVERRIDE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        colored::control::set_override(true);
+        ForcedColors { _guard: guard }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_json_file_lines_are_valid_json() {
+        let config = LoggerConfig::builder()
+            .file_format(FileFormat::Json)
+            .build();
+        let formatter = LogFormatter::new(config);
+
+        let messages = [
+            "plain message",
+            "message with \"quotes\" and \\ backslash",
+            "multi\nline\tmessage\u{7}",
+        ];
+
+        for message in messages {
+            let line = formatter.format_file(
+                &Record::builder()
+                    .args(format_args!("{message}"))
+                    .level(Level::Warn)
+                    .target("myapp::db")
+                    .file(Some("src/db.rs"))
+                    .line(Some(42))
+                    .build(),
+            );
+
+            assert!(line.ends_with('\n'));
+            assert_eq!(line.matches('\n').count(), 1);
+
+            let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(value["level"], "WARN");
+            assert_eq!(value["target"], "myapp::db");
+            assert_eq!(value["file"], "src/db.rs");
+            assert_eq!(value["line"], 42);
+            assert_eq!(value["message"], message);
+            assert!(
+                chrono::DateTime::parse_from_rfc3339(value["timestamp"].as_str().unwrap()).is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn test_collapse_newlines() {
+        let config = LoggerConfig::builder()
+            .collapse_newlines(Some('⏎'))
+            .use_colors(false)
+            .build();
+        let formatter = LogFormatter::new(config);
+        let record = Record::builder()
+            .args(format_args!("first\nsecond\r\nthird"))
+            .level(Level::Info)
+            .build();
+
+        let file_line = formatter.format_file(&record);
+        assert!(file_line.ends_with("first⏎second⏎third\n"));
+        assert_eq!(file_line.matches('\n').count(), 1);
+
+        let stdout_line = formatter.format_stdout(&record);
+        assert!(stdout_line.ends_with("first⏎second⏎third"));
+        assert!(!stdout_line.contains('\n'));
+    }
+
+    #[test]
+    fn test_file_hyperlinks() {
+        let _colors = force_colors();
+        let record = Record::builder()
+            .args(format_args!("linked"))
+            .level(Level::Info)
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .detect_test_mode(false)
+                .file_hyperlinks(true)
+                .build(),
+        );
+        let line = formatter.format_stdout(&record);
+        assert!(line.contains("\x1b]8;;file://"));
+        assert!(line.contains("src/main.rs\x1b\\"));
+        assert!(line.contains("\x1b]8;;\x1b\\"));
+
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .detect_test_mode(false)
+                .file_hyperlinks(true)
+                .hyperlink_template(Some("vscode://file{path}:{line}".to_string()))
+                .build(),
+        );
+        assert!(
+            formatter
+                .format_stdout(&record)
+                .contains("\x1b]8;;vscode://file/")
+        );
+        assert!(
+            formatter
+                .format_stdout(&record)
+                .contains("src/main.rs:7\x1b\\")
+        );
+
+        // Never in files
+        assert!(!formatter.format_file(&record).contains("\x1b]8;;"));
+
+        // Nor for records without a source location
+        let record = Record::builder()
+            .args(format_args!("unlinked"))
+            .level(Level::Info)
+            .build();
+        let line = formatter.format_stdout(&record);
+        assert!(line.contains("unknown:0"), "{line}");
+        assert!(!line.contains("\x1b]8;;"), "{line}");
+    }
+
+    #[test]
+    fn test_auto_colors_follow_terminal() {
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .detect_test_mode(false)
+                .auto_colors()
+                .build(),
+        );
+        assert_eq!(formatter.stdout_colors, io::stdout().is_terminal());
+        assert_eq!(formatter.stderr_colors, io::stderr().is_terminal());
+
+        // An explicit setting after auto_colors() wins
+        let config = LoggerConfig::builder()
+            .detect_test_mode(false)
+            .auto_colors()
+            .use_colors(true)
+            .build();
+        let formatter = LogFormatter::new(config);
+        assert!(formatter.stdout_colors && formatter.stderr_colors);
+    }
+
+    #[test]
+    fn test_colors_follow_record_stream() {
+        let mut formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .error_stream(ErrorStream::Stderr)
+                .build(),
+        );
+        formatter.stdout_colors = false;
+        formatter.stderr_colors = true;
+
+        assert!(formatter.colors_enabled(Level::Error));
+        assert!(formatter.colors_enabled(Level::Warn));
+        assert!(!formatter.colors_enabled(Level::Info));
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("plain text"), "plain text");
+        assert_eq!(
+            strip_ansi("\x1b[1;31mERROR\x1b[0m disk full"),
+            "ERROR disk full"
+        );
+        // Nested styles and 256-color/true-color parameters
+        assert_eq!(
+            strip_ansi("\x1b[1m\x1b[38;5;208mbold \x1b[2;38;2;1;2;3mdim\x1b[22m\x1b[0m\x1b[0m end"),
+            "bold dim end"
+        );
+        // Hyperlinks keep their text, with either terminator
+        assert_eq!(
+            strip_ansi(
+                "\x1b]8;;file:///a.rs\x1b\\a.rs:3\x1b]8;;\x1b\\ and \x1b]8;;x\x07b\x1b]8;;\x07"
+            ),
+            "a.rs:3 and b"
+        );
+        assert_eq!(strip_ansi("ünïcode \x1b[32m✓\x1b[0m"), "ünïcode ✓");
+    }
+
+    #[test]
+    fn test_strip_colors_on_redirect() {
+        let _colors = force_colors();
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .detect_test_mode(false)
+            .use_colors(true)
+            .strip_colors_on_redirect(true)
+            .build();
+        let mut formatter = LogFormatter::new(config);
+        let record = Record::builder()
+            .args(format_args!("\x1b[1mteed\x1b[0m"))
+            .level(Level::Error)
+            .build();
+
+        formatter.stdout_stripped = false;
+        let colored = formatter.format_stdout(&record);
+        assert!(colored.contains("\x1b[1;31mERROR\x1b[0m"));
+
+        formatter.stdout_stripped = true;
+        let stripped = formatter.format_stdout(&record);
+        assert_eq!(stripped, strip_ansi(&colored));
+        assert!(stripped.ends_with(" ERROR] teed"));
+    }
+
+    #[test]
+    fn test_color_levels() {
+        let _colors = force_colors();
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .detect_test_mode(false)
+                .use_colors(true)
+                .color_levels(&[Level::Error])
+                .build(),
+        );
+        let record = |level| {
+            Record::builder()
+                .args(format_args!("message"))
+                .level(level)
+                .file(Some("src/main.rs"))
+                .line(Some(7))
+                .build()
+        };
+
+        let info = formatter.format_stdout(&record(Level::Info));
+        assert!(!info.contains('\x1b'));
+        let error = formatter.format_stdout(&record(Level::Error));
+        assert!(error.contains("\x1b[1;31mERROR\x1b[0m"));
+
+        // use_colors doesn't undo an earlier color_levels
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .detect_test_mode(false)
+                .color_levels(&[Level::Error])
+                .use_colors(true)
+                .build(),
+        );
+        let info = formatter.format_stdout(&record(Level::Info));
+        assert!(!info.contains('\x1b'));
+        let error = formatter.format_stdout(&record(Level::Error));
+        assert!(error.contains("\x1b[1;31mERROR\x1b[0m"));
+    }
+
+    #[test]
+    fn test_color_scheme() {
+        let config = LoggerConfig::builder()
+            .detect_test_mode(false)
+            .use_colors(true)
+            .color_scheme(
+                ColorScheme::default()
+                    .info(Color::Cyan)
+                    .trace(Color::TrueColor {
+                        r: 255,
+                        g: 135,
+                        b: 0,
+                    })
+                    .bold(Level::Error, false),
+            )
+            .build();
+        let formatter = LogFormatter::new(config);
+
+        assert_eq!(formatter.get_level_color(Level::Info), "INFO".cyan().bold());
+        assert_eq!(
+            formatter.get_level_color(Level::Trace),
+            "TRACE".truecolor(255, 135, 0)
+        );
+        assert_eq!(formatter.get_level_color(Level::Error), "ERROR".red());
+        // Untouched levels keep the default colors
+        assert_eq!(formatter.get_level_color(Level::Debug), "DEBUG".green());
+    }
+
+    #[test]
+    fn test_color_intensity() {
+        let _colors = force_colors();
+
+        let label = |intensity| {
+            let config = LoggerConfig::builder()
+                .detect_test_mode(false)
+                .use_colors(true)
+                .color_intensity(intensity)
+                .build();
+            LogFormatter::new(config)
+                .get_level_color(Level::Debug)
+                .to_string()
+        };
+
+        // Green is `32`, bold adds `1`, dimmed adds `2`
+        assert_eq!(label(ColorIntensity::Bold), "\x1b[1;32mDEBUG\x1b[0m");
+        assert_eq!(label(ColorIntensity::Normal), "\x1b[32mDEBUG\x1b[0m");
+        assert_eq!(label(ColorIntensity::Dim), "\x1b[2;32mDEBUG\x1b[0m");
+
+        // Normal also removes the bold of the default scheme
+        let config = LoggerConfig::builder()
+            .detect_test_mode(false)
+            .use_colors(true)
+            .color_intensity(ColorIntensity::Normal)
+            .build();
+        assert_eq!(
+            LogFormatter::new(config).get_level_color(Level::Error),
+            "ERROR".red()
+        );
+    }
+
+    #[test]
+    fn test_color_style_full_line() {
+        let _colors = force_colors();
+
+        let config = LoggerConfig::builder()
+            .detect_test_mode(false)
+            .use_colors(true)
+            .color_style(ColorStyle::FullLine)
+            .build();
+        let formatter = LogFormatter::new(config);
+        let args = format_args!("disk full");
+        let record = Record::builder()
+            .args(args)
+            .level(Level::Error)
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+
+        // A single bold red sequence wraps the line, with no inner resets
+        let output = formatter.format_stdout(&record);
+        assert!(output.starts_with("\x1b[1;31m["), "{output:?}");
+        assert!(
+            output.ends_with(" ERROR src/main.rs:7] disk full\x1b[0m"),
+            "{output:?}"
+        );
+        assert_eq!(output.matches('\x1b').count(), 2, "{output:?}");
+
+        // The default only colors the level label
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .detect_test_mode(false)
+                .use_colors(true)
+                .build(),
+        );
+        let output = formatter.format_stdout(&record);
+        assert!(output.contains("\x1b[1;31mERROR\x1b[0m"), "{output:?}");
+        assert!(output.ends_with("] disk full"), "{output:?}");
+    }
+
+    #[test]
+    fn test_colors_disabled_under_test() {
+        let _colors = force_colors();
+
+        // This test binary is target/<profile>/deps/fstdout_logger-<hash>,
+        // and the default colors weren't chosen explicitly
+        let formatter = LogFormatter::new(LoggerConfig::default());
+        assert!(!formatter.stdout_colors && !formatter.stderr_colors);
+
+        let record = Record::builder()
+            .args(format_args!("no colors"))
+            .level(Level::Error)
+            .build();
+        assert!(!formatter.format_stdout(&record).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_detect_test_mode() {
+        let test_exe = Path::new("/project/target/debug/deps/my_crate-0123456789abcdef");
+        let app_exe = Path::new("/project/target/debug/my_app");
+
+        assert!(detect_test_mode(None, Some(test_exe)));
+        assert!(detect_test_mode(
+            None,
+            Some(Path::new(
+                "/project/target/debug/deps/my_crate-0123456789abcdef.exe"
+            ))
+        ));
+        assert!(!detect_test_mode(None, Some(app_exe)));
+        assert!(!detect_test_mode(None, None));
+
+        // Other binaries under a deps directory aren't test runs
+        assert!(!detect_test_mode(None, Some(Path::new("/opt/deps/server"))));
+        assert!(!detect_test_mode(
+            None,
+            Some(Path::new("/project/target/debug/deps/my-tool"))
+        ));
+
+        // The environment variable overrides the heuristic both ways
+        assert!(detect_test_mode(Some("1"), Some(app_exe)));
+        assert!(detect_test_mode(Some("true"), None));
+        assert!(!detect_test_mode(Some("0"), Some(test_exe)));
+
+        // Explicit colors are kept, unless detection is enabled afterwards
+        let config = LoggerConfig::builder().use_colors(true).build();
+        assert!(!config.detect_test_mode);
+        let config = LoggerConfig::builder()
+            .use_colors(true)
+            .detect_test_mode(true)
+            .build();
+        assert!(config.detect_test_mode);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_custom_time_formats() {
+        let config = LoggerConfig::builder()
+            .use_colors(false)
+            .stdout_time_format(Some("%H:%M:%S%.3f".to_string()))
+            .file_time_format(Some("%d/%m/%Y".to_string()))
+            .build();
+        let formatter = LogFormatter::new(config);
+        let record = Record::builder()
+            .args(format_args!("timed"))
+            .level(Level::Info)
+            .build();
+
+        // [HH:MM:SS.mmm INFO ...]
+        let stdout_line = formatter.format_stdout(&record);
+        let time = stdout_line[1..].split(' ').next().unwrap();
+        assert_eq!(time.len(), 12);
+        assert_eq!(&time[8..9], ".");
+
+        // [DD/MM/YYYY INFO ...]
+        let file_line = formatter.format_file(&record);
+        let date = file_line[1..].split(' ').next().unwrap();
+        assert!(chrono::NaiveDate::parse_from_str(date, "%d/%m/%Y").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_time_format() {
+        assert!(validate_time_format("%H:%M:%S%.3f").is_ok());
+        assert!(matches!(
+            validate_time_format("%H:%Q"),
+            Err(LogError::InvalidTimeFormat(_))
+        ));
+
+        // The formatter falls back to the default instead of panicking
+        let config = LoggerConfig::builder()
+            .stdout_time_format(Some("%Q".to_string()))
+            .build();
+        let formatter = LogFormatter::new(config);
+        let record = Record::builder().args(format_args!("x")).build();
+        assert!(!formatter.format_stdout(&record).contains("%Q"));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_utc_timestamps() {
+        let record = Record::builder()
+            .args(format_args!("when"))
+            .level(Level::Info)
+            .build();
+        let local = LogFormatter::new(LoggerConfig::default());
+        let utc = LogFormatter::new(LoggerConfig::builder().timezone(TimeZoneMode::Utc).build());
+
+        let local_line = local.format_file(&record);
+        let utc_line = utc.format_file(&record);
+
+        // [YYYY-MM-DD HH:MM:SS INFO ...] vs [YYYY-MM-DD HH:MM:SSZ INFO ...]
+        assert_eq!(&utc_line[20..21], "Z");
+        let parse = |line: &str| {
+            chrono::NaiveDateTime::parse_from_str(&line[1..20], "%Y-%m-%d %H:%M:%S").unwrap()
+        };
+        let difference = (parse(&local_line) - parse(&utc_line)).num_seconds();
+        let offset = i64::from(chrono::Local::now().offset().local_minus_utc());
+        assert!((difference - offset).abs() <= 1);
+
+        let json = LogFormatter::new(
+            LoggerConfig::builder()
+                .timezone(TimeZoneMode::Utc)
+                .file_format(FileFormat::Json)
+                .build(),
+        );
+        let value: serde_json::Value = serde_json::from_str(&json.format_file(&record)).unwrap();
+        assert!(value["timestamp"].as_str().unwrap().ends_with('Z'));
+
+        // A format that writes the offset itself doesn't get another suffix
+        let offset = LogFormatter::new(
+            LoggerConfig::builder()
+                .timezone(TimeZoneMode::Utc)
+                .file_time_format(Some("%Y-%m-%d %H:%M:%S%:z".to_string()))
+                .build(),
+        );
+        assert_eq!(&offset.format_file(&record)[20..27], "+00:00 ");
+    }
+
+    #[test]
+    fn test_shows_time_zone() {
+        assert!(shows_time_zone("%H:%M:%S%z"));
+        assert!(shows_time_zone("%H:%M:%S%:z"));
+        assert!(shows_time_zone("%H:%M:%S %Z"));
+        assert!(shows_time_zone("%+"));
+        assert!(!shows_time_zone("%Y-%m-%d %H:%M:%S"));
+        assert!(!shows_time_zone("%H:%M:%S%%z"));
+    }
+
+    #[test]
+    fn test_elapsed_timestamps() {
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .timezone(TimeZoneMode::Utc)
+            .timestamp_source(TimestampSource::SinceStart)
+            .build();
+        let formatter = LogFormatter::new(config);
+        let record = Record::builder()
+            .args(format_args!("tick"))
+            .level(Level::Info)
+            .build();
+        let elapsed = |line: &str| -> f64 {
+            let end = line.find("s ").unwrap();
+            line[2..end].parse().unwrap()
+        };
+
+        let first = formatter.format_stdout(&record);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let second = formatter.format_stdout(&record);
+        let file_line = formatter.format_file(&record);
+
+        assert!(first.starts_with("[+0.0"));
+        assert!(second.ends_with(" INFO] tick"));
+        assert!(elapsed(&second) >= elapsed(&first) + 0.02);
+        // No time zone suffix on elapsed times
+        assert!(file_line.starts_with("[+") && !file_line.contains("sZ"));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_logfmt_separators() {
+        let key_values: [(&str, Value); 3] = [
+            ("user", Value::from("bob")),
+            ("attempts", Value::from(3)),
+            ("note", Value::from("a:b")),
+        ];
+        let record = Record::builder()
+            .args(format_args!("login ok"))
+            .level(Level::Warn)
+            .target("auth")
+            .file(Some("src/auth.rs"))
+            .line(Some(12))
+            .key_values(&key_values)
+            .build();
+
+        let logfmt = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Logfmt)
+                .build(),
+        )
+        .format_file(&record);
+        let (time, rest) = logfmt.split_once(' ').unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(time.strip_prefix("time=").unwrap()).is_ok());
+        assert_eq!(
+            rest,
+            "level=WARN target=auth file=src/auth.rs line=12 msg=\"login ok\" user=bob attempts=3 note=a:b\n"
+        );
+
+        let custom = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Logfmt)
+                .kv_separator(":")
+                .kv_pair_delimiter(";")
+                .build(),
+        )
+        .format_file(&record);
+        let (_, rest) = custom.split_once(';').unwrap();
+        assert_eq!(
+            rest,
+            "level:WARN;target:auth;file:src/auth.rs;line:12;msg:\"login ok\";user:bob;attempts:3;note:\"a:b\"\n"
+        );
+    }
+
+    #[test]
+    fn test_logfmt_quoting() {
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Logfmt)
+                .build(),
+        );
+        let format = |args: std::fmt::Arguments| {
+            formatter.format_file(&Record::builder().args(args).level(Level::Info).build())
+        };
+
+        assert!(format(format_args!("ready")).ends_with(" msg=ready\n"));
+        assert!(format(format_args!("cache warmed up")).ends_with(" msg=\"cache warmed up\"\n"));
+        assert!(format(format_args!("ratio=0.5")).ends_with(" msg=\"ratio=0.5\"\n"));
+        assert!(format(format_args!("say \"hi\"")).ends_with(" msg=\"say \\\"hi\\\"\"\n"));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let record = Record::builder()
+            .args(format_args!("saved"))
+            .level(Level::Info)
+            .build();
+
+        for file_format in [
+            FileFormat::Text,
+            FileFormat::Json,
+            FileFormat::Logfmt,
+            FileFormat::Dual,
+        ] {
+            let formatter = LogFormatter::new(
+                LoggerConfig::builder()
+                    .use_colors(false)
+                    .file_format(file_format)
+                    .line_ending(LineEnding::CrLf)
+                    .build(),
+            );
+            let line = formatter.format_file(&record);
+            assert!(line.ends_with("\r\n"), "{file_format:?}: {line:?}");
+            assert_eq!(line.matches('\n').count(), line.matches("\r\n").count());
+        }
+
+        // Separators without a newline are kept as they are
+        let config = LoggerConfig::builder()
+            .record_separator("\0")
+            .line_ending(LineEnding::CrLf)
+            .build();
+        assert!(
+            LogFormatter::new(config)
+                .format_file(&record)
+                .ends_with("] saved\0")
+        );
+    }
+
+    #[test]
+    fn test_duration_format() {
+        let elapsed = std::time::Duration::from_millis(1500);
+        let key_values: [(&str, Value); 2] = [
+            ("elapsed", Value::from_debug(&elapsed)),
+            ("name", Value::from("1s")),
+        ];
+        let record = Record::builder()
+            .args(format_args!("done"))
+            .level(Level::Info)
+            .key_values(&key_values)
+            .build();
+        let format = |durations| {
+            LogFormatter::new(
+                LoggerConfig::builder()
+                    .file_format(FileFormat::Logfmt)
+                    .duration_format(durations)
+                    .build(),
+            )
+            .format_file(&record)
+        };
+
+        assert!(format(DurationFormat::Debug).ends_with(" elapsed=1.5s name=1s\n"));
+        assert!(format(DurationFormat::Seconds).ends_with(" elapsed=1.5s name=1s\n"));
+        // Plain strings that merely look like durations are left alone
+        assert!(format(DurationFormat::Millis).ends_with(" elapsed=1500ms name=1s\n"));
+
+        assert_eq!(
+            format_duration("250µs", DurationFormat::Millis).as_deref(),
+            Some("0.25ms")
+        );
+        assert_eq!(
+            format_duration("1.000000001s", DurationFormat::Seconds).as_deref(),
+            Some("1s")
+        );
+        assert_eq!(format_duration("soon", DurationFormat::Seconds), None);
+
+        let json = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Json)
+                .duration_format(DurationFormat::Millis)
+                .build(),
+        )
+        .format_file(&record);
+        let parsed: serde_json::Value = serde_json::from_str(json.trim_end()).unwrap();
+        assert_eq!(parsed["fields"]["elapsed"], "1500ms");
+    }
+
+    #[test]
+    fn test_align_levels() {
+        let _colors = force_colors();
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .use_colors(true)
+                .detect_test_mode(false)
+                .align_levels(true)
+                .build(),
+        );
+        let record = |level| {
+            Record::builder()
+                .args(format_args!("message"))
+                .level(level)
+                .file(Some("src/main.rs"))
+                .line(Some(7))
+                .build()
+        };
+        let column = |line: String| strip_ansi(&line).find("message").unwrap();
+
+        let info = record(Level::Info);
+        let error = record(Level::Error);
+        assert_eq!(
+            column(formatter.format_stdout(&info)),
+            column(formatter.format_stdout(&error))
+        );
+        assert_eq!(
+            column(formatter.format_file(&info)),
+            column(formatter.format_file(&error))
+        );
+        // The padding follows the escape sequences instead of being inside them
+        assert!(formatter.format_stdout(&info).contains("INFO\u{1b}[0m  "));
+    }
+
+    #[test]
+    fn test_level_style() {
+        let _colors = force_colors();
+
+        let args = format_args!("message");
+        let record = |level| {
+            Record::builder()
+                .args(args)
+                .level(level)
+                .file(Some("src/main.rs"))
+                .line(Some(7))
+                .build()
+        };
+        // The label sits between the timestamp and the file info
+        let label = |line: String| {
+            let line = strip_ansi(&line);
+            let end = line.find("src/main.rs").unwrap() - 1;
+            let start = line[..end].trim_end().rfind(' ').unwrap() + 1;
+            line[start..end].to_string()
+        };
+
+        for (style, error, info) in [
+            (LevelStyle::Full, "ERROR", "INFO "),
+            (LevelStyle::Short, "ERR", "INF"),
+            (LevelStyle::Single, "E", "I"),
+        ] {
+            let formatter = LogFormatter::new(
+                LoggerConfig::builder()
+                    .use_colors(true)
+                    .detect_test_mode(false)
+                    .align_levels(true)
+                    .level_style(style)
+                    .file_level_style(style)
+                    .build(),
+            );
+            assert_eq!(label(formatter.format_stdout(&record(Level::Error))), error);
+            assert_eq!(label(formatter.format_stdout(&record(Level::Info))), info);
+            assert_eq!(label(formatter.format_file(&record(Level::Error))), error);
+            assert_eq!(label(formatter.format_file(&record(Level::Info))), info);
+            assert_eq!(error.len(), style.width());
+        }
+
+        // Shortened labels are still colored
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .use_colors(true)
+                .detect_test_mode(false)
+                .level_style(LevelStyle::Single)
+                .build(),
+        );
+        assert!(
+            formatter
+                .format_stdout(&record(Level::Error))
+                .contains("\x1b[1;31mE\x1b[0m")
+        );
+        // The file can keep full names while stdout is shortened
+        assert_eq!(label(formatter.format_file(&record(Level::Error))), "ERROR");
+    }
+
+    #[test]
+    fn test_missing_file_info() {
+        let located = Record::builder()
+            .args(format_args!("located"))
+            .level(Level::Info)
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+        let unlocated = Record::builder()
+            .args(format_args!("unlocated"))
+            .level(Level::Info)
+            .build();
+        let formatter = |builder: LoggerConfigBuilder| {
+            LogFormatter::new(builder.use_colors(false).show_file_info(true).build())
+        };
+
+        let default = formatter(LoggerConfig::builder());
+        assert!(
+            default
+                .format_stdout(&unlocated)
+                .ends_with(" INFO unknown:0] unlocated")
+        );
+        assert!(
+            default
+                .format_file(&unlocated)
+                .ends_with(" INFO unknown:0] unlocated\n")
+        );
+
+        let placeholders = formatter(
+            LoggerConfig::builder()
+                .missing_file_placeholder("-")
+                .missing_line_placeholder("-")
+                .file_template(Some("{file}:{line} {message}".to_string())),
+        );
+        assert!(
+            placeholders
+                .format_stdout(&unlocated)
+                .ends_with(" INFO -:-] unlocated")
+        );
+        assert_eq!(placeholders.format_file(&unlocated), "-:- unlocated\n");
+        assert_eq!(
+            placeholders.format_file(&located),
+            "src/main.rs:7 located\n"
+        );
+
+        let skipping = formatter(LoggerConfig::builder().skip_file_info_when_missing(true));
+        assert!(
+            skipping
+                .format_stdout(&unlocated)
+                .ends_with(" INFO] unlocated")
+        );
+        assert!(
+            skipping
+                .format_file(&unlocated)
+                .ends_with(" INFO] unlocated\n")
+        );
+        // Records with a location keep it
+        assert!(
+            skipping
+                .format_stdout(&located)
+                .ends_with(" INFO src/main.rs:7] located")
+        );
+        assert!(
+            skipping
+                .format_file(&located)
+                .ends_with(" INFO src/main.rs:7] located\n")
+        );
+    }
+
+    #[test]
+    fn test_systemd_stdout() {
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .use_colors(true)
+                .detect_test_mode(false)
+                .systemd_stdout(true)
+                .build(),
+        );
+        for (level, prefix) in [
+            (Level::Error, b"<3>"),
+            (Level::Warn, b"<4>"),
+            (Level::Info, b"<6>"),
+            (Level::Debug, b"<7>"),
+            (Level::Trace, b"<7>"),
+        ] {
+            let record = Record::builder()
+                .args(format_args!("message"))
+                .level(level)
+                .build();
+            let line = formatter.format_stdout(&record);
+            assert_eq!(&line.as_bytes()[..3], prefix);
+            assert_eq!(line.as_bytes()[3], b'[');
+            // Colors are off, as the journal doesn't keep them
+            assert!(!line.contains('\x1b'));
+            // File lines have no prefix
+            assert!(formatter.format_file(&record).starts_with('['));
+        }
+    }
+
+    #[cfg(feature = "syslog")]
+    #[test]
+    fn test_syslog_format() {
+        let formatter = LogFormatter::new(LoggerConfig::default());
+        let target = SyslogTarget {
+            facility: crate::config::SyslogFacility::Local(0),
+            app_name: "my app".to_string(),
+            transport: crate::config::SyslogTransport::Udp(([127, 0, 0, 1], 514).into()),
+        };
+        let message = |level| {
+            let args = format_args!("disk full");
+            let record = Record::builder().args(args).level(level).build();
+            formatter.format_syslog(&record, &target, "host")
+        };
+
+        // local0 (16) * 8 + severity
+        let error = message(Level::Error);
+        assert!(error.starts_with("<131>1 "));
+        assert!(error.ends_with(&format!(" host myapp {} - - disk full", std::process::id())));
+        assert!(message(Level::Warn).starts_with("<132>1 "));
+        assert!(message(Level::Info).starts_with("<134>1 "));
+        assert!(message(Level::Trace).starts_with("<135>1 "));
+    }
+
+    #[test]
+    fn test_level_icons() {
+        let record = |level| {
+            Record::builder()
+                .args(format_args!("message"))
+                .level(level)
+                .file(Some("src/main.rs"))
+                .line(Some(7))
+                .build()
+        };
+        let config = LoggerConfig {
+            use_colors: false,
+            ..LoggerConfig::with_default_icons()
+        };
+        let formatter = LogFormatter::new(config.clone());
+        let error = record(Level::Error);
+        assert!(
+            formatter
+                .format_stdout(&error)
+                .contains(" ❌ ERROR src/main.rs:7]")
+        );
+        assert!(!formatter.format_file(&error).contains('❌'));
+
+        // Aligned labels start the message in the same terminal column
+        let mut icons = config.level_icons.clone().unwrap();
+        icons.remove(&Level::Debug);
+        let formatter = LogFormatter::new(LoggerConfig {
+            align_levels: true,
+            level_icons: Some(icons),
+            ..config
+        });
+        let column = |level| {
+            let line = formatter.format_stdout(&record(level));
+            display_width(&line[..line.find("message").unwrap()])
+        };
+        for level in [Level::Warn, Level::Info, Level::Debug, Level::Trace] {
+            assert_eq!(column(level), column(Level::Error));
+        }
+
+        assert_eq!(display_width("ERROR"), 5);
+        assert_eq!(display_width("❌"), 2);
+        assert_eq!(display_width("⚠️"), 2);
+        assert_eq!(display_width("🔍"), 2);
+    }
+
+    #[test]
+    fn test_show_pid() {
+        let record = Record::builder()
+            .args(format_args!("message"))
+            .level(Level::Info)
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+        let pid = format!(" pid:{} INFO src/main.rs:7]", std::process::id());
+
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .use_colors(false)
+                .show_pid(true)
+                .build(),
+        );
+        assert!(formatter.format_file(&record).contains(&pid));
+        assert!(!formatter.format_stdout(&record).contains("pid:"));
+
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .use_colors(false)
+                .show_pid_in_stdout(true)
+                .build(),
+        );
+        assert!(formatter.format_stdout(&record).contains(&pid));
+        assert!(!formatter.format_file(&record).contains("pid:"));
+
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .show_pid(true)
+                .file_format(FileFormat::Json)
+                .build(),
+        );
+        let value: serde_json::Value =
+            serde_json::from_str(formatter.format_file(&record).trim_end()).unwrap();
+        assert_eq!(value["pid"], std::process::id());
+    }
+
+    #[test]
+    fn test_sequence_fields() {
+        let record = Record::builder()
+            .args(format_args!("message"))
+            .level(Level::Info)
+            .build();
+        let config = |format| {
+            LoggerConfig::builder()
+                .show_sequence(true)
+                .file_format(format)
+                .build()
+        };
+
+        let stamp = LineStamp {
+            sequence: Some(42),
+            ..LineStamp::default()
+        };
+
+        let formatter = LogFormatter::new(config(FileFormat::Json));
+        let value: serde_json::Value =
+            serde_json::from_str(formatter.format_file_stamped(&record, stamp).trim_end()).unwrap();
+        assert_eq!(value["seq"], 42);
+
+        let formatter = LogFormatter::new(config(FileFormat::Logfmt));
+        assert!(
+            formatter
+                .format_file_stamped(&record, stamp)
+                .contains(" seq=42 ")
+        );
+        // Without a number from the logger, there is no field
+        assert!(!formatter.format_file(&record).contains("seq="));
+    }
+
+    /// Clock stopped at 2024-02-29 23:59:58.042 UTC.
+    struct FixedClock;
+
+    impl Clock for FixedClock {
+        fn now(&self) -> std::time::SystemTime {
+            std::time::UNIX_EPOCH + std::time::Duration::from_millis(1_709_251_198_042)
+        }
+    }
+
+    #[test]
+    fn test_fixed_clock() {
+        let args = format_args!("message");
+        let record = Record::builder()
+            .args(args)
+            .level(Level::Info)
+            .target("app")
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+        let formatter = |file_format| {
+            LogFormatter::with_clock(
+                LoggerConfig::builder()
+                    .use_colors(false)
+                    .timezone(TimeZoneMode::Utc)
+                    .file_format(file_format)
+                    .build(),
+                FixedClock,
+            )
+        };
+
+        let text = formatter(FileFormat::Text);
+        assert_eq!(
+            text.format_stdout(&record),
+            "[23:59:58 INFO src/main.rs:7] message"
+        );
+        assert_eq!(
+            text.format_file(&record),
+            "[2024-02-29 23:59:58Z INFO src/main.rs:7] message\n"
+        );
+        assert_eq!(
+            formatter(FileFormat::Json).format_file(&record),
+            "{\"timestamp\":\"2024-02-29T23:59:58.042Z\",\"level\":\"INFO\",\"target\":\"app\",\
+             \"file\":\"src/main.rs\",\"line\":7,\"message\":\"message\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_file_rfc3339() {
+        let record = Record::builder()
+            .args(format_args!("message"))
+            .level(Level::Info)
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+        let formatter = |precision, timezone, file_format| {
+            LogFormatter::with_clock(
+                LoggerConfig::builder()
+                    .timezone(timezone)
+                    .file_format(file_format)
+                    .file_rfc3339(Some(precision))
+                    .build(),
+                FixedClock,
+            )
+        };
+
+        for (precision, expected) in [
+            (SecondsPrecision::Seconds, "2024-02-29T23:59:58Z"),
+            (SecondsPrecision::Millis, "2024-02-29T23:59:58.042Z"),
+            (SecondsPrecision::Micros, "2024-02-29T23:59:58.042000Z"),
+            (SecondsPrecision::Nanos, "2024-02-29T23:59:58.042000000Z"),
+        ] {
+            let text = formatter(precision, TimeZoneMode::Utc, FileFormat::Text);
+            assert_eq!(
+                text.format_file(&record),
+                format!("[{expected} INFO src/main.rs:7] message\n")
+            );
+            let json = formatter(precision, TimeZoneMode::Utc, FileFormat::Json);
+            assert!(
+                json.format_file(&record)
+                    .starts_with(&format!("{{\"timestamp\":\"{expected}\","))
+            );
+        }
+
+        // Local times carry their offset and parse back to the same instant
+        #[cfg(feature = "chrono")]
+        {
+            let text = formatter(
+                SecondsPrecision::Millis,
+                TimeZoneMode::Local,
+                FileFormat::Text,
+            );
+            let line = text.format_file(&record);
+            let timestamp = &line[1..line.find(' ').unwrap()];
+            let parsed = chrono::DateTime::parse_from_rfc3339(timestamp).unwrap();
+            assert_eq!(parsed.timestamp_millis(), 1_709_251_198_042);
+        }
+    }
+
+    #[test]
+    fn test_format_parts() {
+        let formatter = LogFormatter::with_clock(
+            LoggerConfig::builder()
+                .use_colors(false)
+                .show_target(true)
+                .build(),
+            FixedClock,
+        );
+        let args = format_args!("disk {}% full", 90);
+        let record = Record::builder()
+            .args(args)
+            .level(Level::Warn)
+            .target("app::disk")
+            .file(Some("src/disk.rs"))
+            .line(Some(3))
+            .build();
+
+        let parts = |is_stdout| {
+            formatter.format_parts(
+                Level::Warn,
+                "app::disk",
+                Some("src/disk.rs"),
+                Some(3),
+                format_args!("disk {}% full", 90),
+                is_stdout,
+            )
+        };
+        assert_eq!(parts(true), formatter.format_stdout(&record));
+        assert_eq!(parts(false), formatter.format_file(&record));
+        assert!(parts(true).ends_with(" WARN app::disk src/disk.rs:3] disk 90% full"));
+    }
+
+    #[test]
+    fn test_prefix_delimiters() {
+        let args = format_args!("message");
+        let record = Record::builder()
+            .args(args)
+            .level(Level::Info)
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+        let formatter = |use_colors| {
+            LogFormatter::with_clock(
+                LoggerConfig::builder()
+                    .use_colors(use_colors)
+                    .detect_test_mode(false)
+                    .timezone(TimeZoneMode::Utc)
+                    .prefix_delimiters("<", ">")
+                    .prefix_message_separator(" | ")
+                    .build(),
+                FixedClock,
+            )
+        };
+
+        assert_eq!(
+            formatter(false).format_stdout(&record),
+            "<23:59:58 INFO src/main.rs:7> | message"
+        );
+        assert_eq!(
+            formatter(false).format_file(&record),
+            "<2024-02-29 23:59:58Z INFO src/main.rs:7> | message\n"
+        );
+        // The delimiters stay outside the colors
+        let _colors = force_colors();
+        assert_eq!(
+            strip_ansi(&formatter(true).format_stdout(&record)),
+            "<23:59:58 INFO src/main.rs:7> | message"
+        );
+    }
+
+    #[test]
+    fn test_show_target() {
+        let record = Record::builder()
+            .args(format_args!("connected"))
+            .level(Level::Info)
+            .target("myapp::db")
+            .file(Some("src/db.rs"))
+            .line(Some(42))
+            .build();
+        let formatter = |show_file_info| {
+            LogFormatter::new(
+                LoggerConfig::builder()
+                    .use_colors(false)
+                    .show_target(true)
+                    .show_file_info(show_file_info)
+                    .build(),
+            )
+        };
+
+        // The target comes before the file info
+        let formatter_with_file = formatter(true);
+        assert!(
+            formatter_with_file
+                .format_stdout(&record)
+                .ends_with(" INFO myapp::db src/db.rs:42] connected")
+        );
+        assert!(
+            formatter_with_file
+                .format_file(&record)
+                .ends_with(" INFO myapp::db src/db.rs:42] connected\n")
+        );
+        assert!(
+            formatter(false)
+                .format_stdout(&record)
+                .ends_with(" INFO myapp::db] connected")
+        );
+
+        // Off by default
+        let formatter = LogFormatter::new(LoggerConfig::builder().use_colors(false).build());
+        assert!(!formatter.format_stdout(&record).contains("myapp::db"));
+        assert!(!formatter.format_file(&record).contains("myapp::db"));
+    }
+
+    #[test]
+    fn test_show_module_path() {
+        let formatter = |show_module_path| {
+            LogFormatter::new(
+                LoggerConfig::builder()
+                    .show_target(true)
+                    .show_module_path(show_module_path)
+                    .build(),
+            )
+        };
+        let record = Record::builder()
+            .args(format_args!("connected"))
+            .level(Level::Info)
+            .target("db")
+            .module_path(Some("myapp::db::pool"))
+            .file(Some("src/db/pool.rs"))
+            .line(Some(42))
+            .build();
+
+        // The module path comes after the target, the file info still follows
+        assert!(
+            formatter(true)
+                .format_file(&record)
+                .ends_with(" INFO db myapp::db::pool src/db/pool.rs:42] connected\n")
+        );
+        assert!(
+            formatter(false)
+                .format_file(&record)
+                .ends_with(" INFO db src/db/pool.rs:42] connected\n")
+        );
+
+        // Without a module path, the file path is shown instead
+        let record = Record::builder()
+            .args(format_args!("connected"))
+            .level(Level::Info)
+            .target("db")
+            .build();
+        assert!(
+            formatter(true)
+                .format_file(&record)
+                .ends_with(" INFO db unknown unknown:0] connected\n")
+        );
+    }
+
+    #[test]
+    fn test_global_fields() {
+        let formatter = |file_format| {
+            LogFormatter::new(
+                LoggerConfig::builder()
+                    .use_colors(false)
+                    .show_file_info(false)
+                    .file_format(file_format)
+                    .add_global_field("service", "checkout")
+                    .add_global_field("env", "prod eu")
+                    .build(),
+            )
+        };
+        let key_values = [("env", "staging")];
+        let plain = Record::builder()
+            .args(format_args!("paid"))
+            .level(Level::Info)
+            .build();
+        let with_env = Record::builder()
+            .args(format_args!("paid"))
+            .level(Level::Info)
+            .key_values(&key_values)
+            .build();
+
+        // Text output appends them to the message, quoted where needed
+        let text = formatter(FileFormat::Text);
+        assert!(
+            text.format_stdout(&plain)
+                .ends_with(" INFO] paid service=checkout env=\"prod eu\"")
+        );
+        assert!(
+            text.format_file(&plain)
+                .ends_with("] paid service=checkout env=\"prod eu\"\n")
+        );
+
+        // Structured output merges them with the record's key-values,
+        // which take precedence
+        let json = formatter(FileFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json.format_file(&with_env)).unwrap();
+        assert_eq!(value["message"], "paid");
+        assert_eq!(
+            value["fields"],
+            serde_json::json!({"env": "staging", "service": "checkout"})
+        );
+        let logfmt = formatter(FileFormat::Logfmt);
+        assert!(
+            logfmt
+                .format_file(&with_env)
+                .ends_with(" msg=paid env=staging service=checkout\n")
+        );
+    }
+
+    #[test]
+    fn test_global_fields_count_towards_limit() {
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .use_colors(false)
+                .show_file_info(false)
+                .add_global_field("service", "checkout")
+                .max_message_len(Some(25))
+                .build(),
+        );
+        let record = Record::builder()
+            .args(format_args!("payment accepted"))
+            .level(Level::Info)
+            .build();
+
+        let line = formatter.format_stdout(&record);
+        let message = line.split_once("] ").unwrap().1;
+        assert_eq!(message, "payment …(truncated) service=checkout");
+    }
+
+    #[test]
+    fn test_compact_preset() {
+        let formatter = LogFormatter::with_clock(LoggerConfig::compact(), FixedClock);
+        let record = Record::builder()
+            .args(format_args!("backup done"))
+            .level(Level::Info)
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+
+        let line = formatter.format_stdout(&record);
+        assert_eq!(strip_ansi(&line), line);
+        assert!(line.starts_with('[') && line.ends_with(" INF] backup done"));
+        assert_eq!(line.len(), "[hh:mm:ss INF] backup done".len());
+    }
+
+    #[test]
+    fn test_max_message_len() {
+        // 'é' takes two bytes, so a limit of 5 falls inside the third one
+        let message = "é".repeat(1000);
+        let args = format_args!("{message}");
+        let record = Record::builder().args(args).level(Level::Info).build();
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .show_file_info(false)
+                .use_colors(false)
+                .max_message_len(Some(5))
+                .max_file_message_len(Some(8))
+                .build(),
+        );
+
+        let stdout = formatter.format_stdout(&record);
+        assert!(stdout.ends_with("] éé…(truncated)"));
+        let file = formatter.format_file(&record);
+        assert!(file.ends_with("] éééé…(truncated)\n"));
+
+        // Messages within the limit are left alone
+        let short = Record::builder()
+            .args(format_args!("éé"))
+            .level(Level::Info)
+            .build();
+        assert!(formatter.format_stdout(&short).ends_with("] éé"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_redactions() {
+        let record = Record::builder()
+            .args(format_args!("request token=abc123 user=bob"))
+            .level(Level::Info)
+            .build();
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .show_file_info(false)
+                .use_colors(false)
+                .redact(regex::Regex::new(r"token=\w+").unwrap())
+                .build(),
+        );
+
+        let stdout = formatter.format_stdout(&record);
+        assert!(stdout.ends_with("] request ***REDACTED*** user=bob"));
+        let file = formatter.format_file(&record);
+        assert!(file.ends_with("] request ***REDACTED*** user=bob\n"));
+        assert!(!file.contains("abc123"));
+    }
+
+    #[test]
+    fn test_show_delta() {
+        let record = Record::builder()
+            .args(format_args!("step"))
+            .level(Level::Info)
+            .build();
+        let stamp = LineStamp {
+            delta: Some(Duration::from_millis(12)),
+            ..LineStamp::default()
+        };
+        let config = |format| {
+            LoggerConfig::builder()
+                .use_colors(false)
+                .show_file_info(false)
+                .show_delta(true)
+                .file_format(format)
+                .build()
+        };
+
+        let formatter = LogFormatter::new(config(FileFormat::Text));
+        let stdout = formatter.format_stdout_stamped(&record, stamp);
+        assert!(stdout.contains(" (+12ms) INFO]"), "{stdout}");
+        let file = formatter.format_file_stamped(&record, stamp);
+        assert!(file.contains(" (+12ms) INFO "), "{file}");
+
+        // Only text lines show it
+        for format in [FileFormat::Json, FileFormat::Logfmt] {
+            let formatter = LogFormatter::new(config(format));
+            assert!(!formatter.format_file_stamped(&record, stamp).contains("12ms"));
+        }
+        // Without a delta from the logger, there is nothing to show
+        assert!(!formatter.format_stdout(&record).contains("(+"));
+    }
+
+    #[test]
+    fn test_intra_second_counter() {
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .intra_second_counter(true)
+            .build();
+        let formatter = LogFormatter::new(config);
+        let record = Record::builder()
+            .args(format_args!("tick"))
+            .level(Level::Info)
+            .build();
+
+        // Retry in the unlikely case that the loop crosses a second boundary
+        let stamps = loop {
+            let stamps: Vec<(String, u32)> = (0..5)
+                .map(|_| {
+                    let line = formatter.format_stdout(&record);
+                    let time = line[1..line.find(' ').unwrap()].to_string();
+                    let (second, counter) = time.split_once('.').unwrap();
+                    (second.to_string(), counter.parse().unwrap())
+                })
+                .collect();
+            if stamps.iter().all(|(second, _)| *second == stamps[0].0) {
+                break stamps;
+            }
+        };
+        let counters: Vec<u32> = stamps.iter().map(|(_, counter)| *counter).collect();
+        assert!(counters.windows(2).all(|pair| pair[1] == pair[0] + 1));
+
+        // The file has its own counter
+        let file_line = formatter.format_file(&record);
+        // (in UTC without chrono, so with a `Z` suffix)
+        let time = &file_line[1..file_line.find(" INFO").unwrap()];
+        let time = time.strip_suffix('Z').unwrap_or(time);
+        assert_eq!(time.rsplit_once('.').unwrap().1.len(), 3);
+
+        // Formats with fractions of a second are not numbered
+        assert!(clock::has_subsecond_precision("%H:%M:%S%.3f"));
+        #[cfg(feature = "chrono")]
+        assert!(clock::has_subsecond_precision("%H:%M:%S.%6f"));
+        assert!(!clock::has_subsecond_precision("%Y-%m-%d %H:%M:%S"));
+    }
+
+    #[test]
+    fn test_json_type_key_is_promoted() {
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Json)
+                .build(),
+        );
+        let key_values = [("_type", "myapp::Order"), ("order_id", "A-17")];
+        let record = Record::builder()
+            .args(format_args!("order created"))
+            .level(Level::Debug)
+            .key_values(&key_values)
+            .build();
+
+        let value: serde_json::Value =
+            serde_json::from_str(&formatter.format_file(&record)).unwrap();
+        assert_eq!(value["type"], "myapp::Order");
+        assert_eq!(value["fields"]["order_id"], "A-17");
+        assert!(value["fields"].get("_type").is_none());
+
+        // No key-values: neither `type` nor `fields` are written
+        let plain = Record::builder().args(format_args!("plain")).build();
+        let value: serde_json::Value =
+            serde_json::from_str(&formatter.format_file(&plain)).unwrap();
+        assert!(value.get("type").is_none());
+        assert!(value.get("fields").is_none());
+    }
+
+    #[test]
+    fn test_json_fields_keep_native_types() {
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Json)
+                .build(),
+        );
+        let key_values: [(&str, Value); 5] = [
+            ("count", Value::from(42)),
+            ("offset", Value::from(-7i64)),
+            ("ratio", Value::from(0.5)),
+            ("ok", Value::from(true)),
+            ("name", Value::from("42")),
+        ];
+        let record = Record::builder()
+            .args(format_args!("typed fields"))
+            .key_values(&key_values)
+            .build();
+
+        let value: serde_json::Value =
+            serde_json::from_str(&formatter.format_file(&record)).unwrap();
+        assert_eq!(value["fields"]["count"], serde_json::json!(42));
+        assert_eq!(value["fields"]["offset"], serde_json::json!(-7));
+        assert_eq!(value["fields"]["ratio"], serde_json::json!(0.5));
+        assert_eq!(value["fields"]["ok"], serde_json::json!(true));
+        // Strings stay strings, even if they look like numbers
+        assert_eq!(value["fields"]["name"], serde_json::json!("42"));
+    }
+
+    #[test]
+    fn test_dual_format_markers() {
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Dual)
+                .build(),
+        );
+        let record = Record::builder()
+            .args(format_args!("both ways"))
+            .level(Level::Warn)
+            .file(Some("src/main.rs"))
+            .line(Some(7))
+            .build();
+
+        let output = formatter.format_file(&record);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let text = lines[0].strip_prefix("TXT: ").unwrap();
+        assert!(text.ends_with("WARN src/main.rs:7] both ways"));
+
+        let json = lines[1].strip_prefix("JSN: ").unwrap();
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(value["message"], "both ways");
+        assert_eq!(value["level"], "WARN");
+    }
+
+    #[test]
+    fn test_json_numeric_levels() {
+        let levels = [
+            Level::Trace,
+            Level::Debug,
+            Level::Info,
+            Level::Warn,
+            Level::Error,
+        ];
+
+        for (scale, expected) in [
+            (LevelScale::Ascending, [0, 1, 2, 3, 4]),
+            (LevelScale::Syslog, [7, 7, 6, 4, 3]),
+        ] {
+            let formatter = LogFormatter::new(
+                LoggerConfig::builder()
+                    .file_format(FileFormat::Json)
+                    .numeric_levels(Some(scale))
+                    .build(),
+            );
+
+            for (level, code) in levels.into_iter().zip(expected) {
+                let record = Record::builder()
+                    .args(format_args!("message"))
+                    .level(level)
+                    .build();
+                let value: serde_json::Value =
+                    serde_json::from_str(&formatter.format_file(&record)).unwrap();
+                assert_eq!(value["level_num"], code, "{scale:?} {level}");
+                assert_eq!(value["level"], level.as_str());
+            }
+        }
+
+        // Disabled by default
+        let formatter = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Json)
+                .build(),
+        );
+        let record = Record::builder().args(format_args!("message")).build();
+        let value: serde_json::Value =
+            serde_json::from_str(&formatter.format_file(&record)).unwrap();
+        assert!(value.get("level_num").is_none());
+    }
+
+    #[test]
+    fn test_function_name_is_shown() {
+        let key_values = [("_fn", "handle_request")];
+        let record = Record::builder()
+            .args(format_args!("accepted"))
+            .level(Level::Info)
+            .key_values(&key_values)
+            .build();
+
+        let text = LogFormatter::new(LoggerConfig::builder().use_colors(false).build());
+        assert!(
+            text.format_stdout(&record)
+                .ends_with("] handle_request: accepted")
+        );
+        assert!(
+            text.format_file(&record)
+                .ends_with("] handle_request: accepted\n")
+        );
+
+        let json = LogFormatter::new(
+            LoggerConfig::builder()
+                .file_format(FileFormat::Json)
+                .build(),
+        );
+        let value: serde_json::Value = serde_json::from_str(&json.format_file(&record)).unwrap();
+        assert_eq!(value["function"], "handle_request");
+        assert_eq!(value["message"], "accepted");
+        assert!(value.get("fields").is_none());
+    }
+
+    #[test]
+    fn test_templates() {
+        let config = LoggerConfig::builder()
+            .use_colors(false)
+            .stdout_template(Some("{level} {target} - {message}".to_string()))
+            .file_template(Some(
+                "{{{level}}} {file}:{line} [{thread}] {message}".to_string(),
+            ))
+            .build();
+        let formatter = LogFormatter::new(config);
+        let record = Record::builder()
+            .args(format_args!("templated"))
+            .level(Level::Warn)
+            .target("myapp::core")
+            .file(Some("src/core.rs"))
+            .line(Some(12))
+            .build();
+
+        assert_eq!(
+            formatter.format_stdout(&record),
+            "WARN myapp::core - templated"
+        );
+        let thread = std::thread::current();
+        assert_eq!(
+            formatter.format_file(&record),
+            format!(
+                "{{WARN}} src/core.rs:12 [{}] templated\n",
+                thread.name().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_template_parsing() {
+        assert_eq!(
+            parse_template("[{time}] {message}!").unwrap(),
+            vec![
+                TemplatePart::Literal("[".to_string()),
+                TemplatePart::Time,
+                TemplatePart::Literal("] ".to_string()),
+                TemplatePart::Message,
+                TemplatePart::Literal("!".to_string()),
+            ]
+        );
+
+        for template in ["{time} {lvl}", "{message", "message}"] {
+            assert!(matches!(
+                validate_template(template),
+                Err(LogError::InvalidTemplate(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_json_missing_location_is_null() {
+        let config = LoggerConfig::builder()
+            .file_format(FileFormat::Json)
+            .build();
+        let formatter = LogFormatter::new(config);
+
+        let line = formatter.format_file(&Record::builder().args(format_args!("hi")).build());
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert!(value["file"].is_null());
+        assert!(value["line"].is_null());
+    }
 }
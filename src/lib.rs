@@ -46,28 +46,276 @@
 //!
 //! The library provides convenient presets for common scenarios:
 //!
-//! ```rust
+//! ```rust,no_run
 //! // For development (Debug level, file info shown)
 //! fstdout_logger::init_development_logger(Some("dev.log")).expect("Failed to initialize logger");
 //!
 //! // For production (Info level, no file info)
 //! fstdout_logger::init_production_logger(Some("app.log")).expect("Failed to initialize logger");
+//!
+//! // For cron jobs and other non-terminal output (no colors, short levels)
+//! fstdout_logger::init_compact_logger(None::<&str>).expect("Failed to initialize logger");
 //! ```
 
+#[cfg(feature = "chrono")]
+use chrono::NaiveTime;
 use flate2::Compression;
-use log::{LevelFilter, Log, Metadata, Record};
-use std::fs::{File, OpenOptions, create_dir_all};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fs::{File, OpenOptions, TryLockError, create_dir_all};
 use std::io::{self, Write};
-use std::path::Path;
-use std::sync::Mutex;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+mod clock;
 mod config;
+mod event;
 pub mod examples;
+mod ext;
 pub mod formatter;
+mod macros;
+mod panic;
+mod sink;
+#[cfg(feature = "tracing")]
+mod tracing_bridge;
+mod writer;
+
+pub use clock::{Clock, SystemClock};
+pub use colored::Color;
+pub use config::{
+    BackpressurePolicy, ColorIntensity, ColorMode, ColorStyle, DurationFormat, ErrorHandler,
+    ErrorStream, FileFormat, FileMode, FsyncPolicy, LevelScale, LevelStyle, LineEnding,
+    LoggerConfig, LoggerConfigBuilder, RecordObserver, RetentionPolicy, RotationPolicy,
+    SecondsPrecision, TimeZoneMode, TimestampSource,
+};
+#[cfg(feature = "syslog")]
+pub use config::{SyslogFacility, SyslogTarget, SyslogTransport};
+pub use event::LogEvent;
+pub use ext::{LOG_ERR_TARGET, LogResultExt};
+use formatter::LineStamp;
+pub use formatter::{ColorScheme, LogFormatter};
+pub use panic::install_panic_logger;
+#[cfg(feature = "regex")]
+pub use regex::Regex;
+#[cfg(feature = "syslog")]
+use sink::SyslogSink;
+use sink::{FileSink, RingBuffer, StdoutWriter};
+#[cfg(feature = "tracing")]
+pub use tracing_bridge::{LogBridgeLayer, init_logger_with_tracing};
+pub use writer::LogWriter;
+
+/// Re-export of `log` for use by this crate's macros.
+#[doc(hidden)]
+pub use log as __log;
+
+/// A log file shared between the installed logger and its [`LoggerHandle`].
+///
+/// The sink is `None` once the file has been closed by [`LoggerHandle::shutdown`].
+type SharedFile = Arc<Mutex<Option<FileSink>>>;
+
+/// A log file together with the range of levels written to it.
+#[derive(Clone)]
+struct FileDestination {
+    /// The file, shared with the flusher thread and [`LoggerHandle`]
+    file: SharedFile,
+
+    /// Records more verbose than this are not written to the file
+    level: LevelFilter,
+
+    /// Records more severe than this are not written to the file
+    max_level: Level,
+
+    /// The file to open on the first write, if it is opened lazily
+    lazy: Option<Arc<LazyFile>>,
+}
+
+impl FileDestination {
+    /// Open a log file at `path`, or prepare to open it on the first write
+    /// if `lazy_open` is configured.
+    fn open(
+        path: &Path,
+        level: LevelFilter,
+        max_level: Level,
+        config: &LoggerConfig,
+    ) -> Result<Self, LogError> {
+        let (file, lazy) = if config.lazy_open {
+            let lazy = LazyFile {
+                path: path.to_path_buf(),
+                pending: AtomicBool::new(true),
+            };
+            (share_file(None, config)?, Some(Arc::new(lazy)))
+        } else {
+            (open_file_sink(path, config.file_mode, config)?, None)
+        };
+        Ok(Self {
+            file,
+            level,
+            max_level,
+            lazy,
+        })
+    }
+
+    /// Check whether a record at `level` is written to the file.
+    fn accepts(&self, level: Level) -> bool {
+        level <= self.level && level >= self.max_level
+    }
+}
+
+/// A log file that is opened on the first write instead of at init.
+struct LazyFile {
+    /// Where to open the file
+    path: PathBuf,
+
+    /// Whether the file still has to be opened
+    ///
+    /// Cleared by the first write while holding the lock of the file's sink,
+    /// so the file is opened at most once even if the first records race.
+    pending: AtomicBool,
+}
+
+/// A background thread writing formatted lines to the log files.
+///
+/// Log calls queue their lines over a bounded channel instead of waiting for
+/// the disk and the file locks. The thread exits once every sender, held by
+/// the logger and its handles, is dropped.
+#[derive(Clone)]
+struct FileWorker {
+    /// Queue of the writer thread
+    sender: SyncSender<FileJob>,
+
+    /// The writer thread, which must never wait for its own queue
+    thread: ThreadId,
+
+    /// Number of lines dropped because the queue was full
+    dropped: Arc<AtomicU64>,
+}
+
+/// Work queued for a [`FileWorker`].
+enum FileJob {
+    /// Write a formatted line to the files that accept its level, or to the
+    /// audit file
+    Write {
+        line: String,
+        level: Level,
+        audit: bool,
+    },
+
+    /// Report back once every job queued before is done
+    Drain(SyncSender<()>),
+}
 
-pub use config::{LoggerConfig, LoggerConfigBuilder};
-pub use formatter::LogFormatter;
+impl FileWorker {
+    /// Spawn the writer thread for the given files.
+    fn spawn(
+        files: Vec<FileDestination>,
+        audit_file: Option<SharedFile>,
+        settings: SharedSettings,
+        capacity: usize,
+    ) -> Result<Self, LogError> {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let thread = thread::Builder::new()
+            .name("fstdout-logger-file".to_string())
+            .spawn(move || {
+                for job in receiver {
+                    match job {
+                        FileJob::Write { line, level, audit } => {
+                            let settings = current_settings(&settings);
+                            // A panicking error handler must not stop the thread,
+                            // or every later line would be lost
+                            let _ = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                                write_files(
+                                    &files,
+                                    audit_file.as_ref(),
+                                    &line,
+                                    level,
+                                    audit,
+                                    &settings.formatter,
+                                );
+                            }));
+                        }
+                        FileJob::Drain(done) => {
+                            let _ = done.send(());
+                        }
+                    }
+                }
+            })?;
+        Ok(Self {
+            sender,
+            thread: thread.thread().id(),
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Queue a line for the writer thread, applying the configured backpressure.
+    fn send(&self, line: String, level: Level, audit: bool, config: &LoggerConfig) {
+        let job = FileJob::Write { line, level, audit };
+        let blocking = config.async_file_backpressure == BackpressurePolicy::Block
+            && thread::current().id() != self.thread;
+        let result = if blocking {
+            self.sender
+                .send(job)
+                .map_err(|_| TrySendError::Disconnected(()))
+        } else {
+            self.sender.try_send(job).map_err(|error| match error {
+                TrySendError::Full(_) => TrySendError::Full(()),
+                TrySendError::Disconnected(_) => TrySendError::Disconnected(()),
+            })
+        };
+        let error = match result {
+            Ok(()) => return,
+            Err(TrySendError::Full(())) => {
+                LogError::FileQueueFull(self.dropped.fetch_add(1, Ordering::Relaxed) + 1)
+            }
+            Err(TrySendError::Disconnected(())) => LogError::FileWriterStopped,
+        };
+        report_error(config.on_error.as_ref(), error);
+    }
+
+    /// Wait until every line queued so far has been written.
+    fn drain(&self) {
+        // The writer thread would wait for itself, e.g. when the error
+        // handler flushes
+        if thread::current().id() == self.thread {
+            return;
+        }
+        let (done, finished) = mpsc::sync_channel(1);
+        if self.sender.send(FileJob::Drain(done)).is_ok() {
+            let _ = finished.recv();
+        }
+    }
+}
+
+/// The console writer shared between the installed logger and its [`LoggerHandle`].
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// The settings of a logger, shared with its [`LoggerHandle`].
+///
+/// Log calls only hold the lock long enough to clone the inner `Arc`, so
+/// [`LoggerHandle::set_config`] never waits for a record being written and
+/// records logged from an error handler can't deadlock.
+type SharedSettings = Arc<RwLock<Arc<Settings>>>;
+
+/// The recent lines kept in memory, shared with the [`LoggerHandle`].
+type SharedRingBuffer = Arc<Mutex<RingBuffer>>;
+
+/// Target of the records logged by [`audit!`].
+///
+/// Records with this target are written to the configured
+/// [`audit_file`](LoggerConfigBuilder::audit_file) instead of the log file.
+/// They bypass the level and module filters and are never collapsed as
+/// repeats, so the audit trail has no gaps.
+pub const AUDIT_TARGET: &str = "audit";
+
+/// The logger registered with the `log` crate, once initialized.
+static INSTALLED: OnceLock<&'static FStdoutLogger> = OnceLock::new();
+
+/// Whether the thread writing repeat summaries of the installed logger runs.
+static DEDUP_TIMER: AtomicBool = AtomicBool::new(false);
 
 /// Errors that can occur when using the logger.
 #[derive(Error, Debug)]
@@ -79,6 +327,50 @@ pub enum LogError {
     /// Errors when setting up the global logger.
     #[error("Failed to set logger")]
     Logger,
+
+    /// Errors when an operation requires the global logger but none is installed.
+    #[error("Logger is not initialized")]
+    NotInitialized,
+
+    /// Errors when another process holds the lock on a log file for too long.
+    #[error("Log file is locked by another process: {}", .0.display())]
+    FileLocked(PathBuf),
+
+    /// Errors when a timestamp format string contains an invalid specifier.
+    #[error("Invalid time format: {0}")]
+    InvalidTimeFormat(String),
+
+    /// Errors when a format template contains an unknown token or unbalanced braces.
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+
+    /// Errors when a `RUST_LOG`-style filter string contains a malformed directive.
+    #[error("Invalid log filter: {0}")]
+    InvalidFilter(String),
+
+    /// Errors when a level given as a string is not a known level name or number.
+    #[error("Invalid log level: {0}")]
+    InvalidLevel(String),
+
+    /// Errors when a line could not be sent to the channel sink, with the
+    /// total number of lines dropped so far.
+    #[error("Log channel is full or closed, {0} lines dropped")]
+    ChannelDropped(u64),
+
+    /// Errors when a line could not be queued for the asynchronous file
+    /// writer, with the total number of lines dropped so far.
+    #[error("Log file queue is full, {0} lines dropped")]
+    FileQueueFull(u64),
+
+    /// Errors when a line could not be queued because the asynchronous file
+    /// writer thread has stopped.
+    #[error("Log file writer thread has stopped")]
+    FileWriterStopped,
+
+    /// Errors when a configuration is invalid, e.g. a TOML or JSON document
+    /// that can't be parsed or has unknown keys.
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
 }
 
 /// The main logger implementation that outputs to stdout and optionally to a file.
@@ -105,10 +397,59 @@ pub enum LogError {
 /// logger.init_with_level(LevelFilter::Info).expect("Failed to initialize logger");
 /// ```
 pub struct FStdoutLogger {
-    /// Optional file to log to
-    log_file: Option<Mutex<File>>,
+    /// Primary console output, stdout unless a custom writer was given
+    console: SharedWriter,
+
+    /// Files to log to, each with its own minimum level
+    files: Vec<FileDestination>,
+
+    /// Optional file that receives audit events instead of `files`
+    audit_file: Option<SharedFile>,
+
+    /// Channel that receives every record instead of the console and files
+    processor: Option<Sender<LogEvent>>,
+
+    /// The most recent lines, if `ring_buffer_capacity` is set
+    recent: Option<SharedRingBuffer>,
+
+    /// Number of lines that could not be sent to the channel sink
+    channel_dropped: AtomicU64,
+
+    /// Sequence number of the last record, if `show_sequence` is set
+    sequence: AtomicU64,
+
+    /// Time of the last record, if `show_delta` is set
+    last_record: DeltaClock,
+
+    /// Connection to the syslog daemon, if configured
+    #[cfg(feature = "syslog")]
+    syslog: Option<Mutex<SyslogSink>>,
+
+    /// Filtering and formatting settings, replaceable at runtime
+    settings: SharedSettings,
+
+    /// Canonical path of the main log file, if it was opened from a path
+    log_path: Option<PathBuf>,
+
+    /// Writer thread for the files, if `async_file` is configured
+    file_worker: Option<FileWorker>,
+}
+
+/// The parts of a logger derived from its configuration.
+///
+/// These are rebuilt from scratch when the configuration is replaced with
+/// [`LoggerHandle::set_config`]. The console writer and the files are kept.
+struct Settings {
+    /// Level applied to modules without a per-module level
+    level: LevelFilter,
+
+    /// Cap read from `FSTDOUT_MAX_LEVEL`, if `respect_env_level` is enabled
+    env_level: Option<EnvLevel>,
 
-    /// Formatter for log messages
+    /// The last message, if repeated messages are collapsed
+    dedup: Option<Dedup>,
+
+    /// Formatter for log messages, which owns the configuration
     formatter: LogFormatter,
 }
 
@@ -137,72 +478,285 @@ impl FStdoutLogger {
     ///
     /// # Returns
     ///
-    /// A new logger instance or an error if the log file couldn't be opened.
+    /// A new logger instance, or an error if a custom time format is invalid
+    /// or the log file couldn't be opened.
     pub fn with_config<P: AsRef<Path>>(
         file_path: Option<P>,
         config: LoggerConfig,
     ) -> Result<Self, LogError> {
-        let log_file = match file_path {
-            Some(path) => {
-                let file = Path::new(path.as_ref()).to_path_buf();
-                if let Some(parent) = file.parent() {
-                    create_dir_all(parent)?;
-                };
-                if file.exists() {
-                    use flate2::write::GzEncoder;
-                    use tar::Builder;
-
-                    let file_basename = format!("{}", chrono::Local::now().format("%d%m%Y_%H%M%S"));
-                    let archive_ref = format!("{}.tar.xz", file_basename);
-                    let archive_file = File::create(&archive_ref)?;
-
-                    let encoder = GzEncoder::new(archive_file, Compression::default());
-                    let mut archive = Builder::new(encoder);
-
-                    archive.append_file(
-                        Path::new(&format!("{}.log", file_basename)),
-                        &mut File::open(file)?,
-                    )?;
-
-                    archive.into_inner().unwrap();
-                }
-                let file = OpenOptions::new()
-                    .create(true)
-                    .truncate(true)
-                    .write(true)
-                    .open(path)?;
-                Some(Mutex::new(file))
-            }
+        let mut logger = Self::open(file_path, config)?;
+        logger.start_file_worker()?;
+        Ok(logger)
+    }
+
+    /// Create a logger like [`with_config`](Self::with_config), without
+    /// starting the file writer thread.
+    ///
+    /// This lets a constructor add file destinations before the writer
+    /// thread takes its copy of them.
+    fn open<P: AsRef<Path>>(file_path: Option<P>, config: LoggerConfig) -> Result<Self, LogError> {
+        validate_config(&config)?;
+
+        // There is no file system to write to in the browser
+        #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+        let file_path: Option<P> = {
+            drop(file_path);
+            None
+        };
+
+        // The main log file takes every record the logger accepts
+        let mut files = Vec::new();
+        if let Some(path) = &file_path {
+            files.push(FileDestination::open(
+                path.as_ref(),
+                LevelFilter::Trace,
+                Level::Error,
+                &config,
+            )?);
+        }
+        // Keep the path as given if it can't be resolved, e.g. while a
+        // lazily opened file doesn't exist yet
+        let log_path = file_path.map(|path| {
+            std::fs::canonicalize(&path).unwrap_or_else(|_| path.as_ref().to_path_buf())
+        });
+        let extra_files = config
+            .files
+            .iter()
+            .map(|(path, level)| (path, level, &Level::Error))
+            .chain(
+                config
+                    .file_ranges
+                    .iter()
+                    .map(|(path, level, max)| (path, level, max)),
+            )
+            .filter(|_| cfg!(not(all(feature = "wasm", target_arch = "wasm32"))));
+        for (path, level, max_level) in extra_files {
+            files.push(FileDestination::open(path, *level, *max_level, &config)?);
+        }
+
+        // Audit trails are never archived or truncated, and like the log file
+        // they are not written in the browser
+        let audit_file = match config
+            .audit_file
+            .as_ref()
+            .filter(|_| cfg!(not(all(feature = "wasm", target_arch = "wasm32"))))
+        {
+            Some(path) => Some(open_file_sink(path, FileMode::Append, &config)?),
             None => None,
         };
 
-        Ok(Self {
-            log_file,
-            formatter: LogFormatter::new(config),
-        })
+        let console: Box<dyn Write + Send> = if config.fast_stdout {
+            Box::new(sink::raw_stdout()?)
+        } else {
+            Box::new(StdoutWriter)
+        };
+
+        let mut logger = Self::from_parts(console, files, audit_file, config);
+        logger.log_path = log_path;
+        Ok(logger)
+    }
+
+    /// Create a logger that writes to an already open log file.
+    ///
+    /// This works like [`with_config`](Self::with_config), but the file is
+    /// taken as is instead of being opened from a path, e.g. when a
+    /// supervisor passes the log file as a file descriptor or a test uses a
+    /// temporary file. Writes go to the file's current position. The file
+    /// mode, rotation, retention, the index and the metadata sidecar don't
+    /// apply, as they all need the file's path.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The open log file, writable.
+    /// * `config` - Configuration options for the logger.
+    ///
+    /// # Returns
+    ///
+    /// A new logger instance, or an error if a custom time format is invalid
+    /// or the extra or audit files couldn't be opened.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fstdout_logger::{FStdoutLogger, LoggerConfig};
+    /// use std::fs::File;
+    ///
+    /// let file = File::create("supervised.log").expect("Failed to create file");
+    /// let logger = FStdoutLogger::with_file(file, LoggerConfig::default())
+    ///     .expect("Failed to create logger");
+    /// # drop(logger);
+    /// # let _ = std::fs::remove_file("supervised.log");
+    /// ```
+    pub fn with_file(file: File, config: LoggerConfig) -> Result<Self, LogError> {
+        let sink = FileSink::new(file, None, &config);
+        let mut logger = Self::open(None::<&Path>, config)?;
+        logger.files.insert(
+            0,
+            FileDestination {
+                file: share_file_sink(sink, logger.settings().formatter.config())?,
+                level: LevelFilter::Trace,
+                max_level: Level::Error,
+                lazy: None,
+            },
+        );
+        logger.start_file_worker()?;
+        Ok(logger)
+    }
+
+    /// Create a logger that writes its console output to a custom writer.
+    ///
+    /// Everything that would normally be printed to stdout is written to
+    /// `writer` instead, formatted the same way. This makes it possible to
+    /// capture log output, e.g. into an in-memory buffer in tests. Warnings
+    /// and errors still go to stderr if `error_stream` is set to
+    /// `ErrorStream::Stderr`. The logger has no log file.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination for console output.
+    /// * `config` - Configuration options for the logger.
+    ///
+    /// # Returns
+    ///
+    /// A new logger instance, or an error if a custom time format is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fstdout_logger::{FStdoutLogger, LoggerConfig};
+    ///
+    /// // Send console output to stderr instead of stdout
+    /// let logger = FStdoutLogger::with_writer(std::io::stderr(), LoggerConfig::default())
+    ///     .expect("Failed to create logger");
+    /// ```
+    pub fn with_writer<W: Write + Send + 'static>(
+        writer: W,
+        config: LoggerConfig,
+    ) -> Result<Self, LogError> {
+        validate_config(&config)?;
+        Ok(Self::from_parts(Box::new(writer), Vec::new(), None, config))
+    }
+
+    /// Create a logger that hands every record to a user-supplied channel.
+    ///
+    /// Records that pass the logger's filters are converted into owned
+    /// [`LogEvent`]s and sent to `sender`; nothing is written to the console
+    /// or to a file. The receiving end decides what to do with the events,
+    /// typically on a thread owned by the application. Events are dropped
+    /// once the receiver has been dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - Channel the events are sent to.
+    /// * `config` - Configuration options for the logger. Only filtering,
+    ///   redactions and the time zone of the timestamps apply.
+    ///
+    /// # Returns
+    ///
+    /// A new logger instance, or an error if a custom time format is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fstdout_logger::{FStdoutLogger, LogEvent, LoggerConfig};
+    /// use std::sync::mpsc;
+    ///
+    /// let (sender, receiver) = mpsc::channel::<LogEvent>();
+    /// let logger = FStdoutLogger::with_processor(sender, LoggerConfig::default())
+    ///     .expect("Failed to create logger");
+    ///
+    /// std::thread::spawn(move || {
+    ///     for event in receiver {
+    ///         // Ship the event somewhere
+    ///         let _ = (event.level, event.message);
+    ///     }
+    /// });
+    /// # drop(logger);
+    /// ```
+    pub fn with_processor(
+        sender: Sender<LogEvent>,
+        config: LoggerConfig,
+    ) -> Result<Self, LogError> {
+        validate_config(&config)?;
+        let mut logger = Self::from_parts(Box::new(io::sink()), Vec::new(), None, config);
+        logger.processor = Some(sender);
+        Ok(logger)
+    }
+
+    /// Assemble a logger from its console writer, optional files and configuration.
+    fn from_parts(
+        console: Box<dyn Write + Send>,
+        files: Vec<FileDestination>,
+        audit_file: Option<SharedFile>,
+        config: LoggerConfig,
+    ) -> Self {
+        Self {
+            console: Arc::new(Mutex::new(console)),
+            files,
+            audit_file,
+            processor: None,
+            recent: config
+                .ring_buffer_capacity
+                .map(|capacity| Arc::new(Mutex::new(RingBuffer::new(capacity)))),
+            channel_dropped: AtomicU64::new(0),
+            sequence: AtomicU64::new(0),
+            last_record: DeltaClock::default(),
+            #[cfg(feature = "syslog")]
+            syslog: config
+                .syslog
+                .as_ref()
+                .map(|target| Mutex::new(SyslogSink::new(target))),
+            settings: Arc::new(RwLock::new(Arc::new(Settings::new(config.level, config)))),
+            log_path: None,
+            file_worker: None,
+        }
+    }
+
+    /// Hand file writes to a new writer thread if `async_file` is configured.
+    ///
+    /// A previous writer thread exits once its queue is empty.
+    fn start_file_worker(&mut self) -> Result<(), LogError> {
+        let settings = self.settings();
+        let config = settings.formatter.config();
+        if config.async_file && (!self.files.is_empty() || self.audit_file.is_some()) {
+            self.file_worker = Some(FileWorker::spawn(
+                self.files.clone(),
+                self.audit_file.clone(),
+                Arc::clone(&self.settings),
+                config.async_file_capacity,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Get the current settings.
+    fn settings(&self) -> Arc<Settings> {
+        current_settings(&self.settings)
     }
 
     /// Initialize the logger with the default configuration.
     ///
-    /// This sets the maximum log level to `Trace` to enable all logs,
-    /// but actual filtering will happen according to the `level` setting
-    /// in the logger's configuration.
+    /// This sets the global maximum log level to the most verbose of the
+    /// configured level and the per-module levels, so that cheap checks in
+    /// the `log` macros skip records no module wants. The actual filtering
+    /// happens according to the logger's configuration.
     ///
     /// # Returns
     ///
     /// `Ok(())` if initialization succeeded, or an error if it failed.
     pub fn init(self) -> Result<(), LogError> {
-        if log::set_logger(Box::leak(Box::new(self))).is_err() {
-            return Err(LogError::Logger);
-        }
-        log::set_max_level(LevelFilter::Trace);
+        let max_level = self.settings().max_level();
+        self.install()?;
+        log::set_max_level(max_level);
         Ok(())
     }
 
     /// Initialize the logger with a specific log level.
     ///
-    /// This sets the global maximum log level, overriding the level
-    /// in the logger's configuration.
+    /// This overrides the level in the logger's configuration. Per-module
+    /// levels still take precedence for their modules, even if they are more
+    /// verbose than `level`, and the global maximum log level is set to the
+    /// most verbose of them.
     ///
     /// # Arguments
     ///
@@ -212,137 +766,1405 @@ impl FStdoutLogger {
     ///
     /// `Ok(())` if initialization succeeded, or an error if it failed.
     pub fn init_with_level(self, level: LevelFilter) -> Result<(), LogError> {
-        if log::set_logger(Box::leak(Box::new(self))).is_err() {
-            return Err(LogError::Logger);
+        let mut config = self.settings().formatter.config().clone();
+        config.level = level;
+        for file in self.file_sinks() {
+            let result = match sink::lock(file).as_mut() {
+                Some(sink) => sink.reconfigure(&config),
+                None => Ok(()),
+            };
+            if let Err(error) = result {
+                report_error(config.on_error.as_ref(), error);
+            }
         }
-        log::set_max_level(level);
+        let settings = Settings::new(level, config);
+        let max_level = settings.max_level();
+        replace_settings(&self.settings, settings);
+        self.install()?;
+        log::set_max_level(max_level);
         Ok(())
     }
-}
 
-/// Implementation of the `Log` trait for `FStdoutLogger`.
-///
-/// This handles:
-/// - Checking if a log message should be processed
-/// - Formatting messages differently for stdout and file
-/// - Writing to both destinations
-/// - Flushing output streams
-impl Log for FStdoutLogger {
-    fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= log::max_level()
+    /// Initialize the logger with a specific log level and return a handle to it.
+    ///
+    /// This works like [`init_with_level`](Self::init_with_level), but the
+    /// returned [`LoggerHandle`] shares the logger's file, so buffered lines
+    /// can be flushed and the file closed deterministically at shutdown.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The minimum log level to display.
+    ///
+    /// # Returns
+    ///
+    /// A handle to the installed logger, or an error if initialization failed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fstdout_logger::FStdoutLogger;
+    /// use log::{LevelFilter, info};
+    ///
+    /// let handle = FStdoutLogger::new(Some("handle.log"))
+    ///     .expect("Failed to create logger")
+    ///     .init_with_handle(LevelFilter::Info)
+    ///     .expect("Failed to initialize logger");
+    ///
+    /// info!("Shutting down");
+    /// handle.shutdown();
+    /// ```
+    pub fn init_with_handle(self, level: LevelFilter) -> Result<LoggerHandle, LogError> {
+        let handle = self.handle();
+        self.init_with_level(level)?;
+        Ok(handle)
     }
 
-    fn log(&self, record: &Record) {
-        if !self.enabled(record.metadata()) {
+    /// Create a handle sharing this logger's outputs and settings.
+    fn handle(&self) -> LoggerHandle {
+        LoggerHandle {
+            console: Arc::clone(&self.console),
+            files: self
+                .files
+                .iter()
+                .map(|dest| Arc::clone(&dest.file))
+                .collect(),
+            lazy_files: self
+                .files
+                .iter()
+                .filter_map(|dest| dest.lazy.clone())
+                .collect(),
+            audit_file: self.audit_file.clone(),
+            recent: self.recent.clone(),
+            settings: Arc::clone(&self.settings),
+            log_path: self.log_path.clone(),
+            file_worker: self.file_worker.clone(),
+        }
+    }
+
+    /// Switch the log files between buffered and direct writes.
+    ///
+    /// Pending buffered data is flushed before the mode changes. This is a
+    /// no-op for loggers without a log file.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffered` - Whether file writes should be buffered from now on.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the mode was changed, or an error if flushing failed.
+    pub fn set_buffered(&self, buffered: bool) -> Result<(), LogError> {
+        for file in self.file_sinks() {
+            if let Some(file) = sink::lock(file).as_mut() {
+                file.set_buffered(buffered)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterate over the log files and the audit file.
+    fn file_sinks(&self) -> impl Iterator<Item = &SharedFile> {
+        self.files
+            .iter()
+            .map(|dest| &dest.file)
+            .chain(&self.audit_file)
+    }
+
+    /// Format and write a record without going through the global `log` registry.
+    ///
+    /// The record is filtered by this logger's level, per-module levels and
+    /// quiet windows, but not by the global `log::max_level`, so any number of
+    /// loggers with different configurations can be driven directly, e.g.
+    /// from tests, without installing any of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record to log.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fstdout_logger::{FStdoutLogger, LoggerConfig};
+    /// use log::{Level, Record};
+    ///
+    /// let logger = FStdoutLogger::with_writer(Vec::new(), LoggerConfig::default())
+    ///     .expect("Failed to create logger");
+    ///
+    /// logger.log_record(
+    ///     &Record::builder()
+    ///         .args(format_args!("not installed"))
+    ///         .level(Level::Info)
+    ///         .build(),
+    /// );
+    /// ```
+    pub fn log_record(&self, record: &Record) {
+        let settings = self.settings();
+        if !settings.accepts(record.metadata()) {
             return;
         }
 
-        // Format for stdout (with or without colors)
-        let stdout_formatted = format!("{}\n", self.formatter.format_stdout(record));
+        if let Some(dedup) = &settings.dedup
+            && record.target() != AUDIT_TARGET
+        {
+            match dedup.observe(record) {
+                Repeat::Suppressed => return,
+                Repeat::Ended(Some(last)) => self.write_repeats(&settings, &last),
+                Repeat::Ended(None) => {}
+            }
+        }
+        if let Some(observer) = &settings.formatter.config().observer {
+            observer.observe(record);
+        }
+        self.write(&settings, record);
+    }
+
+    /// Write the summary line of a message's dropped repeats.
+    fn write_repeats(&self, settings: &Settings, last: &LastMessage) {
+        self.write(
+            settings,
+            &Record::builder()
+                .args(format_args!(
+                    "{} (repeated {} times)",
+                    last.message, last.repeats
+                ))
+                .level(last.level)
+                .target(&last.target)
+                .build(),
+        );
+    }
+
+    /// Write the summary of repeats that is still pending, if any.
+    ///
+    /// With `force`, the summary is written even if the dedup window hasn't
+    /// passed yet, e.g. when flushing.
+    fn write_pending_repeats(&self, force: bool) {
+        let settings = self.settings();
+        if let Some(dedup) = &settings.dedup
+            && let Some(last) = dedup.take_pending(force)
+        {
+            self.write_repeats(&settings, &last);
+        }
+    }
+
+    /// Send an accepted record to the processor, or to the console and files.
+    fn write(&self, settings: &Settings, record: &Record) {
+        let formatter = &settings.formatter;
+
+        // A custom processor takes the place of all built-in outputs
+        if let Some(processor) = &self.processor {
+            let message = formatter.render_message_text(record.args());
+            let _ = processor.send(LogEvent::from_record(
+                record,
+                message,
+                formatter.now().into(),
+            ));
+            return;
+        }
 
-        // Log to stdout
-        print!("{stdout_formatted}");
+        let config = formatter.config();
+        let stamp = LineStamp {
+            sequence: config
+                .show_sequence
+                .then(|| self.sequence.fetch_add(1, Ordering::Relaxed) + 1),
+            delta: config.show_delta.then(|| self.last_record.next()),
+        };
+        if config.stdout_enabled || config.channel_sink.is_some() {
+            let line = formatter.format_stdout_stamped(record, stamp);
+            if let Some(channel) = &config.channel_sink {
+                self.send_to_channel(channel, line.clone(), config.on_error.as_ref());
+            }
+            if config.stdout_enabled {
+                self.write_console(formatter, record.level(), &line);
+            }
+        }
 
-        // Log to file if configured
-        if let Some(file) = &self.log_file {
-            if let Ok(mut file) = file.lock() {
-                // Format for file (always without colors)
-                let file_formatted = self.formatter.format_file(record);
+        // Format for files (always without colors), once for all of them,
+        // and write it here or on the writer thread
+        let audit = self.audit_file.is_some() && record.target() == AUDIT_TARGET;
+        let mut file_formatted = None;
+        if audit || self.files.iter().any(|dest| dest.accepts(record.level())) {
+            let line = file_formatted.insert(formatter.format_file_stamped(record, stamp));
+            match &self.file_worker {
+                Some(worker) => worker.send(line.clone(), record.level(), audit, config),
+                None => write_files(
+                    &self.files,
+                    self.audit_file.as_ref(),
+                    line,
+                    record.level(),
+                    audit,
+                    formatter,
+                ),
+            }
+        }
 
-                // Ignore errors when writing to file as we don't want to crash the application
-                let _ = file.write_all(file_formatted.as_bytes());
+        #[cfg(feature = "syslog")]
+        if let Some(syslog) = &self.syslog {
+            let result = {
+                let mut syslog = sink::lock(syslog);
+                let message = formatter.format_syslog(record, syslog.target(), syslog.hostname());
+                syslog.send(&message)
+            };
+            if let Err(error) = result {
+                report_error(formatter.config().on_error.as_ref(), error);
             }
         }
+
+        if let Some(recent) = &self.recent {
+            let line =
+                file_formatted.get_or_insert_with(|| formatter.format_file_stamped(record, stamp));
+            sink::lock(recent).push(
+                line.strip_suffix(formatter.file_separator())
+                    .unwrap_or(line),
+            );
+        }
     }
 
-    fn flush(&self) {
-        // Flush stdout
-        let _ = io::stdout().flush();
+    /// Write a line formatted for stdout to the console.
+    fn write_console(&self, formatter: &LogFormatter, level: Level, line: &str) {
+        let separator = &formatter.config().record_separator;
+        let stdout_formatted = format!("{line}{separator}");
+
+        // In the browser, log to the devtools console
+        #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+        sink::write_browser_console(
+            level,
+            stdout_formatted
+                .strip_suffix(separator.as_str())
+                .unwrap_or(&stdout_formatted),
+        );
+
+        // Log to the console writer, or to stderr for warnings and errors if configured
+        #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+        if formatter.config().error_stream == ErrorStream::Stderr && level <= Level::Warn {
+            eprint!("{stdout_formatted}");
+        } else {
+            let _ = sink::lock(&self.console).write_all(stdout_formatted.as_bytes());
+        }
+    }
 
-        // Flush file if configured
-        if let Some(file) = &self.log_file {
-            if let Ok(mut file) = file.lock() {
-                let _ = file.flush();
+    /// Send a line to the channel sink without waiting for the receiver.
+    fn send_to_channel(
+        &self,
+        channel: &SyncSender<String>,
+        line: String,
+        on_error: Option<&ErrorHandler>,
+    ) {
+        if channel.try_send(line).is_err() {
+            let dropped = self.channel_dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if let Some(handler) = on_error {
+                handler.report(LogError::ChannelDropped(dropped));
             }
         }
     }
-}
 
-//
-// Helper functions for easily initializing the logger
-//
+    /// Leak the logger and register it with the `log` crate.
+    fn install(self) -> Result<(), LogError> {
+        let logger: &'static Self = Box::leak(Box::new(self));
+        if log::set_logger(logger).is_err() {
+            return Err(LogError::Logger);
+        }
+        #[cfg(windows)]
+        sink::enable_ansi_colors();
+        let _ = INSTALLED.set(logger);
+        let settings = logger.settings();
+        let config = settings.formatter.config();
+        if config.dedup_window.is_some() {
+            start_dedup_timer(logger)?;
+        }
+        if config.init_banner_stdout
+            && config.stdout_enabled
+            && let Some(banner) = render_init_banner(config, settings.level)
+        {
+            let line = format!("{banner}{}", config.record_separator);
+            let _ = sink::lock(&logger.console).write_all(line.as_bytes());
+        }
+        if config.log_startup_banner {
+            logger.write_startup_banner(&settings);
+        }
+        Ok(())
+    }
 
-/// Initialize a logger with default configuration.
-///
-/// This uses [`LoggerConfig::default()`] which sets:
-/// - `Info` as the minimum log level
-/// - File information shown in logs
-/// - No date in stdout output (only time)
-/// - Colors enabled for terminal output
-///
-/// # Arguments
-///
-/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
-///
-/// # Returns
+    /// Write a one-line summary of the effective configuration.
+    ///
+    /// The banner bypasses the level filters, but like any other `Info`
+    /// record it skips files that only take more severe records.
+    fn write_startup_banner(&self, settings: &Settings) {
+        let config = settings.formatter.config();
+        let mut banner = format!("logger started level={}", settings.level);
+        if !config.module_levels.is_empty() {
+            let modules: Vec<String> = config
+                .module_levels
+                .iter()
+                .map(|(prefix, level)| format!("{prefix}:{level}"))
+                .collect();
+            banner.push_str(&format!(" modules={}", modules.join(",")));
+        }
+        banner.push_str(&format!(
+            " format={:?} colors={}",
+            config.file_format,
+            settings.formatter.colors_enabled(Level::Info)
+        ));
+
+        let mut destinations = vec![if self.processor.is_some() {
+            "channel".to_string()
+        } else {
+            "console".to_string()
+        }];
+        // Files handed over already open have no path to show
+        let name = |file: &SharedFile| {
+            sink::lock(file).as_ref().map(|file| {
+                file.path()
+                    .map_or("file".to_string(), |path| path.display().to_string())
+            })
+        };
+        for dest in &self.files {
+            // Lazily opened files may not be open yet
+            let path = name(&dest.file).or_else(|| {
+                dest.lazy
+                    .as_ref()
+                    .map(|lazy| lazy.path.display().to_string())
+            });
+            if let Some(path) = path {
+                destinations.push(match (dest.level, dest.max_level) {
+                    (LevelFilter::Trace, Level::Error) => path,
+                    (level, Level::Error) => format!("{path}:{level}"),
+                    (level, max_level) => format!("{path}:{level}-{max_level}"),
+                });
+            }
+        }
+        if let Some(path) = self.audit_file.as_ref().and_then(name) {
+            destinations.push(format!("{path}:audit"));
+        }
+        #[cfg(feature = "syslog")]
+        if self.syslog.is_some() {
+            destinations.push("syslog".to_string());
+        }
+        banner.push_str(&format!(" destinations={}", destinations.join(",")));
+
+        self.write(
+            settings,
+            &Record::builder()
+                .args(format_args!("{banner}"))
+                .level(Level::Info)
+                .target(module_path!())
+                .build(),
+        );
+    }
+}
+
+/// Implementation of the `Log` trait for `FStdoutLogger`.
 ///
-/// `Ok(())` if initialization succeeded, or an error if it failed.
+/// This handles:
+/// - Checking if a log message should be processed
+/// - Formatting messages differently for stdout and file
+/// - Writing to the console (stdout, or stderr for warnings and errors if
+///   configured) and the file
+/// - Flushing output streams
+impl Log for FStdoutLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Apply the per-module levels too, so `log_enabled!` callers can skip
+        // building records this logger would drop
+        (metadata.level() <= log::max_level() || metadata.target() == AUDIT_TARGET)
+            && self.settings().accepts(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        // `log_record` applies the logger's own filters, taking the settings
+        // lock only once
+        if record.level() <= log::max_level() || record.target() == AUDIT_TARGET {
+            self.log_record(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.write_pending_repeats(true);
+        flush_outputs(
+            &self.console,
+            self.file_worker.as_ref(),
+            self.file_sinks(),
+            self.settings().formatter.config().on_error.as_ref(),
+        );
+    }
+}
+
+/// Flushes buffered output when a logger that was never installed is dropped.
+impl Drop for FStdoutLogger {
+    fn drop(&mut self) {
+        Log::flush(self);
+    }
+}
+
+impl Settings {
+    /// Derive the settings of a logger from its configuration.
+    fn new(level: LevelFilter, config: LoggerConfig) -> Self {
+        Self {
+            level,
+            env_level: config.respect_env_level.then(EnvLevel::new),
+            dedup: config.dedup_window.map(Dedup::new),
+            formatter: LogFormatter::new(config),
+        }
+    }
+
+    /// Get the effective level for records from `target`.
+    ///
+    /// This is the per-module level if one matches, or the global level
+    /// otherwise, capped by the quiet level if `time` is inside a quiet window
+    /// and by `FSTDOUT_MAX_LEVEL` if enabled.
+    fn level_for(
+        &self,
+        target: &str,
+        #[cfg(feature = "chrono")] time: Option<NaiveTime>,
+    ) -> LevelFilter {
+        let config = self.formatter.config();
+        let mut level = config.module_level(target).unwrap_or(self.level);
+        #[cfg(feature = "chrono")]
+        if let Some(quiet) = time.and_then(|time| config.quiet_level_at(time)) {
+            level = level.min(quiet);
+        }
+        if let Some(cap) = self.env_level.as_ref().and_then(EnvLevel::get) {
+            level = level.min(cap);
+        }
+        level
+    }
+
+    /// Get the most verbose level any record can be logged at.
+    fn max_level(&self) -> LevelFilter {
+        self.formatter
+            .config()
+            .module_levels
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, Ord::max)
+    }
+
+    /// Check the logger's own filters, ignoring the global `log::max_level`.
+    fn accepts(&self, metadata: &Metadata) -> bool {
+        // Audit events form a trail that must not have gaps
+        if metadata.target() == AUDIT_TARGET {
+            return true;
+        }
+        #[cfg(feature = "chrono")]
+        let level = if self.formatter.config().quiet_windows.is_empty() {
+            self.level_for(metadata.target(), None)
+        } else {
+            self.level_for(metadata.target(), Some(self.formatter.now().time()))
+        };
+        #[cfg(not(feature = "chrono"))]
+        let level = self.level_for(metadata.target());
+        metadata.level() <= level
+    }
+}
+
+/// Get the current settings of a logger.
+fn current_settings(settings: &SharedSettings) -> Arc<Settings> {
+    Arc::clone(&settings.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Replace the settings of a logger, for the records logged from now on.
+fn replace_settings(settings: &SharedSettings, new: Settings) {
+    *settings.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(new);
+}
+
+/// Open a log file and wrap it in a sink shared with the flusher thread.
+fn open_file_sink(
+    path: &Path,
+    file_mode: FileMode,
+    config: &LoggerConfig,
+) -> Result<SharedFile, LogError> {
+    share_file(
+        Some(open_file(
+            path,
+            file_mode,
+            config.file_lock_timeout,
+            config,
+        )?),
+        config,
+    )
+}
+
+/// Open a log file and start its session.
+///
+/// Missing parent directories are created. With `file_locking`, the lock is
+/// waited for up to `lock_timeout`. If the file already has content,
+/// it is archived first in [`FileMode::Archive`], or the configured restart
+/// separator is written after it in [`FileMode::Append`].
+fn open_file(
+    path: &Path,
+    file_mode: FileMode,
+    lock_timeout: Duration,
+    config: &LoggerConfig,
+) -> Result<FileSink, LogError> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    };
+    let existed = path.exists();
+    let restarted = file_mode == FileMode::Append && path.metadata().is_ok_and(|m| m.len() > 0);
+
+    // Previous contents are only archived and truncated once the file is
+    // locked, if locking is enabled
+    let mut options = OpenOptions::new();
+    options.create(true);
+    match file_mode {
+        FileMode::Append => options.append(true),
+        FileMode::Archive | FileMode::Truncate => options.write(true),
+    };
+    let file = options.open(path)?;
+    if config.file_locking {
+        lock_file(&file, path, lock_timeout)?;
+    }
+
+    if existed && file_mode == FileMode::Archive {
+        use flate2::write::GzEncoder;
+        use tar::Builder;
+
+        let file_basename = clock::Timestamp::now().format("%d%m%Y_%H%M%S");
+        let archive_ref = format!("{}.tar.xz", file_basename);
+        let archive_file = File::create(&archive_ref)?;
+
+        let encoder = GzEncoder::new(archive_file, Compression::default());
+        let mut archive = Builder::new(encoder);
+
+        archive.append_file(
+            Path::new(&format!("{}.log", file_basename)),
+            &mut File::open(path)?,
+        )?;
+
+        archive.into_inner().unwrap();
+    }
+    if file_mode != FileMode::Append {
+        file.set_len(0)?;
+    }
+    let mut sink = FileSink::new(file, Some(path.to_path_buf()), config);
+
+    // Mark where this run begins if a previous run left a log behind
+    if restarted && let Some(separator) = &config.restart_separator {
+        sink.write_line(&format!("{separator}{}", config.file_record_separator()))?;
+    }
+    start_file_sink(&mut sink, config)?;
+    Ok(sink)
+}
+
+/// Take an exclusive lock on an open log file, waiting up to `timeout`.
+fn lock_file(file: &File, path: &Path, timeout: Duration) -> Result<(), LogError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(()),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Err(TryLockError::WouldBlock) => return Err(LogError::FileLocked(path.to_path_buf())),
+            Err(TryLockError::Error(error)) => return Err(error.into()),
+        }
+    }
+}
+
+/// Start the session of a file sink and share it with the flusher thread.
+fn share_file_sink(mut sink: FileSink, config: &LoggerConfig) -> Result<SharedFile, LogError> {
+    start_file_sink(&mut sink, config)?;
+    share_file(Some(sink), config)
+}
+
+/// Write the init banner and the metadata sidecar of a newly opened file.
+fn start_file_sink(sink: &mut FileSink, config: &LoggerConfig) -> io::Result<()> {
+    if let Some(banner) = render_init_banner(config, config.level) {
+        sink.write_line(&format!("{banner}{}", config.file_record_separator()))?;
+    }
+    sink.write_metadata(None)
+}
+
+/// Share a file sink, open or not yet, with the flusher thread.
+fn share_file(sink: Option<FileSink>, config: &LoggerConfig) -> Result<SharedFile, LogError> {
+    let sink = Arc::new(Mutex::new(sink));
+    if let Some(interval) = config.flush_interval {
+        sink::spawn_flusher(Arc::downgrade(&sink), interval, config.on_error.clone())?;
+    }
+    Ok(sink)
+}
+
+/// Fill in the tokens of the configured init banner, if there is one.
+fn render_init_banner(config: &LoggerConfig, level: LevelFilter) -> Option<String> {
+    let banner = config.init_banner.as_ref()?;
+    let version = config.app_version.as_deref().unwrap_or("unknown");
+    Some(
+        banner
+            .replace("{version}", version)
+            .replace("{level}", &level.to_string())
+            .replace("{time}", &clock::Timestamp::now().to_rfc3339_short()),
+    )
+}
+
+/// Measures the time between the records written by a logger.
+#[derive(Default)]
+struct DeltaClock(Mutex<Option<Instant>>);
+
+impl DeltaClock {
+    /// Get the time since the previous record, or zero for the first one.
+    fn next(&self) -> Duration {
+        let now = Instant::now();
+        let mut last = sink::lock(&self.0);
+        let delta = last.map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        *last = Some(now);
+        delta
+    }
+}
+
+/// Environment variable that caps the level of loggers with `respect_env_level`.
+const MAX_LEVEL_ENV: &str = "FSTDOUT_MAX_LEVEL";
+
+/// The level cap read from [`MAX_LEVEL_ENV`], refreshed while logging.
+///
+/// Reading the environment on every record would be wasteful, so the value is
+/// cached and read again at most once per [`EnvLevel::REFRESH_INTERVAL`].
+struct EnvLevel {
+    /// Origin of `checked_at`
+    started: Instant,
+
+    /// Milliseconds since `started` at which the variable was last read
+    checked_at: AtomicU64,
+
+    /// The cap as `LevelFilter as usize`, or `usize::MAX` for no cap
+    cap: AtomicUsize,
+}
+
+impl EnvLevel {
+    /// How long a value read from the environment is used before reading it again.
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Read the current cap from the environment.
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            checked_at: AtomicU64::new(0),
+            cap: AtomicUsize::new(Self::read()),
+        }
+    }
+
+    /// Read the environment variable, encoded as stored in `cap`.
+    fn read() -> usize {
+        std::env::var(MAX_LEVEL_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse::<LevelFilter>().ok())
+            .map_or(usize::MAX, |level| level as usize)
+    }
+
+    /// Get the current cap, reading the environment again if the cached value is stale.
+    fn get(&self) -> Option<LevelFilter> {
+        let now = self.started.elapsed().as_millis() as u64;
+        let checked_at = self.checked_at.load(Ordering::Relaxed);
+        // Only one thread refreshes the value per interval
+        if now.saturating_sub(checked_at) >= Self::REFRESH_INTERVAL.as_millis() as u64
+            && self
+                .checked_at
+                .compare_exchange(checked_at, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.cap.store(Self::read(), Ordering::Relaxed);
+        }
+        LevelFilter::iter().nth(self.cap.load(Ordering::Relaxed))
+    }
+}
+
+/// The last logged message, used to collapse repeated messages.
+struct Dedup {
+    /// How long after the last logged occurrence repeats are dropped
+    window: Duration,
+
+    /// The last message, `None` before the first record
+    last: Mutex<Option<LastMessage>>,
+}
+
+/// A logged message and the number of times it was repeated since.
+struct LastMessage {
+    /// Level of the message
+    level: Level,
+
+    /// Target of the message
+    target: String,
+
+    /// The message text
+    message: String,
+
+    /// When the message was last logged
+    logged_at: Instant,
+
+    /// Number of dropped repeats
+    repeats: u64,
+}
+
+/// Outcome of comparing a record with the last message.
+enum Repeat {
+    /// The record repeats the last message within the window and is dropped
+    Suppressed,
+
+    /// The record is logged, after a summary of the previous message's
+    /// repeats if there were any
+    Ended(Option<LastMessage>),
+}
+
+impl Dedup {
+    /// Start without a last message.
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Compare a record with the last message and remember it if it is logged.
+    fn observe(&self, record: &Record) -> Repeat {
+        let message = record.args().to_string();
+        let now = Instant::now();
+        let mut last = sink::lock(&self.last);
+        if let Some(last) = last.as_mut()
+            && last.level == record.level()
+            && last.message == message
+            && now.duration_since(last.logged_at) < self.window
+        {
+            last.repeats += 1;
+            return Repeat::Suppressed;
+        }
+
+        let previous = last.replace(LastMessage {
+            level: record.level(),
+            target: record.target().to_string(),
+            message,
+            logged_at: now,
+            repeats: 0,
+        });
+        Repeat::Ended(previous.filter(|previous| previous.repeats > 0))
+    }
+
+    /// Take the summary of the last message's repeats, if there are any and
+    /// the window has passed or `force` is set.
+    ///
+    /// Once the window has passed the message is forgotten, so its next
+    /// occurrence is logged again. Otherwise, later repeats keep being
+    /// dropped and counted from zero.
+    fn take_pending(&self, force: bool) -> Option<LastMessage> {
+        let mut guard = sink::lock(&self.last);
+        let last = guard.as_mut().filter(|last| last.repeats > 0)?;
+        if last.logged_at.elapsed() >= self.window {
+            return guard.take();
+        }
+        if !force {
+            return None;
+        }
+        let summary = LastMessage {
+            level: last.level,
+            target: last.target.clone(),
+            message: last.message.clone(),
+            logged_at: last.logged_at,
+            repeats: last.repeats,
+        };
+        last.repeats = 0;
+        Some(summary)
+    }
+}
+
+/// Write pending repeat summaries of the installed logger once their window passes.
+///
+/// The thread is started when collapsing is first turned on, at install or
+/// later with [`LoggerHandle::set_config`], and runs for the rest of the
+/// process. It wakes up once per window of the current settings, or once a
+/// second while collapsing is turned off.
+fn start_dedup_timer(logger: &'static FStdoutLogger) -> io::Result<()> {
+    if DEDUP_TIMER.swap(true, Ordering::Relaxed) {
+        return Ok(());
+    }
+    let spawned = std::thread::Builder::new()
+        .name("fstdout-logger-dedup".to_string())
+        .spawn(move || {
+            loop {
+                let window = logger
+                    .settings()
+                    .dedup
+                    .as_ref()
+                    .map_or(Duration::from_secs(1), |dedup| dedup.window);
+                std::thread::sleep(window);
+                logger.write_pending_repeats(false);
+            }
+        });
+    if spawned.is_err() {
+        DEDUP_TIMER.store(false, Ordering::Relaxed);
+    }
+    spawned.map(|_| ())
+}
+
+/// Check configuration values that can only be validated at runtime.
+fn validate_config(config: &LoggerConfig) -> Result<(), LogError> {
+    for format in [&config.stdout_time_format, &config.file_time_format]
+        .into_iter()
+        .flatten()
+    {
+        formatter::validate_time_format(format)?;
+    }
+    for template in [&config.stdout_template, &config.file_template]
+        .into_iter()
+        .flatten()
+    {
+        formatter::validate_template(template)?;
+    }
+    #[cfg(not(feature = "chrono"))]
+    if config.timezone == TimeZoneMode::Local {
+        return Err(LogError::InvalidConfig(
+            "TimeZoneMode::Local requires the chrono feature".to_string(),
+        ));
+    }
+    if config.async_file && config.async_file_capacity == 0 {
+        return Err(LogError::InvalidConfig(
+            "async_file_capacity must be at least 1".to_string(),
+        ));
+    }
+    for (path, level, max_level) in &config.file_ranges {
+        if *level < *max_level {
+            return Err(LogError::InvalidLevel(format!(
+                "{} would receive no records, as {max_level} is more verbose than {level}",
+                path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Write a formatted line to the files whose level range includes `level`,
+/// or to the audit file for audit events.
+fn write_files(
+    files: &[FileDestination],
+    audit_file: Option<&SharedFile>,
+    line: &str,
+    level: Level,
+    audit: bool,
+    formatter: &LogFormatter,
+) {
+    let config = formatter.config();
+    let target_files: Vec<(&SharedFile, Option<&LazyFile>)> = match audit_file {
+        Some(audit_file) if audit => vec![(audit_file, None)],
+        _ => files
+            .iter()
+            .filter(|dest| dest.accepts(level))
+            .map(|dest| (&dest.file, dest.lazy.as_deref()))
+            .collect(),
+    };
+
+    for (file, lazy) in target_files {
+        let mut result = Ok(());
+        let mut sink = sink::lock(file);
+
+        // Open a lazily opened file on its first write; after a failure
+        // the file stays closed. A locked file fails right away instead of
+        // stalling the log call for the lock timeout.
+        if sink.is_none()
+            && let Some(lazy) = lazy
+            && lazy.pending.swap(false, Ordering::Relaxed)
+        {
+            match open_file(&lazy.path, config.file_mode, Duration::ZERO, config) {
+                Ok(opened) => *sink = Some(opened),
+                Err(error) => result = Err(error),
+            }
+        }
+
+        let mut deferred = Vec::new();
+        if let Some(file) = sink.as_mut() {
+            // Roll over to a new file if a rotation boundary was crossed
+            let rotated = file.rotate_if_needed(formatter.local_now());
+            let written = file.write_record(line, level);
+            result = rotated.and(written).map_err(LogError::from);
+            deferred = file.take_errors();
+        }
+        drop(sink);
+
+        // Errors never fail the log call, they are only passed to the handler.
+        // The file lock is released at this point, so the handler may log.
+        for error in deferred {
+            report_error(config.on_error.as_ref(), error);
+        }
+        if let Err(error) = result {
+            report_error(config.on_error.as_ref(), error);
+        }
+    }
+}
+
+/// Pass a log file error to the configured handler, if any.
+fn report_error(on_error: Option<&ErrorHandler>, error: impl Into<LogError>) {
+    if let Some(handler) = on_error {
+        handler.report(error.into());
+    }
+}
+
+/// Flush the console writer, stderr and the shared log files.
+///
+/// With a writer thread, the queued lines are written first.
+fn flush_outputs<'a>(
+    console: &SharedWriter,
+    file_worker: Option<&FileWorker>,
+    files: impl IntoIterator<Item = &'a SharedFile>,
+    on_error: Option<&ErrorHandler>,
+) {
+    // Flush console streams
+    let _ = sink::lock(console).flush();
+    let _ = io::stderr().flush();
+
+    // Let the writer thread catch up before flushing what it wrote
+    if let Some(worker) = file_worker {
+        worker.drain();
+    }
+
+    // Flush files if configured
+    for file in files {
+        let mut result = Ok(());
+        if let Some(file) = sink::lock(file).as_mut() {
+            result = file.flush();
+        }
+        if let Err(error) = result {
+            report_error(on_error, error);
+        }
+    }
+}
+
+/// A handle to an installed logger.
+///
+/// The global logger registered with the `log` crate lives for the rest of
+/// the process, so it is never dropped and its buffers are not flushed
+/// automatically on exit. A `LoggerHandle`, returned by
+/// [`FStdoutLogger::init_with_handle`], shares the logger's file and allows
+/// flushing and closing it explicitly.
+///
+/// Dropping a handle flushes too, so keeping one alive until the end of
+/// `main` is enough to get every buffered line written, even on an early
+/// return. Records logged while the handle is being dropped, e.g. by other
+/// threads, may still be left in the buffer.
 ///
 /// # Example
 ///
 /// ```rust
-/// use fstdout_logger::init_logger;
-/// use log::info;
+/// use fstdout_logger::{FStdoutLogger, LoggerConfig};
+/// use log::{LevelFilter, info};
 ///
-/// init_logger(Some("app.log")).expect("Failed to initialize logger");
-/// info!("Logger initialized with default settings");
+/// let config = LoggerConfig::builder().buffered(true).build();
+/// let handle = FStdoutLogger::with_config(Some("service.log"), config)
+///     .expect("Failed to create logger")
+///     .init_with_handle(LevelFilter::Info)
+///     .expect("Failed to initialize logger");
+///
+/// info!("Working...");
 ///
+/// // Make sure buffered lines reach the file
+/// handle.flush();
 /// ```
-pub fn init_logger<P: AsRef<Path>>(file_path: Option<P>) -> Result<(), LogError> {
-    FStdoutLogger::new(file_path)?.init()
+#[derive(Clone)]
+pub struct LoggerHandle {
+    /// The console writer shared with the installed logger
+    console: SharedWriter,
+
+    /// The log files shared with the installed logger
+    files: Vec<SharedFile>,
+
+    /// The lazily opened log files, so shutdown can keep them closed
+    lazy_files: Vec<Arc<LazyFile>>,
+
+    /// The audit file shared with the installed logger
+    audit_file: Option<SharedFile>,
+
+    /// The recent lines shared with the installed logger
+    recent: Option<SharedRingBuffer>,
+
+    /// The settings shared with the installed logger
+    settings: SharedSettings,
+
+    /// Canonical path of the installed logger's main log file
+    log_path: Option<PathBuf>,
+
+    /// The writer thread shared with the installed logger
+    file_worker: Option<FileWorker>,
 }
 
-/// Initialize a logger with a specific log level.
+impl LoggerHandle {
+    /// Flush console output and any buffered lines in the log files.
+    ///
+    /// A pending summary of repeated messages is written first.
+    pub fn flush(&self) {
+        if let Some(logger) = self.installed_logger() {
+            logger.write_pending_repeats(true);
+        }
+        flush_outputs(
+            &self.console,
+            self.file_worker.as_ref(),
+            self.files.iter().chain(&self.audit_file),
+            current_settings(&self.settings)
+                .formatter
+                .config()
+                .on_error
+                .as_ref(),
+        );
+    }
+
+    /// Get the path of the main log file, e.g. to tell the user where logs go.
+    ///
+    /// The path is canonicalized when the logger is created, or kept as
+    /// given if that fails. It is `None` if the logger was created without a
+    /// log file path, including loggers given an already open file.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        self.log_path.clone()
+    }
+
+    /// Get an `io::Write` adapter that logs each written line at `level`.
+    ///
+    /// The lines go through the logger this handle belongs to like any
+    /// other record; see [`LogWriter`] for how partial lines are handled.
+    pub fn writer(&self, level: Level) -> LogWriter {
+        match self.installed_logger() {
+            Some(logger) => LogWriter::with_logger(level, logger),
+            None => LogWriter::new(level),
+        }
+    }
+
+    /// Get the logger this handle belongs to, if it is the installed one.
+    fn installed_logger(&self) -> Option<&'static FStdoutLogger> {
+        INSTALLED
+            .get()
+            .copied()
+            .filter(|logger| Arc::ptr_eq(&logger.settings, &self.settings))
+    }
+
+    /// Get the most recent lines, oldest first.
+    ///
+    /// This is empty unless the logger was created with a
+    /// [`ring_buffer_capacity`](LoggerConfigBuilder::ring_buffer_capacity).
+    pub fn recent_logs(&self) -> Vec<String> {
+        self.recent
+            .as_ref()
+            .map(|recent| sink::lock(recent).lines())
+            .unwrap_or_default()
+    }
+
+    /// Replace the installed logger's configuration at runtime.
+    ///
+    /// The new configuration applies from the next record on, e.g. to turn
+    /// on debug logging or file information in response to a signal. The
+    /// level, per-module levels, quiet windows, formatting and colors all
+    /// follow `config`, and the global `log::max_level` is updated to match.
+    /// Open files pick up the new `record_separator` and `line_ending`, and
+    /// their metadata sidecars record the new configuration. A pending
+    /// `(repeated N times)` summary is written before the switch, then
+    /// repeated-message tracking and elapsed timestamps start over; sequence
+    /// numbers and the time since the previous record carry on.
+    ///
+    /// The destinations stay as they were when the logger was created: the
+    /// console writer, the log, extra and audit files, the ring buffer, and
+    /// file handling options like `file_mode`, `rotation`, `retention`,
+    /// `buffered`, `flush_interval`, `async_file` and `async_file_capacity`
+    /// are not affected.
+    ///
+    /// Supporting this costs every log call a brief, uncontended read lock
+    /// on the settings; `cargo bench --bench settings` measures it.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration to use from now on.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the configuration was replaced, or an error if a custom
+    /// time format or template is invalid or the thread writing repeat
+    /// summaries couldn't be started, in which case the previous
+    /// configuration stays in place.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fstdout_logger::{FStdoutLogger, LoggerConfig};
+    /// use log::LevelFilter;
+    ///
+    /// let handle = FStdoutLogger::with_config(None::<&str>, LoggerConfig::default())
+    ///     .expect("Failed to create logger")
+    ///     .init_with_handle(LevelFilter::Info)
+    ///     .expect("Failed to initialize logger");
+    ///
+    /// // Later, e.g. on SIGUSR1
+    /// let debug = LoggerConfig::builder()
+    ///     .level(LevelFilter::Debug)
+    ///     .show_file_info(true)
+    ///     .build();
+    /// handle.set_config(debug).expect("Invalid configuration");
+    /// ```
+    pub fn set_config(&self, config: LoggerConfig) -> Result<(), LogError> {
+        validate_config(&config)?;
+        for file in self.files.iter().chain(&self.audit_file) {
+            let result = match sink::lock(file).as_mut() {
+                Some(sink) => sink.reconfigure(&config),
+                None => Ok(()),
+            };
+            if let Err(error) = result {
+                report_error(config.on_error.as_ref(), error);
+            }
+        }
+        // Don't lose the count of repeats dropped under the old settings,
+        // and write summaries once their window closes if collapsing is new
+        if let Some(logger) = self.installed_logger() {
+            logger.write_pending_repeats(true);
+            if config.dedup_window.is_some() {
+                start_dedup_timer(logger)?;
+            }
+        }
+        let settings = Settings::new(config.level, config);
+        let max_level = settings.max_level();
+        replace_settings(&self.settings, settings);
+        log::set_max_level(max_level);
+        Ok(())
+    }
+
+    /// Flush all output and close the log and audit files.
+    ///
+    /// If enabled, the metadata sidecars are completed with the end time and
+    /// record counts. After shutdown, records are still printed to stdout but
+    /// no longer written to the files.
+    pub fn shutdown(self) {
+        self.flush();
+        let settings = current_settings(&self.settings);
+        // Files that were never written to must not be opened afterwards
+        for lazy in &self.lazy_files {
+            lazy.pending.store(false, Ordering::Relaxed);
+        }
+        for file in self.files.iter().chain(&self.audit_file) {
+            let closed = sink::lock(file).take();
+            if let Some(sink) = closed
+                && let Err(error) = sink.close()
+            {
+                report_error(settings.formatter.config().on_error.as_ref(), error);
+            }
+        }
+    }
+}
+
+/// Flushes buffered output when the handle goes out of scope.
+///
+/// Like [`LoggerHandle::flush`], this recovers locks poisoned by a panic in
+/// another thread and passes write errors to the error handler, so it never
+/// panics itself.
+impl Drop for LoggerHandle {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Switch the installed logger's file between buffered and direct writes.
 ///
-/// This uses the default configuration but overrides the log level.
+/// This is useful for applications that move between interactive and batch
+/// modes: buffering can be turned on for throughput and off again when every
+/// line should reach the file immediately. Turning buffering off flushes any
+/// pending lines first.
 ///
 /// # Arguments
 ///
-/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
-/// * `level` - The minimum log level to display.
+/// * `buffered` - Whether file writes should be buffered from now on.
 ///
 /// # Returns
 ///
-/// `Ok(())` if initialization succeeded, or an error if it failed.
+/// `Ok(())` if the mode was changed, or an error if no logger is installed
+/// or flushing failed.
 ///
 /// # Example
 ///
 /// ```rust
-/// use fstdout_logger::init_logger_with_level;
-/// use log::LevelFilter;
+/// use fstdout_logger::{init_logger, set_buffered};
 ///
-/// init_logger_with_level(Some("debug.log"), LevelFilter::Debug)
-///     .expect("Failed to initialize logger");
+/// init_logger(Some("batch.log")).expect("Failed to initialize logger");
+///
+/// // Entering a batch job: favour throughput
+/// set_buffered(true).expect("Failed to enable buffering");
 /// ```
-pub fn init_logger_with_level<P: AsRef<Path>>(
-    file_path: Option<P>,
-    level: LevelFilter,
-) -> Result<(), LogError> {
-    FStdoutLogger::new(file_path)?.init_with_level(level)
+pub fn set_buffered(buffered: bool) -> Result<(), LogError> {
+    INSTALLED
+        .get()
+        .ok_or(LogError::NotInitialized)?
+        .set_buffered(buffered)
 }
 
-/// Initialize a logger with custom configuration.
-///
-/// This gives full control over all configuration options.
-///
-/// # Arguments
+/// Get the configuration of the installed logger.
 ///
-/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
-/// * `config` - Configuration options for the logger.
+/// This lets code that didn't set up the logger check how it behaves, e.g.
+/// whether file info or colors are shown, without passing the configuration
+/// around. The returned copy reflects the level the logger was installed
+/// with (such as the one passed to
+/// [`init_with_level`](FStdoutLogger::init_with_level)) and any later
+/// [`LoggerHandle::set_config`].
 ///
 /// # Returns
 ///
-/// `Ok(())` if initialization succeeded, or an error if it failed.
+/// A copy of the active configuration, or `None` if no logger is installed.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::{active_config, init_stdout_logger, LoggerConfig};
+///
+/// init_stdout_logger(LoggerConfig::default()).expect("Failed to initialize logger");
+///
+/// if let Some(config) = active_config() {
+///     println!("logging at {} and above", config.level);
+/// }
+/// ```
+pub fn active_config() -> Option<LoggerConfig> {
+    let settings = INSTALLED.get()?.settings();
+    let mut config = settings.formatter.config().clone();
+    config.level = settings.level;
+    Some(config)
+}
+
+//
+// Helper functions for easily initializing the logger
+//
+
+/// Initialize a logger with default configuration.
+///
+/// This uses [`LoggerConfig::default()`] which sets:
+/// - `Info` as the minimum log level
+/// - File information shown in logs
+/// - No date in stdout output (only time)
+/// - Colors enabled for terminal output
+///
+/// # Arguments
+///
+/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
+///
+/// # Returns
+///
+/// `Ok(())` if initialization succeeded, or an error if it failed.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::init_logger;
+/// use log::info;
+///
+/// init_logger(Some("app.log")).expect("Failed to initialize logger");
+/// info!("Logger initialized with default settings");
+///
+/// ```
+pub fn init_logger<P: AsRef<Path>>(file_path: Option<P>) -> Result<(), LogError> {
+    FStdoutLogger::new(file_path)?.init()
+}
+
+/// Initialize a logger with a specific log level.
+///
+/// This uses the default configuration but overrides the log level.
+///
+/// # Arguments
+///
+/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
+/// * `level` - The minimum log level to display.
+///
+/// # Returns
+///
+/// `Ok(())` if initialization succeeded, or an error if it failed.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::init_logger_with_level;
+/// use log::LevelFilter;
+///
+/// init_logger_with_level(Some("debug.log"), LevelFilter::Debug)
+///     .expect("Failed to initialize logger");
+/// ```
+pub fn init_logger_with_level<P: AsRef<Path>>(
+    file_path: Option<P>,
+    level: LevelFilter,
+) -> Result<(), LogError> {
+    FStdoutLogger::new(file_path)?.init_with_level(level)
+}
+
+/// Initialize a logger unless one is already installed.
+///
+/// This works like [`init_logger`], but succeeds without doing anything if
+/// a logger is already installed, so it is safe to call from several entry
+/// points, e.g. every test in a test binary or every plugin of a host
+/// application. Use [`init_logger`] to get [`LogError::Logger`] instead.
+///
+/// If this crate's logger is installed, the log file is not touched. A
+/// logger installed by another crate is only noticed after the log file was
+/// opened, which may archive or truncate it depending on the file mode.
+///
+/// # Arguments
+///
+/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
+///
+/// # Returns
+///
+/// `Ok(())` if the logger was initialized or one was already installed, or
+/// an error if the log file couldn't be opened.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::try_init;
+///
+/// try_init(None::<&str>).expect("Failed to initialize logger");
+/// // A second call is fine
+/// try_init(None::<&str>).expect("Failed to initialize logger");
+/// ```
+pub fn try_init<P: AsRef<Path>>(file_path: Option<P>) -> Result<(), LogError> {
+    if INSTALLED.get().is_some() {
+        return Ok(());
+    }
+    ignore_installed(FStdoutLogger::new(file_path)?.init())
+}
+
+/// Initialize a logger with a specific log level unless one is already installed.
+///
+/// This works like [`init_logger_with_level`], but succeeds without doing
+/// anything if a logger is already installed, see [`try_init`]. The level
+/// of an installed logger is left as it is.
+///
+/// # Arguments
+///
+/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
+/// * `level` - The minimum log level to display.
+///
+/// # Returns
+///
+/// `Ok(())` if the logger was initialized or one was already installed, or
+/// an error if the log file couldn't be opened.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::try_init_with_level;
+/// use log::LevelFilter;
+///
+/// try_init_with_level(None::<&str>, LevelFilter::Debug).expect("Failed to initialize logger");
+/// ```
+pub fn try_init_with_level<P: AsRef<Path>>(
+    file_path: Option<P>,
+    level: LevelFilter,
+) -> Result<(), LogError> {
+    if INSTALLED.get().is_some() {
+        return Ok(());
+    }
+    ignore_installed(FStdoutLogger::new(file_path)?.init_with_level(level))
+}
+
+/// Treat an already installed logger as success.
+fn ignore_installed(result: Result<(), LogError>) -> Result<(), LogError> {
+    match result {
+        Err(LogError::Logger) => Ok(()),
+        result => result,
+    }
+}
+
+/// Initialize a logger with custom configuration.
+///
+/// This gives full control over all configuration options.
+///
+/// # Arguments
+///
+/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
+/// * `config` - Configuration options for the logger.
+///
+/// # Returns
+///
+/// `Ok(())` if initialization succeeded, or an error if it failed.
 ///
 /// # Example
 ///
@@ -368,6 +2190,66 @@ pub fn init_logger_with_config<P: AsRef<Path>>(
     FStdoutLogger::with_config(file_path, config)?.init_with_level(level)
 }
 
+/// Initialize a logger that writes to an already open log file.
+///
+/// See [`FStdoutLogger::with_file`] for what applies to such files.
+///
+/// # Arguments
+///
+/// * `file` - The open log file, writable.
+/// * `config` - Configuration options for the logger.
+///
+/// # Returns
+///
+/// `Ok(())` if initialization succeeded, or an error if it failed.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::{init_logger_with_file, LoggerConfig};
+/// use std::fs::File;
+///
+/// let file = File::create("inherited.log").expect("Failed to create file");
+/// init_logger_with_file(file, LoggerConfig::default())
+///     .expect("Failed to initialize logger");
+/// # let _ = std::fs::remove_file("inherited.log");
+/// ```
+pub fn init_logger_with_file(file: File, config: LoggerConfig) -> Result<(), LogError> {
+    let level = config.level;
+    FStdoutLogger::with_file(file, config)?.init_with_level(level)
+}
+
+/// Initialize a logger configured from the `RUST_LOG` environment variable.
+///
+/// The variable is parsed with [`LoggerConfig::from_env_filter`], so it
+/// accepts the same directives as `env_logger`, e.g.
+/// `RUST_LOG=info,myapp=debug,hyper=warn`. If the variable is not set, the
+/// default configuration is used.
+///
+/// # Arguments
+///
+/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
+///
+/// # Returns
+///
+/// `Ok(())` if initialization succeeded, or an error if `RUST_LOG` is
+/// malformed or initialization failed.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::init_logger_from_env;
+///
+/// init_logger_from_env(Some("app.log")).expect("Failed to initialize logger");
+/// ```
+pub fn init_logger_from_env<P: AsRef<Path>>(file_path: Option<P>) -> Result<(), LogError> {
+    let config = match std::env::var("RUST_LOG") {
+        Ok(filter) => LoggerConfig::from_env_filter(&filter)?,
+        Err(_) => LoggerConfig::default(),
+    };
+    init_logger_with_config(file_path, config)
+}
+
 /// Initialize a production-ready logger (no file info, concise format).
 ///
 /// This uses [`LoggerConfig::production()`] which is optimized for
@@ -426,6 +2308,35 @@ pub fn init_development_logger<P: AsRef<Path>>(file_path: Option<P>) -> Result<(
     init_logger_with_config(file_path, LoggerConfig::development())
 }
 
+/// Initialize a compact logger (minimal lines, no colors).
+///
+/// This uses [`LoggerConfig::compact()`] which is meant for output that is
+/// read outside a terminal, e.g. cron jobs whose output is mailed:
+/// - `Info` as the minimum log level (no debug messages)
+/// - No file information shown in logs
+/// - No date in stdout output (only time)
+/// - Three-letter level labels (`INF`, `WRN`, ...)
+/// - Colors disabled
+///
+/// # Arguments
+///
+/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
+///
+/// # Returns
+///
+/// `Ok(())` if initialization succeeded, or an error if it failed.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::init_compact_logger;
+///
+/// init_compact_logger(None::<&str>).expect("Failed to initialize compact logger");
+/// ```
+pub fn init_compact_logger<P: AsRef<Path>>(file_path: Option<P>) -> Result<(), LogError> {
+    init_logger_with_config(file_path, LoggerConfig::compact())
+}
+
 /// Initialize a logger that only writes to stdout (not to a file).
 ///
 /// # Arguments
@@ -483,9 +2394,28 @@ pub fn init_simple_stdout_logger(level: LevelFilter) -> Result<(), LogError> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use log::{debug, error, info, trace, warn};
-    use std::fs;
-    use std::io::Read;
+    use std::time::SystemTime;
+
+    /// In-memory writer whose contents can be inspected after logging.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
 
     #[test]
     fn test_stdout_logger() {
@@ -501,9 +2431,25 @@ mod tests {
 
     #[test]
     fn test_file_logger() {
+        use log::{debug, error, info, trace, warn};
+        use std::io::Read;
+
+        // Only one global logger can be installed per process, and
+        // test_stdout_logger installs one too, so this test runs in a child
+        const CHILD_ENV: &str = "FSTDOUT_FILE_LOGGER_CHILD";
+        if std::env::var(CHILD_ENV).is_err() {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "tests::test_file_logger"])
+                .env(CHILD_ENV, "1")
+                .status()
+                .expect("Failed to run child process");
+            assert!(status.success());
+            return;
+        }
+
         let test_file = "test_log.txt";
         // Clean up any existing test file
-        let _ = fs::remove_file(test_file);
+        let _ = std::fs::remove_file(test_file);
 
         // Initialize logger
         let config = LoggerConfig::builder()
@@ -536,6 +2482,1329 @@ mod tests {
         assert!(contents.contains("error message"));
 
         // Clean up
-        let _ = fs::remove_file(test_file);
+        let _ = std::fs::remove_file(test_file);
+    }
+
+    #[test]
+    fn test_custom_writer() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .build();
+        let logger = FStdoutLogger::with_writer(buffer.clone(), config).unwrap();
+
+        logger.log(
+            &Record::builder()
+                .args(format_args!("captured in memory"))
+                .level(Level::Info)
+                .build(),
+        );
+
+        let output = buffer.contents();
+        assert!(output.ends_with(" INFO] captured in memory\n"));
+        assert_eq!(output.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_log_record_without_registry() {
+        let verbose = SharedBuffer::default();
+        let quiet = SharedBuffer::default();
+        let loggers = [
+            (verbose.clone(), LevelFilter::Debug, true),
+            (quiet.clone(), LevelFilter::Warn, false),
+        ]
+        .map(|(buffer, level, show_file_info)| {
+            let config = LoggerConfig::builder()
+                .level(level)
+                .show_file_info(show_file_info)
+                .use_colors(false)
+                .build();
+            FStdoutLogger::with_writer(buffer, config).unwrap()
+        });
+
+        for logger in &loggers {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("scoped record"))
+                    .level(Level::Debug)
+                    .file(Some("src/scoped.rs"))
+                    .line(Some(3))
+                    .build(),
+            );
+        }
+
+        assert!(
+            verbose
+                .contents()
+                .ends_with(" DEBUG src/scoped.rs:3] scoped record\n")
+        );
+        assert_eq!(quiet.contents(), "");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_processor_receives_events() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let config = LoggerConfig::builder()
+            .level(LevelFilter::Info)
+            .timezone(TimeZoneMode::Utc)
+            .build();
+        let logger = FStdoutLogger::with_processor(sender, config).unwrap();
+
+        for level in [Level::Debug, Level::Warn] {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("disk {}% full", 93))
+                    .level(level)
+                    .target("myapp::storage")
+                    .file(Some("src/storage.rs"))
+                    .line(Some(42))
+                    .build(),
+            );
+        }
+        drop(logger);
+
+        let events: Vec<LogEvent> = receiver.iter().collect();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.level, Level::Warn);
+        assert_eq!(event.target, "myapp::storage");
+        assert_eq!(event.file.as_deref(), Some("src/storage.rs"));
+        assert_eq!(event.line, Some(42));
+        assert_eq!(event.message, "disk 93% full");
+        assert!(
+            chrono::Utc::now().signed_duration_since(event.datetime())
+                < chrono::Duration::seconds(5)
+        );
+        assert!(event.timestamp.elapsed().unwrap() < Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_processor_events_redacted() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let config = LoggerConfig::builder()
+            .redact(regex::Regex::new(r"token=\w+").unwrap())
+            .build();
+        let logger = FStdoutLogger::with_processor(sender, config).unwrap();
+
+        logger.log_record(
+            &Record::builder()
+                .args(format_args!("request token=abc123 user=bob"))
+                .level(Level::Info)
+                .build(),
+        );
+        drop(logger);
+
+        let events: Vec<LogEvent> = receiver.iter().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message, "request ***REDACTED*** user=bob");
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[test]
+    fn test_local_time_zone_requires_chrono() {
+        assert_eq!(LoggerConfig::default().timezone, TimeZoneMode::Utc);
+        let config = LoggerConfig::builder()
+            .timezone(TimeZoneMode::Local)
+            .build();
+        assert!(matches!(
+            FStdoutLogger::with_writer(Vec::new(), config),
+            Err(LogError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_dedup_window() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .dedup_window(Some(Duration::from_secs(60)))
+            .build();
+        let logger = FStdoutLogger::with_writer(buffer.clone(), config).unwrap();
+        let log = |level, message: &str| {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{message}"))
+                    .level(level)
+                    .build(),
+            );
+        };
+
+        for _ in 0..1000 {
+            log(Level::Warn, "dependency misbehaving");
+        }
+        // Same text at another level is a different message
+        log(Level::Error, "dependency misbehaving");
+        log(Level::Info, "recovered");
+
+        let output = buffer.contents();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].ends_with(" WARN] dependency misbehaving"));
+        assert!(lines[1].ends_with(" WARN] dependency misbehaving (repeated 999 times)"));
+        assert!(lines[2].ends_with(" ERROR] dependency misbehaving"));
+        assert!(lines[3].ends_with(" INFO] recovered"));
+    }
+
+    #[test]
+    fn test_dedup_summary_on_flush() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .dedup_window(Some(Duration::from_secs(60)))
+            .build();
+        let logger = FStdoutLogger::with_writer(buffer.clone(), config).unwrap();
+        let log = || {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("disk almost full"))
+                    .level(Level::Warn)
+                    .build(),
+            );
+        };
+
+        for _ in 0..5 {
+            log();
+        }
+        logger.flush();
+        // Flushing without new repeats writes nothing more
+        logger.flush();
+        log();
+        log();
+        drop(logger);
+
+        let output = buffer.contents();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3, "{output}");
+        assert!(lines[0].ends_with(" WARN] disk almost full"));
+        assert!(lines[1].ends_with(" WARN] disk almost full (repeated 4 times)"));
+        assert!(lines[2].ends_with(" WARN] disk almost full (repeated 2 times)"));
+    }
+
+    #[test]
+    fn test_dedup_summary_after_window() {
+        let dedup = Dedup::new(Duration::from_millis(20));
+        let record = Record::builder()
+            .args(format_args!("retrying"))
+            .level(Level::Info)
+            .build();
+        assert!(matches!(dedup.observe(&record), Repeat::Ended(None)));
+        assert!(matches!(dedup.observe(&record), Repeat::Suppressed));
+        assert!(dedup.take_pending(false).is_none());
+
+        std::thread::sleep(Duration::from_millis(30));
+        let summary = dedup.take_pending(false).unwrap();
+        assert_eq!(summary.repeats, 1);
+        // The message is forgotten, so it is logged again
+        assert!(matches!(dedup.observe(&record), Repeat::Ended(None)));
+    }
+
+    /// Clock that only moves when the test advances it.
+    #[derive(Clone)]
+    struct ManualClock(Arc<Mutex<SystemTime>>);
+
+    impl clock::Clock for ManualClock {
+        fn now(&self) -> SystemTime {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_dedup_across_rotation() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let dir = std::env::temp_dir().join("fstdout_logger_dedup_rotation");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .rotation(RotationPolicy::Daily)
+            .dedup_window(Some(Duration::from_secs(60)))
+            .build();
+        let logger = FStdoutLogger::with_config(Some(&path), config.clone()).unwrap();
+        let clock = ManualClock(Arc::new(Mutex::new(SystemTime::now())));
+        replace_settings(
+            &logger.settings,
+            Settings {
+                level: LevelFilter::Trace,
+                env_level: None,
+                dedup: config.dedup_window.map(Dedup::new),
+                formatter: LogFormatter::with_clock(config, clock.clone()),
+            },
+        );
+        let log = |message: &str| {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{message}"))
+                    .level(Level::Warn)
+                    .build(),
+            );
+        };
+
+        for _ in 0..3 {
+            log("retrying");
+        }
+        // The next day's repeats roll the file over while they are collapsed
+        *clock.0.lock().unwrap() += Duration::from_secs(24 * 60 * 60);
+        log("retrying");
+        log("retrying");
+        log("connected");
+        logger.flush();
+
+        let rotated: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|entry| *entry != path)
+            .collect();
+        assert_eq!(rotated.len(), 1, "{rotated:?}");
+        let old = std::fs::read_to_string(&rotated[0]).unwrap();
+        let old_lines: Vec<&str> = old.lines().collect();
+        assert_eq!(old_lines.len(), 1, "{old}");
+        assert!(old_lines[0].ends_with("] retrying"));
+
+        // One summary counts the repeats from both sides of the boundary
+        let active = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = active.lines().collect();
+        assert_eq!(lines.len(), 2, "{active}");
+        assert!(lines[0].ends_with("] retrying (repeated 4 times)"));
+        assert!(lines[1].ends_with("] connected"));
+
+        drop(logger);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_module_levels() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .level(LevelFilter::Info)
+            .module_level("hyper", LevelFilter::Warn)
+            .module_level("hyper::server", LevelFilter::Trace)
+            .show_file_info(false)
+            .use_colors(false)
+            .build();
+        let logger = FStdoutLogger::with_writer(buffer.clone(), config).unwrap();
+
+        for target in [
+            "hyper::client",
+            "myapp::core",
+            "hyper::server::conn",
+            "hyperlocal",
+        ] {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("from {target}"))
+                    .level(Level::Info)
+                    .target(target)
+                    .build(),
+            );
+        }
+
+        let output = buffer.contents();
+        assert!(!output.contains("from hyper::client"));
+        assert!(output.contains("from myapp::core"));
+        // The longest matching prefix wins
+        assert!(output.contains("from hyper::server::conn"));
+        // Prefixes only match whole path segments
+        assert!(output.contains("from hyperlocal"));
+    }
+
+    #[test]
+    fn test_with_file() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let path = std::env::temp_dir().join("fstdout_logger_with_file.log");
+        std::fs::write(&path, "opened by supervisor\n").unwrap();
+        let file = OpenOptions::new().append(true).open(&path).unwrap();
+
+        let config = LoggerConfig::builder()
+            .rotation(RotationPolicy::Hourly)
+            .build();
+        let logger = FStdoutLogger::with_file(file, config).unwrap();
+        logger.log(
+            &Record::builder()
+                .args(format_args!("handed over"))
+                .level(Level::Info)
+                .build(),
+        );
+
+        // The file is written where it stands, without any path-based handling
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "opened by supervisor");
+        assert!(lines[1].ends_with("] handed over"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_locking() {
+        let path = std::env::temp_dir().join("fstdout_logger_file_locking.log");
+        std::fs::write(&path, "held by another instance\n").unwrap();
+        let config = LoggerConfig::builder()
+            .file_mode(FileMode::Truncate)
+            .file_locking(true)
+            .file_lock_timeout(Duration::from_millis(50))
+            .build();
+
+        // Another open file description holds the lock
+        let other = File::open(&path).unwrap();
+        other.lock().unwrap();
+        let result = FStdoutLogger::with_config(Some(&path), config.clone());
+        assert!(matches!(result, Err(LogError::FileLocked(locked)) if locked == path));
+        // The file was not truncated while locked
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "held by another instance\n"
+        );
+
+        // A lazily opened file doesn't wait for the lock in the log call
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let errors = Arc::clone(&errors);
+            ErrorHandler::new(move |error| errors.lock().unwrap().push(error.to_string()))
+        };
+        let lazy_config = LoggerConfig {
+            lazy_open: true,
+            file_lock_timeout: Duration::from_secs(60),
+            on_error: Some(handler),
+            ..config.clone()
+        };
+        let logger = FStdoutLogger::with_config(Some(&path), lazy_config).unwrap();
+        let start = Instant::now();
+        logger.log_record(
+            &Record::builder()
+                .args(format_args!("not written"))
+                .level(Level::Info)
+                .build(),
+        );
+        assert!(start.elapsed() < Duration::from_secs(30));
+        assert_eq!(
+            *errors.lock().unwrap(),
+            [LogError::FileLocked(path.clone()).to_string()]
+        );
+        drop(logger);
+
+        other.unlock().unwrap();
+        let logger = FStdoutLogger::with_config(Some(&path), config).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        // The logger holds the lock now
+        assert!(matches!(other.try_lock(), Err(TryLockError::WouldBlock)));
+
+        drop(logger);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_init_banner() {
+        let dir = std::env::temp_dir().join("fstdout_logger_init_banner");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let config = LoggerConfig::builder()
+            .level(LevelFilter::Debug)
+            .with_banner("started level={level} at {time} version={version}")
+            .app_version("1.2.3")
+            .build();
+        let logger = FStdoutLogger::with_config(Some(&path), config).unwrap();
+        logger.log_record(
+            &Record::builder()
+                .args(format_args!("first record"))
+                .level(Level::Info)
+                .build(),
+        );
+        logger.flush();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("started level=DEBUG at "));
+        assert!(lines[0].ends_with(" version=1.2.3"));
+        assert!(!lines[0].contains('{'));
+        assert!(lines[1].ends_with("first record"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_restart_separator() {
+        let dir = std::env::temp_dir().join("fstdout_logger_restart_separator");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let config = LoggerConfig::builder()
+            .file_mode(FileMode::Append)
+            .restart_separator(Some("===== RESTART =====".to_string()))
+            .build();
+
+        // A fresh file gets no separator
+        FStdoutLogger::with_config(Some(&path), config.clone()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+        // Appending to a file left behind by a previous run does
+        std::fs::write(&path, "previous run\n").unwrap();
+        FStdoutLogger::with_config(Some(&path), config.clone()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "previous run\n===== RESTART =====\n"
+        );
+
+        // A truncated file starts empty, so there is no run to separate
+        let config = LoggerConfig {
+            file_mode: FileMode::Truncate,
+            ..config
+        };
+        FStdoutLogger::with_config(Some(&path), config).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_flush_interval() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let dir = std::env::temp_dir().join("fstdout_logger_flush_interval");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let config = LoggerConfig::builder()
+            .buffered(true)
+            .flush_interval(Some(std::time::Duration::from_millis(10)))
+            .build();
+        let logger = FStdoutLogger::with_config(Some(&path), config).unwrap();
+
+        logger.log(
+            &Record::builder()
+                .args(format_args!("flushed in the background"))
+                .level(Level::Info)
+                .build(),
+        );
+
+        // The background thread writes the buffered line out without an explicit flush
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while std::fs::read_to_string(&path).unwrap().is_empty() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "buffer was never flushed"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("flushed in the background")
+        );
+
+        drop(logger);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_quiet_window_level() {
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let config = LoggerConfig::builder()
+            .level(LevelFilter::Debug)
+            .quiet_window(at(2, 0), at(3, 0))
+            .quiet_level(LevelFilter::Warn)
+            .build();
+        let logger = FStdoutLogger::with_writer(io::sink(), config).unwrap();
+
+        // Inside the window, logs are capped at the quiet level
+        assert_eq!(
+            logger.settings().level_for("myapp", Some(at(2, 30))),
+            LevelFilter::Warn
+        );
+        // Outside it, the normal level applies
+        assert_eq!(
+            logger.settings().level_for("myapp", Some(at(4, 0))),
+            LevelFilter::Debug
+        );
+    }
+
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    #[test]
+    fn test_browser_console() {
+        log::set_max_level(LevelFilter::Trace);
+
+        // The file path is ignored in the browser
+        let logger =
+            FStdoutLogger::with_config(Some("browser.log"), LoggerConfig::default()).unwrap();
+        assert!(logger.files.is_empty());
+
+        for level in [Level::Error, Level::Warn, Level::Info] {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("browser console"))
+                    .level(level)
+                    .build(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_file_modes() {
+        let dir = std::env::temp_dir().join("fstdout_logger_file_modes");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let open = |mode| {
+            let config = LoggerConfig::builder().file_mode(mode).build();
+            FStdoutLogger::with_config(Some(&path), config).unwrap()
+        };
+        let write = |logger: &FStdoutLogger, message: &str| {
+            let file = &logger.files[0].file;
+            let mut file = file.lock().unwrap();
+            file.as_mut().unwrap().write_line(message).unwrap();
+        };
+
+        let logger = open(FileMode::Append);
+        write(&logger, "first run\n");
+        drop(logger);
+
+        // Appending keeps the previous run
+        let logger = open(FileMode::Append);
+        write(&logger, "second run\n");
+        drop(logger);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "first run\nsecond run\n"
+        );
+
+        // Truncating discards it
+        let logger = open(FileMode::Truncate);
+        write(&logger, "third run\n");
+        drop(logger);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "third run\n");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_channel_sink() {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(2);
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let dropped = Arc::clone(&dropped);
+            ErrorHandler::new(move |error| {
+                if let LogError::ChannelDropped(count) = error {
+                    dropped.lock().unwrap().push(*count);
+                }
+            })
+        };
+        let config = LoggerConfig::builder()
+            .use_colors(false)
+            .show_file_info(false)
+            .stdout_enabled(false)
+            .channel_sink(Some(sender))
+            .on_error(handler)
+            .build();
+        let output = SharedBuffer::default();
+        let logger = FStdoutLogger::with_writer(output.clone(), config).unwrap();
+
+        // The channel holds two lines, the others are dropped without blocking
+        for n in 1..=4 {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("line {n}"))
+                    .level(Level::Info)
+                    .build(),
+            );
+        }
+
+        let lines: Vec<String> = receiver.try_iter().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" INFO] line 1"));
+        assert!(lines[1].ends_with(" INFO] line 2"));
+        assert_eq!(*dropped.lock().unwrap(), [1, 2]);
+        // Nothing is printed with stdout disabled
+        assert!(output.contents().is_empty());
+    }
+
+    #[test]
+    fn test_observer() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let slot: Arc<OnceLock<FStdoutLogger>> = Arc::new(OnceLock::new());
+        let observer = {
+            let observed = Arc::clone(&observed);
+            let slot = Arc::clone(&slot);
+            RecordObserver::new(move |record| {
+                observed.lock().unwrap().push(record.level());
+
+                // Logging from the observer is written but not observed
+                if let Some(logger) = slot.get() {
+                    logger.log(
+                        &Record::builder()
+                            .args(format_args!("from the observer"))
+                            .level(Level::Warn)
+                            .build(),
+                    );
+                }
+            })
+        };
+        let output = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .level(LevelFilter::Info)
+            .use_colors(false)
+            .observer(observer)
+            .build();
+        let _ = slot.set(FStdoutLogger::with_writer(output.clone(), config).unwrap());
+        let logger = slot.get().unwrap();
+
+        for level in [Level::Error, Level::Info, Level::Debug] {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{level} record"))
+                    .level(level)
+                    .build(),
+            );
+        }
+
+        // The filtered out debug record is not observed
+        assert_eq!(*observed.lock().unwrap(), [Level::Error, Level::Info]);
+        let contents = output.contents();
+        assert_eq!(contents.lines().count(), 4);
+        assert_eq!(contents.matches("from the observer").count(), 2);
+    }
+
+    #[test]
+    fn test_observer_after_panic() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let observed = Arc::new(AtomicUsize::new(0));
+        let observer = {
+            let observed = Arc::clone(&observed);
+            RecordObserver::new(move |_record| {
+                if observed.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("observer failed");
+                }
+            })
+        };
+        let config = LoggerConfig::builder().observer(observer).build();
+        let logger = FStdoutLogger::with_writer(SharedBuffer::default(), config).unwrap();
+        let log = || {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("observed"))
+                    .level(Level::Info)
+                    .build(),
+            )
+        };
+
+        assert!(std::panic::catch_unwind(AssertUnwindSafe(log)).is_err());
+
+        // A caught panic doesn't stop later records from being observed
+        log();
+        assert_eq!(observed.load(Ordering::SeqCst), 2);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_on_error_reports_write_failures() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        log::set_max_level(LevelFilter::Trace);
+
+        // Writes to /dev/full always fail with "No space left on device"
+        let errors = Arc::new(AtomicUsize::new(0));
+        let slot: Arc<OnceLock<FStdoutLogger>> = Arc::new(OnceLock::new());
+        let handler = {
+            let errors = Arc::clone(&errors);
+            let slot = Arc::clone(&slot);
+            ErrorHandler::new(move |error| {
+                assert!(matches!(error, LogError::Io(_)));
+                errors.fetch_add(1, Ordering::SeqCst);
+
+                // Logging from the handler must neither deadlock nor recurse
+                if let Some(logger) = slot.get() {
+                    logger.log(
+                        &Record::builder()
+                            .args(format_args!("from the handler"))
+                            .level(Level::Warn)
+                            .build(),
+                    );
+                }
+            })
+        };
+        let config = LoggerConfig::builder()
+            .file_mode(FileMode::Append)
+            .on_error(handler)
+            .build();
+        let logger = FStdoutLogger::with_config(Some("/dev/full"), config).unwrap();
+        let logger = slot.get_or_init(|| logger);
+
+        logger.log(
+            &Record::builder()
+                .args(format_args!("disk is full"))
+                .level(Level::Info)
+                .build(),
+        );
+        assert_eq!(errors.load(Ordering::SeqCst), 1);
+
+        // Without a handler, errors are still ignored silently
+        let config = LoggerConfig::builder().file_mode(FileMode::Append).build();
+        let logger = FStdoutLogger::with_config(Some("/dev/full"), config).unwrap();
+        logger.log(
+            &Record::builder()
+                .args(format_args!("disk is full"))
+                .level(Level::Info)
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_multiple_files() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let dir = std::env::temp_dir().join("fstdout_logger_multiple_files");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let combined = dir.join("combined.log");
+        let errors = dir.join("errors.log");
+
+        let config = LoggerConfig::builder()
+            .level(LevelFilter::Debug)
+            .add_file(&errors, LevelFilter::Error)
+            .build();
+        let logger = FStdoutLogger::with_config(Some(&combined), config).unwrap();
+        assert_eq!(logger.files.len(), 2);
+
+        for (level, message) in [(Level::Info, "started"), (Level::Error, "failed")] {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{message}"))
+                    .level(level)
+                    .build(),
+            );
+        }
+
+        let combined = std::fs::read_to_string(&combined).unwrap();
+        let errors = std::fs::read_to_string(&errors).unwrap();
+        assert_eq!(combined.lines().count(), 2);
+        assert!(combined.lines().next().unwrap().ends_with("] started"));
+        // The error record is written to both files, formatted once
+        assert_eq!(
+            errors.lines().collect::<Vec<_>>(),
+            [combined.lines().nth(1).unwrap()]
+        );
+        assert!(errors.contains("ERROR") && errors.ends_with("] failed\n"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_level_range() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let dir = std::env::temp_dir().join("fstdout_logger_file_level_range");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let routine = dir.join("routine.log");
+
+        let config = LoggerConfig::builder()
+            .level(LevelFilter::Trace)
+            .add_file_range(&routine, LevelFilter::Info, Level::Warn)
+            .build();
+        let logger = FStdoutLogger::with_config(None::<&Path>, config).unwrap();
+        for level in Level::iter() {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{level} record"))
+                    .level(level)
+                    .build(),
+            );
+        }
+
+        // Only the records within the range, bounds included
+        let routine = std::fs::read_to_string(&routine).unwrap();
+        let messages: Vec<&str> = routine
+            .lines()
+            .map(|line| line.split("] ").nth(1).unwrap())
+            .collect();
+        assert_eq!(messages, ["WARN record", "INFO record"]);
+
+        // A range no record can match is rejected
+        let config = LoggerConfig::builder()
+            .add_file_range(dir.join("empty.log"), LevelFilter::Warn, Level::Info)
+            .build();
+        assert!(matches!(
+            FStdoutLogger::with_config(None::<&Path>, config),
+            Err(LogError::InvalidLevel(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_record_separator() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let records = |logger: &FStdoutLogger| {
+            for message in ["one", "two"] {
+                logger.log(
+                    &Record::builder()
+                        .args(format_args!("{message}"))
+                        .level(Level::Info)
+                        .build(),
+                );
+            }
+        };
+
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .use_colors(false)
+            .stdout_template(Some("{level} {message}".to_string()))
+            .record_separator("\0")
+            .build();
+        records(&FStdoutLogger::with_writer(buffer.clone(), config).unwrap());
+        assert_eq!(buffer.contents(), "INFO one\0INFO two\0");
+
+        let path = std::env::temp_dir().join("fstdout_logger_record_separator.log");
+        let _ = std::fs::remove_file(&path);
+        let config = LoggerConfig::builder()
+            .file_template(Some("{level} {message}".to_string()))
+            .record_separator("\r\n")
+            .build();
+        records(&FStdoutLogger::with_config(Some(&path), config).unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), b"INFO one\r\nINFO two\r\n");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_drop_flushes() {
+        let dir = std::env::temp_dir().join("fstdout_logger_drop_flushes");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let config = LoggerConfig::builder().buffered(true).build();
+        let logger = FStdoutLogger::with_config(Some(&path), config).unwrap();
+        let handle = logger.handle();
+        let log = |message| {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("{message}"))
+                    .level(Level::Info)
+                    .build(),
+            )
+        };
+
+        log("flushed by the handle");
+        assert!(std::fs::read_to_string(&path).unwrap().is_empty());
+
+        // Poison the file lock, dropping the handle must not panic
+        let file = Arc::clone(&logger.files[0].file);
+        let result = std::thread::spawn(move || {
+            let _guard = file.lock().unwrap();
+            panic!("panic while holding the log file lock");
+        })
+        .join();
+        assert!(result.is_err());
+
+        drop(handle);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("flushed by the handle"));
+
+        log("flushed by the logger");
+        drop(logger);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("flushed by the logger"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lazy_open() {
+        let dir = std::env::temp_dir().join("fstdout_logger_lazy_open");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("app.log");
+
+        let config = LoggerConfig::builder()
+            .stdout_enabled(false)
+            .file_mode(FileMode::Truncate)
+            .lazy_open(true)
+            .build();
+        let logger = FStdoutLogger::with_config(Some(&path), config).unwrap();
+        assert!(!dir.exists());
+
+        // The first records race to open the file, none of them is lost
+        std::thread::scope(|scope| {
+            for n in 0..8 {
+                let logger = &logger;
+                scope.spawn(move || {
+                    logger.log_record(
+                        &Record::builder()
+                            .args(format_args!("thread {n}"))
+                            .level(Level::Info)
+                            .build(),
+                    )
+                });
+            }
+        });
+        drop(logger);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 8);
+        for n in 0..8 {
+            assert!(contents.contains(&format!("thread {n}")));
+        }
+
+        // Failing to open the file is reported once, on the first write
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let errors = Arc::clone(&errors);
+            ErrorHandler::new(move |error| errors.lock().unwrap().push(error.to_string()))
+        };
+        let config = LoggerConfig::builder()
+            .stdout_enabled(false)
+            .lazy_open(true)
+            .on_error(handler)
+            .build();
+        let logger = FStdoutLogger::with_config(Some("/dev/null/app.log"), config).unwrap();
+        assert!(errors.lock().unwrap().is_empty());
+        for _ in 0..2 {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("not written"))
+                    .level(Level::Info)
+                    .build(),
+            );
+        }
+        assert_eq!(errors.lock().unwrap().len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_log_path() {
+        let dir = std::env::temp_dir().join("fstdout_logger_log_path");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(dir.join("sub")).unwrap();
+
+        let logger =
+            FStdoutLogger::with_config(Some(dir.join("sub/../app.log")), LoggerConfig::default())
+                .unwrap();
+        let expected = std::fs::canonicalize(&dir).unwrap().join("app.log");
+        assert_eq!(logger.handle().log_path(), Some(expected));
+
+        // A file that doesn't exist yet keeps the path as given
+        let config = LoggerConfig::builder().lazy_open(true).build();
+        let path = dir.join("missing/lazy.log");
+        let logger = FStdoutLogger::with_config(Some(&path), config).unwrap();
+        assert_eq!(logger.handle().log_path(), Some(path));
+
+        let logger =
+            FStdoutLogger::with_writer(SharedBuffer::default(), LoggerConfig::default()).unwrap();
+        assert_eq!(logger.handle().log_path(), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_async_file() {
+        let dir = std::env::temp_dir().join("fstdout_logger_async_file");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        let log = |logger: &FStdoutLogger, n: usize| {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("line {n}"))
+                    .level(Level::Info)
+                    .build(),
+            )
+        };
+
+        // Blocking keeps every line, in order, and flushing waits for them
+        let config = LoggerConfig::builder()
+            .stdout_enabled(false)
+            .show_file_info(false)
+            .async_file(true)
+            .async_file_capacity(4)
+            .build();
+        let logger = FStdoutLogger::with_config(Some(&path), config).unwrap();
+        for n in 0..100 {
+            log(&logger, n);
+        }
+        logger.flush();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 100);
+        for (n, line) in lines.iter().enumerate() {
+            assert!(line.ends_with(&format!("] line {n}")));
+        }
+        drop(logger);
+
+        // Dropping never waits; what doesn't fit is reported
+        let dropped = Arc::new(AtomicU64::new(0));
+        let handler = {
+            let dropped = Arc::clone(&dropped);
+            ErrorHandler::new(move |error| {
+                if let LogError::FileQueueFull(count) = error {
+                    dropped.store(*count, Ordering::SeqCst);
+                }
+            })
+        };
+        let config = LoggerConfig::builder()
+            .stdout_enabled(false)
+            .file_mode(FileMode::Truncate)
+            .async_file(true)
+            .async_file_capacity(1)
+            .async_file_backpressure(BackpressurePolicy::Drop)
+            .on_error(handler)
+            .build();
+        let logger = FStdoutLogger::with_config(Some(&path), config).unwrap();
+        {
+            // Hold up the writer thread on the file lock
+            let _file = sink::lock(&logger.files[0].file);
+            for n in 0..10 {
+                log(&logger, n);
+            }
+        }
+        drop(logger);
+        let written = std::fs::read_to_string(&path).unwrap().lines().count() as u64;
+        let dropped = dropped.load(Ordering::SeqCst);
+        assert!(dropped >= 8);
+        assert_eq!(written + dropped, 10);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_async_file_capacity_zero_rejected() {
+        let config = LoggerConfig::builder()
+            .async_file(true)
+            .async_file_capacity(0)
+            .build();
+        let result = FStdoutLogger::with_writer(SharedBuffer::default(), config);
+        assert!(matches!(result, Err(LogError::InvalidConfig(_))));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_async_file_survives_panicking_handler() {
+        // Writes to /dev/full always fail, and the handler panics on each one
+        let calls = Arc::new(AtomicUsize::new(0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let handler = {
+            let calls = Arc::clone(&calls);
+            let stopped = Arc::clone(&stopped);
+            ErrorHandler::new(move |error| {
+                if matches!(error, LogError::FileWriterStopped) {
+                    stopped.store(true, Ordering::SeqCst);
+                    return;
+                }
+                calls.fetch_add(1, Ordering::SeqCst);
+                panic!("handler failed");
+            })
+        };
+        let config = LoggerConfig::builder()
+            .stdout_enabled(false)
+            .file_mode(FileMode::Append)
+            .async_file(true)
+            .on_error(handler)
+            .build();
+        let logger = FStdoutLogger::with_config(Some("/dev/full"), config).unwrap();
+        for n in 0..3 {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("line {n}"))
+                    .level(Level::Info)
+                    .build(),
+            );
+            logger.file_worker.as_ref().unwrap().drain();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(!stopped.load(Ordering::SeqCst));
+
+        // Dropping flushes /dev/full on this thread, where the handler panics
+        std::mem::forget(logger);
+    }
+
+    #[test]
+    fn test_poisoned_file_lock_recovers() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let dir = std::env::temp_dir().join("fstdout_logger_poisoned_lock");
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let logger = FStdoutLogger::with_config(Some(&path), LoggerConfig::default()).unwrap();
+
+        // Panic while holding the file lock to poison it
+        let file = Arc::clone(&logger.files[0].file);
+        let result = std::thread::spawn(move || {
+            let _guard = file.lock().unwrap();
+            panic!("panic while holding the log file lock");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(logger.files[0].file.is_poisoned());
+
+        logger.log(
+            &Record::builder()
+                .args(format_args!("still logging"))
+                .level(Level::Info)
+                .build(),
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("still logging"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_show_delta() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .show_delta(true)
+            .ring_buffer_capacity(Some(10))
+            .build();
+        let logger = FStdoutLogger::with_writer(buffer.clone(), config.clone()).unwrap();
+        let handle = logger.handle();
+        let log = || {
+            logger.log_record(
+                &Record::builder()
+                    .args(format_args!("step"))
+                    .level(Level::Info)
+                    .build(),
+            )
+        };
+        let delta = |line: &str| -> u128 {
+            let (_, rest) = line.split_once(" (+").unwrap();
+            rest.split_once("ms)").unwrap().0.parse().unwrap()
+        };
+
+        log();
+        std::thread::sleep(Duration::from_millis(20));
+        log();
+        // Replacing the configuration doesn't restart the measurement
+        std::thread::sleep(Duration::from_millis(20));
+        handle.set_config(config).unwrap();
+        log();
+
+        let output = buffer.contents();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains(" (+0ms) INFO]"), "{}", lines[0]);
+        assert!(delta(lines[1]) >= 20, "{}", lines[1]);
+        assert!(delta(lines[2]) >= 20, "{}", lines[2]);
+
+        // A record shows the same delta on the console and in the files
+        let recent = handle.recent_logs();
+        for (line, console_line) in recent.iter().zip(&lines) {
+            assert_eq!(delta(line), delta(console_line));
+        }
+    }
+
+    #[test]
+    fn test_show_sequence() {
+        log::set_max_level(LevelFilter::Trace);
+
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .show_sequence(true)
+            .ring_buffer_capacity(Some(400))
+            .build();
+        let logger = FStdoutLogger::with_writer(buffer.clone(), config).unwrap();
+        let handle = logger.handle();
+
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let logger = &logger;
+                scope.spawn(move || {
+                    for n in 0..50 {
+                        logger.log_record(
+                            &Record::builder()
+                                .args(format_args!("thread {thread} record {n}"))
+                                .level(Level::Info)
+                                .build(),
+                        );
+                    }
+                });
+            }
+        });
+
+        let sequence = |line: &str| -> u64 {
+            let (_, rest) = line.split_once(" #").unwrap();
+            rest[..6].parse().unwrap()
+        };
+        let output = buffer.contents();
+        let mut numbers: Vec<u64> = output.lines().map(sequence).collect();
+        numbers.sort_unstable();
+        assert_eq!(numbers, (1..=200).collect::<Vec<_>>());
+        assert!(output.contains(" #000001 INFO]"));
+
+        // Each record has the same number on the console and in the files
+        let console: Vec<&str> = output.lines().collect();
+        let recent = handle.recent_logs();
+        assert_eq!(recent.len(), 200);
+        for line in &recent {
+            let message = line.split_once("] ").unwrap().1;
+            let console_line = console
+                .iter()
+                .find(|console_line| console_line.ends_with(&format!("] {message}")))
+                .unwrap();
+            assert_eq!(sequence(line), sequence(console_line));
+        }
+
+        // The width is configurable, and numbering is off by default
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder()
+            .use_colors(false)
+            .show_sequence(true)
+            .sequence_width(0)
+            .build();
+        let logger = FStdoutLogger::with_writer(buffer.clone(), config).unwrap();
+        logger.log_record(
+            &Record::builder()
+                .args(format_args!("unpadded"))
+                .level(Level::Info)
+                .build(),
+        );
+        assert!(buffer.contents().contains(" #1 INFO "));
+
+        let buffer = SharedBuffer::default();
+        let config = LoggerConfig::builder().use_colors(false).build();
+        let logger = FStdoutLogger::with_writer(buffer.clone(), config).unwrap();
+        logger.log_record(
+            &Record::builder()
+                .args(format_args!("unnumbered"))
+                .level(Level::Info)
+                .build(),
+        );
+        assert!(!buffer.contents().contains('#'));
     }
 }
@@ -0,0 +1,189 @@
+//! Logging macros that attach the name of the calling function.
+//!
+//! The `log` crate's macros record the module, file and line of a call, but
+//! not the function it was made from. The `*_fn!` macros in this module add
+//! it as a `_fn` key-value, which the formatter shows in front of the message
+//! in text output and as a top-level `function` field in JSON output.
+//!
+//! The [`log_err!`](crate::log_err) macro logs the error of a `Result`
+//! without breaking the expression it is part of.
+//!
+//! The [`audit!`](crate::audit) macro records compliance events with a fixed
+//! set of fields under the [`AUDIT_TARGET`](crate::AUDIT_TARGET) target.
+
+/// Get the name of the enclosing function as a `&'static str`.
+///
+/// Only the function's own name is returned, without its module path. Inside
+/// a closure, the name of the function containing the closure is returned.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::function_name;
+///
+/// fn handle_request() -> &'static str {
+///     function_name!()
+/// }
+///
+/// assert_eq!(handle_request(), "handle_request");
+/// ```
+#[macro_export]
+macro_rules! function_name {
+    () => {{
+        fn f() {}
+        fn type_name_of<T>(_: T) -> &'static str {
+            ::std::any::type_name::<T>()
+        }
+        let mut name = type_name_of(f);
+        name = name.strip_suffix("::f").unwrap_or(name);
+        while let Some(outer) = name.strip_suffix("::{{closure}}") {
+            name = outer;
+        }
+        match name.rfind("::") {
+            Some(index) => &name[index + 2..],
+            None => name,
+        }
+    }};
+}
+
+/// Log a message at the given level, tagged with the calling function's name.
+///
+/// Accepts the same arguments as `log::log!` after the level.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::log_fn;
+/// use log::Level;
+///
+/// fn connect(attempt: u32) {
+///     log_fn!(Level::Warn, "attempt {attempt} failed");
+/// }
+/// # connect(1);
+/// ```
+#[macro_export]
+macro_rules! log_fn {
+    ($lvl:expr, $($arg:tt)+) => {
+        $crate::__log::log!($lvl, _fn = $crate::function_name!(); $($arg)+)
+    };
+}
+
+/// Log an error, tagged with the calling function's name.
+#[macro_export]
+macro_rules! error_fn {
+    ($($arg:tt)+) => {
+        $crate::log_fn!($crate::__log::Level::Error, $($arg)+)
+    };
+}
+
+/// Log a warning, tagged with the calling function's name.
+#[macro_export]
+macro_rules! warn_fn {
+    ($($arg:tt)+) => {
+        $crate::log_fn!($crate::__log::Level::Warn, $($arg)+)
+    };
+}
+
+/// Log an info message, tagged with the calling function's name.
+#[macro_export]
+macro_rules! info_fn {
+    ($($arg:tt)+) => {
+        $crate::log_fn!($crate::__log::Level::Info, $($arg)+)
+    };
+}
+
+/// Log a debug message, tagged with the calling function's name.
+#[macro_export]
+macro_rules! debug_fn {
+    ($($arg:tt)+) => {
+        $crate::log_fn!($crate::__log::Level::Debug, $($arg)+)
+    };
+}
+
+/// Log a trace message, tagged with the calling function's name.
+#[macro_export]
+macro_rules! trace_fn {
+    ($($arg:tt)+) => {
+        $crate::log_fn!($crate::__log::Level::Trace, $($arg)+)
+    };
+}
+
+/// Log the error of a `Result`, if any, and evaluate to the `Result` unchanged.
+///
+/// The error is logged at the given level, `Error` by default, with the
+/// calling module as its target and the caller's file and line. See
+/// [`LogResultExt::log_err_with_target`](crate::LogResultExt::log_err_with_target).
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::log_err;
+/// use log::Level;
+///
+/// fn load(path: &str) -> Option<String> {
+///     log_err!(std::fs::read_to_string(path), Level::Warn).ok()
+/// }
+/// # load("missing.txt");
+/// ```
+#[macro_export]
+macro_rules! log_err {
+    ($result:expr, $lvl:expr $(,)?) => {
+        $crate::LogResultExt::log_err_with_target($result, $lvl, ::std::module_path!())
+    };
+    ($result:expr $(,)?) => {
+        $crate::log_err!($result, $crate::__log::Level::Error)
+    };
+}
+
+/// Record an audit event: who did what to which resource, and how it ended.
+///
+/// The event is logged at `Info` level with the [`AUDIT_TARGET`](crate::AUDIT_TARGET)
+/// target, so it is written to the configured
+/// [`audit_file`](crate::LoggerConfigBuilder::audit_file) if there is one.
+/// Audit events are never filtered out: they are written whatever the
+/// logger's level and module levels are.
+/// `actor`, `action`, `resource` and `outcome` accept anything that
+/// implements `Display`. They are part of the message in text output and
+/// separate fields in JSON output, which is marked with `"type": "audit"`. Like every
+/// record, the event carries the full date and time in the file.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::audit;
+///
+/// let user = "alice";
+/// audit!(actor = user, action = "delete", resource = "invoice/42", outcome = "success");
+/// ```
+#[macro_export]
+macro_rules! audit {
+    (actor = $actor:expr, action = $action:expr, resource = $resource:expr, outcome = $outcome:expr $(,)?) => {
+        match (&$actor, &$action, &$resource, &$outcome) {
+            // Bypass `log!`, which would drop the event below the maximum level
+            (actor, action, resource, outcome) => {
+                use $crate::__log::kv::Value;
+                let fields = [
+                    ("_type", Value::from("audit")),
+                    ("actor", Value::from_display(actor)),
+                    ("action", Value::from_display(action)),
+                    ("resource", Value::from_display(resource)),
+                    ("outcome", Value::from_display(outcome)),
+                ];
+                $crate::__log::logger().log(
+                    &$crate::__log::Record::builder()
+                        .args(format_args!(
+                            "actor={} action={} resource={} outcome={}",
+                            actor, action, resource, outcome
+                        ))
+                        .level($crate::__log::Level::Info)
+                        .target($crate::AUDIT_TARGET)
+                        .module_path_static(Some(module_path!()))
+                        .file_static(Some(file!()))
+                        .line(Some(line!()))
+                        .key_values(&fields)
+                        .build(),
+                );
+            }
+        }
+    };
+}
@@ -0,0 +1,76 @@
+//! Logging of panics through the installed logger.
+
+use std::panic::{self, PanicHookInfo};
+use std::thread;
+
+use log::{Level, Record};
+
+/// Target of the records logged for panics.
+const PANIC_TARGET: &str = "panic";
+
+/// Install a panic hook that logs panics as errors through the active logger.
+///
+/// The default hook prints panics to stderr only, so they never reach the
+/// log file. The installed hook logs an `Error` record with the `panic`
+/// target, the location of the panic as file and line, and a message like
+/// `thread 'main' panicked at src/main.rs:10:5: index out of bounds`. The
+/// payload is shown if it is a `&str` or a `String`, which covers all panics
+/// raised with `panic!`. Output is flushed right away, since the process may
+/// be about to exit.
+///
+/// Call this after the logger has been initialized. Without an installed
+/// logger, the record is discarded.
+///
+/// # Arguments
+///
+/// * `call_previous` - Whether to run the previously installed hook (by
+///   default the one printing to stderr) after logging.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::{init_logger, install_panic_logger};
+///
+/// init_logger(Some("panics.log")).expect("Failed to initialize logger");
+///
+/// // Log panics, then print them to stderr as usual
+/// install_panic_logger(true);
+/// ```
+pub fn install_panic_logger(call_previous: bool) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        log_panic(info);
+        if call_previous {
+            previous(info);
+        }
+    }));
+}
+
+/// Log a panic as an error record and flush the logger.
+fn log_panic(info: &PanicHookInfo) {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+    let thread = thread::current();
+    let thread = thread.name().unwrap_or("<unnamed>");
+    let location = info.location();
+
+    let message = match location {
+        Some(location) => format!("thread '{thread}' panicked at {location}: {payload}"),
+        None => format!("thread '{thread}' panicked: {payload}"),
+    };
+    let logger = log::logger();
+    logger.log(
+        &Record::builder()
+            .args(format_args!("{message}"))
+            .level(Level::Error)
+            .target(PANIC_TARGET)
+            .file(location.map(|location| location.file()))
+            .line(location.map(|location| location.line()))
+            .build(),
+    );
+    logger.flush();
+}
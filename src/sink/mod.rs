@@ -0,0 +1,1334 @@
+//! Output sinks used by the logger.
+//!
+//! This module contains the writer types that sit behind the logger's locks
+//! and actually move formatted log lines to their destination.
+
+use log::Level;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+#[cfg(feature = "syslog")]
+use std::net::{SocketAddr, UdpSocket};
+#[cfg(all(feature = "syslog", unix))]
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::LogError;
+use crate::clock::Timestamp;
+use crate::config::{ErrorHandler, FsyncPolicy, LoggerConfig, RetentionPolicy, RotationPolicy};
+#[cfg(feature = "syslog")]
+use crate::config::{SyslogTarget, SyslogTransport};
+use crate::formatter::write_json_string;
+
+/// Lock a mutex, recovering it if another thread panicked while holding it.
+///
+/// A panic in the middle of a write leaves at worst a partial line behind,
+/// so the writers stay usable and logging continues instead of stopping for
+/// the rest of the process.
+pub(crate) fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Console writer forwarding to stdout through `print!`.
+///
+/// Unlike writing to [`io::stdout`] directly, `print!` output is captured by
+/// the test harness, so logs from passing tests stay hidden.
+pub(crate) struct StdoutWriter;
+
+impl Write for StdoutWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        print!("{}", String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+/// Open a handle that writes straight to the stdout file descriptor.
+///
+/// The descriptor is duplicated, so writes skip both the lock and the line
+/// buffer of [`io::stdout`].
+#[cfg(unix)]
+pub(crate) fn raw_stdout() -> io::Result<File> {
+    use std::os::fd::AsFd;
+    Ok(File::from(io::stdout().as_fd().try_clone_to_owned()?))
+}
+
+/// Open a handle that writes straight to the stdout file handle.
+///
+/// The handle is duplicated, so writes skip both the lock and the line
+/// buffer of [`io::stdout`].
+#[cfg(windows)]
+pub(crate) fn raw_stdout() -> io::Result<File> {
+    use std::os::windows::io::AsHandle;
+    Ok(File::from(io::stdout().as_handle().try_clone_to_owned()?))
+}
+
+/// Raw stdout handles are not available on this platform.
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn raw_stdout() -> io::Result<File> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "fast_stdout is not supported on this platform",
+    ))
+}
+
+/// Turn on ANSI escape code processing in the Windows consoles.
+///
+/// Older Windows consoles print escape codes literally unless virtual
+/// terminal processing is enabled. If it can't be enabled for stdout or
+/// stderr, colors are turned off instead. Streams that aren't consoles,
+/// e.g. because they are redirected to a file, are left alone.
+#[cfg(windows)]
+pub(crate) fn enable_ansi_colors() {
+    use windows_sys::Win32::System::Console::{
+        ENABLE_VIRTUAL_TERMINAL_PROCESSING, GetConsoleMode, GetStdHandle, STD_ERROR_HANDLE,
+        STD_OUTPUT_HANDLE, SetConsoleMode,
+    };
+
+    for stream in [STD_OUTPUT_HANDLE, STD_ERROR_HANDLE] {
+        // SAFETY: the handle is only passed to the console functions, which
+        // fail cleanly on handles that are invalid or not consoles
+        let enabled = unsafe {
+            let handle = GetStdHandle(stream);
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                continue;
+            }
+            mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+        };
+        if !enabled {
+            colored::control::set_override(false);
+        }
+    }
+}
+
+/// Write a formatted line to the browser devtools console.
+///
+/// `Error` and `Warn` records use `console.error` and `console.warn` so the
+/// browser highlights them; all other levels go to `console.log`.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub(crate) fn write_browser_console(level: Level, line: &str) {
+    use web_sys::console;
+    use web_sys::wasm_bindgen::JsValue;
+
+    let line = JsValue::from_str(line);
+    match level {
+        Level::Error => console::error_1(&line),
+        Level::Warn => console::warn_1(&line),
+        Level::Info | Level::Debug | Level::Trace => console::log_1(&line),
+    }
+}
+
+/// A log file together with its buffering mode and rotation state.
+///
+/// The file is always wrapped in a [`BufWriter`]. In direct mode every
+/// write is flushed immediately, so switching between the two modes only
+/// requires flipping a flag while holding the lock.
+pub(crate) struct FileSink {
+    /// The buffered writer around the open log file
+    writer: BufWriter<File>,
+
+    /// Whether writes are kept in the buffer until the next flush
+    buffered: bool,
+
+    /// Whether buffered writes are flushed after every error record
+    flush_on_error: bool,
+
+    /// Which records are synced to disk after being written
+    fsync: FsyncPolicy,
+
+    /// Path of the active log file, `None` for a file handed over already open
+    path: Option<PathBuf>,
+
+    /// Time-based rotation policy
+    rotation: RotationPolicy,
+
+    /// Size-based rotation and limits on rotated files
+    retention: RetentionPolicy,
+
+    /// When the active log file was opened, used to detect rotation boundaries
+    opened_at: Timestamp,
+
+    /// Number of lines written to the active log file
+    lines: u64,
+
+    /// Delimiter ending each line, used to count lines
+    record_separator: String,
+
+    /// Size of the active log file in bytes
+    bytes: u64,
+
+    /// Whether rotated files are recorded in the index file
+    write_index: bool,
+
+    /// Session information for the metadata sidecar, if enabled
+    session: Option<SessionMetadata>,
+
+    /// Number of records written per level, indexed by `Level as usize - 1`
+    counts: [u64; 5],
+
+    /// Errors of rotation bookkeeping that didn't stop the write, to be
+    /// reported once the file lock is released
+    deferred_errors: Vec<io::Error>,
+}
+
+/// Information about a logging session, written to `<file>.meta.json`.
+struct SessionMetadata {
+    /// When the logger opened the file
+    started_at: Timestamp,
+
+    /// Name of the machine the application runs on
+    hostname: String,
+
+    /// Version of the application, if known
+    app_version: Option<String>,
+
+    /// The main configuration options, already rendered as a JSON object
+    config: String,
+}
+
+impl SessionMetadata {
+    /// Capture the session information at startup.
+    fn new(config: &LoggerConfig) -> Self {
+        Self {
+            started_at: Timestamp::now(),
+            hostname: hostname(),
+            app_version: config.app_version.clone(),
+            config: config_json(config),
+        }
+    }
+}
+
+impl FileSink {
+    /// Create a new sink around an already opened file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The opened log file
+    /// * `path` - The path the file was opened from, used when rotating and
+    ///   for the index and sidecar files. Without a path, none of these apply.
+    /// * `config` - Configuration controlling buffering and rotation
+    pub(crate) fn new(file: File, path: Option<PathBuf>, config: &LoggerConfig) -> Self {
+        // Appended files already have content counting towards the size limit
+        let bytes = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Self {
+            writer: BufWriter::new(file),
+            buffered: config.buffered,
+            flush_on_error: config.flush_interval.is_none(),
+            fsync: config.fsync,
+            path,
+            rotation: config.rotation,
+            retention: config.retention,
+            opened_at: Timestamp::now(),
+            lines: 0,
+            record_separator: config.file_record_separator(),
+            bytes,
+            write_index: config.write_index,
+            session: config
+                .write_metadata_sidecar
+                .then(|| SessionMetadata::new(config)),
+            counts: [0; 5],
+            deferred_errors: Vec::new(),
+        }
+    }
+
+    /// Take the errors that occurred after a rotation had already happened.
+    pub(crate) fn take_errors(&mut self) -> Vec<io::Error> {
+        std::mem::take(&mut self.deferred_errors)
+    }
+
+    /// Get the path of the active log file.
+    pub(crate) fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Switch between buffered and direct writes.
+    ///
+    /// Any pending data is flushed before the mode changes so that no lines
+    /// are left behind in the buffer when turning buffering off.
+    pub(crate) fn set_buffered(&mut self, buffered: bool) -> io::Result<()> {
+        self.writer.flush()?;
+        self.buffered = buffered;
+        Ok(())
+    }
+
+    /// Roll the active file over if `now` is in a later rotation period or
+    /// the file has reached its maximum size.
+    ///
+    /// The active file is renamed after the period it was opened in (e.g.
+    /// `app.log` becomes `app-2024-06-01.log`) and a new, empty file is opened
+    /// at the original path. If the index is enabled, the rotated file is
+    /// recorded in it. Afterwards, rotated files beyond the retention limits
+    /// are deleted. Failing to update the index or to delete old files
+    /// doesn't undo the rotation; those errors are kept for [`take_errors`](Self::take_errors).
+    ///
+    /// # Returns
+    ///
+    /// `Ok(true)` if the file was rotated, `Ok(false)` if no limit was reached
+    /// or the file has no path to rotate.
+    pub(crate) fn rotate_if_needed(&mut self, now: Timestamp) -> io::Result<bool> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+        let opened_period = self.rotation.period_label(&self.opened_at);
+        let period_ended = opened_period
+            .as_ref()
+            .is_some_and(|opened| self.rotation.period_label(&now).as_ref() != Some(opened));
+        let size_reached = self
+            .retention
+            .max_file_size
+            .is_some_and(|max| self.bytes >= max);
+        if !period_ended && !size_reached {
+            return Ok(false);
+        }
+
+        self.writer.flush()?;
+        let label = opened_period.unwrap_or_else(|| self.opened_at.format("%Y-%m-%d"));
+        let rotated = rotated_path(path, &label);
+        fs::rename(path, &rotated)?;
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+        self.writer = BufWriter::new(file);
+
+        // Start the new period before the bookkeeping, so a failure there
+        // doesn't make every following write rotate again
+        let start = std::mem::replace(&mut self.opened_at, now);
+        let lines = std::mem::take(&mut self.lines);
+        self.bytes = 0;
+
+        if self.write_index
+            && let Err(error) = self.append_index_entry(&rotated, start, now, lines)
+        {
+            self.deferred_errors.push(error);
+        }
+        self.apply_retention(now);
+        Ok(true)
+    }
+
+    /// Delete rotated files that break the retention limits.
+    ///
+    /// Files are visited newest first. Expired files are always deleted; once
+    /// a file exceeds the count or total size limit, all older files go too.
+    /// A file that can't be deleted doesn't stop the others from being
+    /// deleted; the errors are kept for the error handler.
+    fn apply_retention(&mut self, now: Timestamp) {
+        let Some(active) = &self.path else {
+            return;
+        };
+        let policy = self.retention;
+        if policy.max_files.is_none() && policy.max_age.is_none() && policy.max_total_size.is_none()
+        {
+            return;
+        }
+        let files = match rotated_files(active) {
+            Ok(files) => files,
+            Err(error) => {
+                self.deferred_errors.push(error);
+                return;
+            }
+        };
+
+        let now = SystemTime::from(now);
+        let mut kept_files = 0;
+        let mut kept_size = self.bytes;
+        let mut dropping = false;
+        for (path, modified, size) in files {
+            let expired = policy
+                .max_age
+                .is_some_and(|max_age| now.duration_since(modified).is_ok_and(|age| age > max_age));
+            dropping = dropping
+                || policy.max_files.is_some_and(|max| kept_files >= max)
+                || policy
+                    .max_total_size
+                    .is_some_and(|max| kept_size + size > max);
+
+            if expired || dropping {
+                if let Err(error) = remove_rotated(&path) {
+                    self.deferred_errors.push(error);
+                }
+            } else {
+                kept_files += 1;
+                kept_size += size;
+            }
+        }
+    }
+
+    /// Append an entry describing a rotated file to the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `rotated` - Path the active file was rotated to
+    /// * `start` - Start of the time range covered by the rotated file
+    /// * `end` - End of the time range covered by the rotated file
+    /// * `lines` - Number of lines in the rotated file
+    fn append_index_entry(
+        &self,
+        rotated: &Path,
+        start: Timestamp,
+        end: Timestamp,
+        lines: u64,
+    ) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let name = rotated
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut entry = String::from("{\"file\":");
+        write_json_string(&mut entry, &name);
+        entry.push_str(",\"start\":");
+        write_json_string(&mut entry, &start.to_rfc3339());
+        entry.push_str(",\"end\":");
+        write_json_string(&mut entry, &end.to_rfc3339());
+        entry.push_str(&format!(",\"lines\":{lines}}}\n"));
+
+        let mut index = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(index_path(path))?;
+        index.write_all(entry.as_bytes())
+    }
+
+    /// Write a formatted line, flushing it right away in direct mode.
+    pub(crate) fn write_line(&mut self, line: &str) -> io::Result<()> {
+        self.writer.write_all(line.as_bytes())?;
+        if !self.record_separator.is_empty() {
+            self.lines += line.matches(self.record_separator.as_str()).count() as u64;
+        }
+        self.bytes += line.len() as u64;
+        if !self.buffered {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write a formatted record, flushing or syncing it afterwards if configured.
+    pub(crate) fn write_record(&mut self, line: &str, level: Level) -> io::Result<()> {
+        self.write_line(line)?;
+        self.counts[level as usize - 1] += 1;
+        if self.fsync.applies_to(level) {
+            self.writer.flush()?;
+            self.writer.get_ref().sync_all()?;
+        } else if self.buffered && self.flush_on_error && level == Level::Error {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flush any buffered data to the file.
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Write the metadata sidecar, if enabled and the file has a path.
+    ///
+    /// `ended_at` is `None` while the session is running, which is recorded
+    /// as a `null` end time.
+    pub(crate) fn write_metadata(&self, ended_at: Option<Timestamp>) -> io::Result<()> {
+        let (Some(session), Some(path)) = (&self.session, &self.path) else {
+            return Ok(());
+        };
+
+        let mut metadata = String::from("{\"file\":");
+        write_json_string(&mut metadata, &path.to_string_lossy());
+        metadata.push_str(",\"start_time\":");
+        write_json_string(&mut metadata, &session.started_at.to_rfc3339());
+        metadata.push_str(",\"end_time\":");
+        match ended_at {
+            Some(ended_at) => write_json_string(&mut metadata, &ended_at.to_rfc3339()),
+            None => metadata.push_str("null"),
+        }
+        metadata.push_str(",\"hostname\":");
+        write_json_string(&mut metadata, &session.hostname);
+        metadata.push_str(",\"app_version\":");
+        match &session.app_version {
+            Some(version) => write_json_string(&mut metadata, version),
+            None => metadata.push_str("null"),
+        }
+        metadata.push_str(",\"config\":");
+        metadata.push_str(&session.config);
+        metadata.push_str(",\"records\":{");
+        for (i, level) in Level::iter().enumerate() {
+            if i > 0 {
+                metadata.push(',');
+            }
+            write_json_string(&mut metadata, &level.as_str().to_lowercase());
+            metadata.push_str(&format!(":{}", self.counts[level as usize - 1]));
+        }
+        metadata.push_str("}}\n");
+
+        fs::write(metadata_path(path), metadata)
+    }
+
+    /// Apply a new configuration to the open file.
+    ///
+    /// Only the record separator, which lines are counted by, and the
+    /// configuration recorded in the sidecar follow it; buffering, rotation
+    /// and the other file handling options stay as they were when the file
+    /// was opened. The file is opened before the logger is installed with
+    /// its final level, so this also runs at install time.
+    pub(crate) fn reconfigure(&mut self, config: &LoggerConfig) -> io::Result<()> {
+        self.record_separator = config.file_record_separator();
+        let Some(session) = &mut self.session else {
+            return Ok(());
+        };
+        session.config = config_json(config);
+        self.write_metadata(None)
+    }
+
+    /// Flush the file and complete the metadata sidecar with the end time.
+    pub(crate) fn close(mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.write_metadata(Some(Timestamp::now()))
+    }
+}
+
+/// The most recent formatted lines, kept in memory.
+pub(crate) struct RingBuffer {
+    /// Lines from oldest to newest
+    lines: VecDeque<String>,
+
+    /// Maximum number of lines kept
+    capacity: usize,
+}
+
+impl RingBuffer {
+    /// Create an empty buffer holding at most `capacity` lines.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Add a line, evicting the oldest one if the buffer is full.
+    pub(crate) fn push(&mut self, line: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.to_string());
+    }
+
+    /// Get a copy of the lines, oldest first.
+    pub(crate) fn lines(&self) -> Vec<String> {
+        self.lines.iter().cloned().collect()
+    }
+}
+
+/// Socket connection to a syslog daemon.
+#[cfg(feature = "syslog")]
+pub(crate) struct SyslogSink {
+    /// Where messages go, and how they are labeled
+    target: SyslogTarget,
+
+    /// Name of this machine, sent with every message
+    hostname: String,
+
+    /// The open socket, `None` until the first message or after a failure
+    socket: Option<SyslogSocket>,
+}
+
+/// A connected datagram socket of either transport.
+#[cfg(feature = "syslog")]
+enum SyslogSocket {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+}
+
+#[cfg(feature = "syslog")]
+impl SyslogSink {
+    /// Create a sink for `target` without connecting yet.
+    pub(crate) fn new(target: &SyslogTarget) -> Self {
+        Self {
+            target: target.clone(),
+            hostname: hostname(),
+            socket: None,
+        }
+    }
+
+    /// Get the target messages are sent to.
+    pub(crate) fn target(&self) -> &SyslogTarget {
+        &self.target
+    }
+
+    /// Get the host name sent with every message.
+    pub(crate) fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    /// Send a formatted message, connecting first if needed.
+    ///
+    /// The socket is non-blocking, so a daemon that doesn't keep up can't
+    /// stall the logger: if its queue is full, the message is dropped with a
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) error. If sending over an
+    /// existing socket fails otherwise, e.g. because the daemon restarted,
+    /// the socket is reopened and the message sent once more.
+    pub(crate) fn send(&mut self, message: &str) -> io::Result<()> {
+        let connected = self.socket.is_some();
+        match self.try_send(message) {
+            Err(error) if connected && error.kind() != io::ErrorKind::WouldBlock => {
+                self.try_send(message)
+            }
+            result => result,
+        }
+    }
+
+    /// Send a message over the current socket, opening one if there is none.
+    fn try_send(&mut self, message: &str) -> io::Result<()> {
+        let socket = match &mut self.socket {
+            Some(socket) => socket,
+            None => self.socket.insert(connect_syslog(&self.target.transport)?),
+        };
+        let sent = match socket {
+            SyslogSocket::Udp(socket) => socket.send(message.as_bytes()),
+            #[cfg(unix)]
+            SyslogSocket::Unix(socket) => socket.send(message.as_bytes()),
+        };
+        if sent
+            .as_ref()
+            .is_err_and(|error| error.kind() != io::ErrorKind::WouldBlock)
+        {
+            self.socket = None;
+        }
+        sent.map(|_| ())
+    }
+}
+
+/// Open a datagram socket connected to the syslog daemon.
+#[cfg(feature = "syslog")]
+fn connect_syslog(transport: &SyslogTransport) -> io::Result<SyslogSocket> {
+    match transport {
+        SyslogTransport::Udp(address) => {
+            let local: SocketAddr = if address.is_ipv4() {
+                ([0, 0, 0, 0], 0).into()
+            } else {
+                ([0u16; 8], 0).into()
+            };
+            let socket = UdpSocket::bind(local)?;
+            socket.connect(address)?;
+            socket.set_nonblocking(true)?;
+            Ok(SyslogSocket::Udp(socket))
+        }
+        #[cfg(unix)]
+        SyslogTransport::Unix(path) => {
+            let socket = UnixDatagram::unbound()?;
+            socket.connect(path)?;
+            socket.set_nonblocking(true)?;
+            Ok(SyslogSocket::Unix(socket))
+        }
+    }
+}
+
+/// Periodically flush a shared file sink from a background thread.
+///
+/// The thread only holds a weak reference, so it exits once the logger
+/// owning the sink is dropped or the sink is shut down. Flush errors are
+/// passed to `on_error`.
+pub(crate) fn spawn_flusher(
+    sink: Weak<Mutex<Option<FileSink>>>,
+    interval: Duration,
+    on_error: Option<ErrorHandler>,
+) -> io::Result<()> {
+    thread::Builder::new()
+        .name("fstdout-logger-flush".to_string())
+        .spawn(move || {
+            loop {
+                thread::sleep(interval);
+                let Some(sink) = sink.upgrade() else {
+                    break;
+                };
+                let result = match lock(&sink).as_mut() {
+                    Some(sink) => sink.flush(),
+                    None => break,
+                };
+                if let Err(error) = result
+                    && let Some(handler) = &on_error
+                {
+                    handler.report(LogError::Io(error));
+                }
+            }
+        })?;
+    Ok(())
+}
+
+/// Get the path of the rotation index for a log file (`app.log.index`).
+fn index_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".index");
+    PathBuf::from(name)
+}
+
+/// Get the path of the metadata sidecar for a log file (`app.log.meta.json`).
+fn metadata_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".meta.json");
+    PathBuf::from(name)
+}
+
+/// Get the name of this machine, or `"unknown"` if it can't be determined.
+fn hostname() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Render the configuration options relevant to the log file as a JSON object.
+fn config_json(config: &LoggerConfig) -> String {
+    // The most verbose level any record can be logged at
+    let max_level = config
+        .module_levels
+        .iter()
+        .map(|(_, level)| *level)
+        .fold(config.level, Ord::max);
+    let mut json = String::from("{\"level\":");
+    write_json_string(&mut json, config.level.as_str());
+    json.push_str(",\"max_level\":");
+    write_json_string(&mut json, max_level.as_str());
+    json.push_str(",\"module_levels\":{");
+    for (i, (prefix, level)) in config.module_levels.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write_json_string(&mut json, prefix);
+        json.push(':');
+        write_json_string(&mut json, level.as_str());
+    }
+    json.push('}');
+    for (key, value) in [
+        ("file_mode", format!("{:?}", config.file_mode)),
+        ("file_format", format!("{:?}", config.file_format)),
+        ("rotation", format!("{:?}", config.rotation)),
+        ("fsync", format!("{:?}", config.fsync)),
+    ] {
+        json.push(',');
+        write_json_string(&mut json, key);
+        json.push(':');
+        write_json_string(&mut json, &value);
+    }
+    json.push_str(&format!(",\"buffered\":{}}}", config.buffered));
+    json
+}
+
+/// List the rotated files of a log file, newest first.
+///
+/// Rotated files live next to the log file and are named
+/// `<stem>-<label>.<ext>`, see [`rotated_path`]. Other files sharing the
+/// stem, e.g. `app-server.log` next to `app.log`, are not listed, so
+/// retention never deletes files the logger didn't rotate itself.
+///
+/// # Returns
+///
+/// The path, modification time and size of every rotated file.
+fn rotated_files(path: &Path) -> io::Result<Vec<(PathBuf, SystemTime, u64)>> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let prefix = format!(
+        "{}-",
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    );
+    let suffix = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let label = name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(&suffix));
+        if !label.is_some_and(is_rotation_label) {
+            continue;
+        }
+        // Skip files deleted since the directory was read
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+        if metadata.is_file() {
+            files.push((entry.path(), metadata.modified()?, metadata.len()));
+        }
+    }
+    files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.cmp(&a.0)));
+    Ok(files)
+}
+
+/// Delete a rotated file.
+///
+/// A file that is already gone, e.g. because another process sharing the
+/// directory deleted it first, is not an error.
+fn remove_rotated(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Check whether the part of a file name between the stem and the extension
+/// was written by [`rotated_path`].
+///
+/// That is a daily (`2024-06-01`) or hourly (`2024-06-01-13`) period label,
+/// optionally followed by a `.N` counter.
+fn is_rotation_label(label: &str) -> bool {
+    let period = match label.split_once('.') {
+        Some((period, counter)) => {
+            if counter.is_empty() || !counter.bytes().all(|b| b.is_ascii_digit()) {
+                return false;
+            }
+            period
+        }
+        None => label,
+    };
+    let pattern: &[u8] = match period.len() {
+        10 => b"dddd-dd-dd",
+        13 => b"dddd-dd-dd-dd",
+        _ => return false,
+    };
+    period.bytes().zip(pattern).all(|(b, &p)| match p {
+        b'd' => b.is_ascii_digit(),
+        p => b == p,
+    })
+}
+
+/// Build the date-stamped path a rotated file is moved to.
+///
+/// The period label is inserted between the file stem and extension, so
+/// `logs/app.log` becomes `logs/app-2024-06-01.log`. If that file already
+/// exists, a numeric suffix is added to avoid overwriting it.
+fn rotated_path(path: &Path, period: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut candidate = path.with_file_name(format!("{stem}-{period}{extension}"));
+    let mut counter = 1;
+    while candidate.exists() {
+        candidate = path.with_file_name(format!("{stem}-{period}.{counter}{extension}"));
+        counter += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "chrono")]
+    use chrono::{Local, TimeZone};
+
+    #[cfg(feature = "chrono")]
+    fn open_sink(path: &Path, rotation: RotationPolicy, opened_at: Timestamp) -> FileSink {
+        let file = File::create(path).unwrap();
+        let config = LoggerConfig::builder()
+            .rotation(rotation)
+            .write_index(true)
+            .build();
+        let mut sink = FileSink::new(file, Some(path.to_path_buf()), &config);
+        sink.opened_at = opened_at;
+        sink
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_daily_rotation_after_idle_midnight() {
+        let dir = std::env::temp_dir().join("fstdout_logger_daily_rotation");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let before_midnight: Timestamp = Local
+            .with_ymd_and_hms(2024, 6, 1, 23, 59, 0)
+            .unwrap()
+            .into();
+        let next_morning: Timestamp = Local.with_ymd_and_hms(2024, 6, 2, 8, 0, 0).unwrap().into();
+
+        let mut sink = open_sink(&path, RotationPolicy::Daily, before_midnight);
+        sink.write_line("day one\n").unwrap();
+
+        // Same day: no rotation
+        assert!(!sink.rotate_if_needed(before_midnight).unwrap());
+
+        // First write the next day rolls the file over
+        assert!(sink.rotate_if_needed(next_morning).unwrap());
+        sink.write_line("day two\n").unwrap();
+
+        let rotated = fs::read_to_string(dir.join("app-2024-06-01.log")).unwrap();
+        let active = fs::read_to_string(&path).unwrap();
+        assert_eq!(rotated, "day one\n");
+        assert_eq!(active, "day two\n");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_hourly_rotation_filename() {
+        let dir = std::env::temp_dir().join("fstdout_logger_hourly_rotation");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let opened: Timestamp = Local.with_ymd_and_hms(2024, 6, 1, 13, 5, 0).unwrap().into();
+        let later: Timestamp = Local.with_ymd_and_hms(2024, 6, 1, 14, 0, 0).unwrap().into();
+
+        let mut sink = open_sink(&path, RotationPolicy::Hourly, opened);
+        assert!(sink.rotate_if_needed(later).unwrap());
+        assert!(dir.join("app-2024-06-01-13.log").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_index_updated_on_rotation() {
+        let dir = std::env::temp_dir().join("fstdout_logger_rotation_index");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let day_one: Timestamp = Local.with_ymd_and_hms(2024, 6, 1, 9, 0, 0).unwrap().into();
+        let day_two: Timestamp = Local.with_ymd_and_hms(2024, 6, 2, 9, 0, 0).unwrap().into();
+        let day_three: Timestamp = Local.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap().into();
+
+        let mut sink = open_sink(&path, RotationPolicy::Daily, day_one);
+        sink.write_line("one\n").unwrap();
+        sink.write_line("two\n").unwrap();
+        sink.rotate_if_needed(day_two).unwrap();
+        sink.write_line("three\n").unwrap();
+        sink.rotate_if_needed(day_three).unwrap();
+
+        let index = fs::read_to_string(dir.join("app.log.index")).unwrap();
+        let entries: Vec<serde_json::Value> = index
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["file"], "app-2024-06-01.log");
+        assert_eq!(entries[0]["lines"], 2);
+        assert_eq!(entries[0]["start"], day_one.to_rfc3339());
+        assert_eq!(entries[0]["end"], day_two.to_rfc3339());
+        assert_eq!(entries[1]["file"], "app-2024-06-02.log");
+        assert_eq!(entries[1]["lines"], 1);
+        assert_eq!(entries[1]["start"], day_two.to_rfc3339());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_index_failure_does_not_repeat_rotation() {
+        let dir = std::env::temp_dir().join("fstdout_logger_index_failure");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        // A directory in place of the index makes appending to it fail
+        fs::create_dir_all(dir.join("app.log.index")).unwrap();
+
+        let day_one: Timestamp = Local.with_ymd_and_hms(2024, 6, 1, 9, 0, 0).unwrap().into();
+        let day_two: Timestamp = Local.with_ymd_and_hms(2024, 6, 2, 9, 0, 0).unwrap().into();
+
+        let mut sink = open_sink(&path, RotationPolicy::Daily, day_one);
+        sink.write_line("one\n").unwrap();
+        assert!(sink.rotate_if_needed(day_two).unwrap());
+        assert_eq!(sink.take_errors().len(), 1);
+
+        // The new period has started, so later writes don't rotate again
+        sink.write_line("two\n").unwrap();
+        assert!(!sink.rotate_if_needed(day_two).unwrap());
+        assert!(sink.take_errors().is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two\n");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_buffered_flushes_on_error() {
+        let dir = std::env::temp_dir().join("fstdout_logger_flush_on_error");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let config = LoggerConfig::builder().buffered(true).build();
+        let mut sink = FileSink::new(File::create(&path).unwrap(), Some(path.clone()), &config);
+
+        sink.write_record("info\n", Level::Info).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+        sink.write_record("error\n", Level::Error).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "info\nerror\n");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reconfigure_record_separator() {
+        let dir = std::env::temp_dir().join("fstdout_logger_reconfigure_separator");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let config = LoggerConfig::default();
+        let mut sink = FileSink::new(File::create(&path).unwrap(), Some(path.clone()), &config);
+        sink.write_line("first\n").unwrap();
+
+        // Lines are counted by the new separator from now on
+        let config = LoggerConfig::builder().record_separator("\0").build();
+        sink.reconfigure(&config).unwrap();
+        sink.write_line("second\0").unwrap();
+        sink.write_line("third\0").unwrap();
+        assert_eq!(sink.lines, 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_fsync_keeps_output_intact() {
+        let dir = std::env::temp_dir().join("fstdout_logger_fsync");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        for policy in [FsyncPolicy::OnError, FsyncPolicy::Always] {
+            let path = dir.join("app.log");
+            let config = LoggerConfig::builder().buffered(true).fsync(policy).build();
+            let mut sink = FileSink::new(File::create(&path).unwrap(), Some(path.clone()), &config);
+
+            sink.write_record("one\n", Level::Info).unwrap();
+            sink.write_record("two\n", Level::Error).unwrap();
+            sink.write_record("three\n", Level::Warn).unwrap();
+            sink.flush().unwrap();
+
+            assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\nthree\n");
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Create a fake rotated file of `size` bytes last modified `days_ago`.
+    fn old_rotated_file(dir: &Path, name: &str, size: usize, days_ago: u64) -> PathBuf {
+        let path = dir.join(name);
+        let file = File::create(&path).unwrap();
+        file.set_len(size as u64).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(days_ago * 24 * 60 * 60))
+            .unwrap();
+        path
+    }
+
+    /// Rotate a daily sink holding `"yesterday\n"` into today's file.
+    fn rotate_with_retention(dir: &Path, retention: RetentionPolicy) {
+        let path = dir.join("app.log");
+        let now = Timestamp::now();
+        let config = LoggerConfig::builder()
+            .rotation(RotationPolicy::Daily)
+            .retention(retention)
+            .build();
+        let mut sink = FileSink::new(File::create(&path).unwrap(), Some(path.clone()), &config);
+        sink.opened_at = Timestamp::from(SystemTime::now() - Duration::from_secs(24 * 60 * 60));
+        sink.write_line("yesterday\n").unwrap();
+        assert!(sink.rotate_if_needed(now).unwrap());
+    }
+
+    fn retention_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_remove_rotated_already_gone() {
+        let dir = retention_dir("fstdout_logger_retention_gone");
+        let path = old_rotated_file(&dir, "app-2000-01-01.log", 10, 1);
+
+        remove_rotated(&path).unwrap();
+        assert!(!path.exists());
+
+        // Another process deleting it first is fine
+        remove_rotated(&path).unwrap();
+
+        // Other errors are still reported
+        assert!(remove_rotated(&dir).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_retention_max_files() {
+        let dir = retention_dir("fstdout_logger_retention_files");
+        let newer = old_rotated_file(&dir, "app-2000-01-03.log", 10, 3);
+        let older = old_rotated_file(&dir, "app-2000-01-02.log", 10, 4);
+        let oldest = old_rotated_file(&dir, "app-2000-01-01.log", 10, 5);
+
+        rotate_with_retention(
+            &dir,
+            RetentionPolicy {
+                max_files: Some(2),
+                ..Default::default()
+            },
+        );
+
+        // The freshly rotated file and the newest old one are kept
+        assert!(newer.exists());
+        assert!(!older.exists() && !oldest.exists());
+        assert_eq!(rotated_files(&dir.join("app.log")).unwrap().len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_retention_max_age() {
+        let dir = retention_dir("fstdout_logger_retention_age");
+        let recent = old_rotated_file(&dir, "app-2000-01-03.log", 10, 1);
+        let expired = old_rotated_file(&dir, "app-2000-01-02.log", 10, 3);
+
+        rotate_with_retention(
+            &dir,
+            RetentionPolicy {
+                max_files: Some(10),
+                max_age: Some(Duration::from_secs(2 * 24 * 60 * 60)),
+                ..Default::default()
+            },
+        );
+
+        assert!(recent.exists());
+        assert!(!expired.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_retention_max_total_size() {
+        let dir = retention_dir("fstdout_logger_retention_total_size");
+        let newer = old_rotated_file(&dir, "app-2000-01-03.log", 10, 3);
+        let older = old_rotated_file(&dir, "app-2000-01-02.log", 10, 4);
+        // Small enough to fit on its own, but older than a file that didn't fit
+        let tiny = old_rotated_file(&dir, "app-2000-01-01.log", 1, 5);
+
+        // "yesterday\n" (10 bytes) + newer (10 bytes) fit, older doesn't
+        rotate_with_retention(
+            &dir,
+            RetentionPolicy {
+                max_files: Some(10),
+                max_total_size: Some(25),
+                ..Default::default()
+            },
+        );
+
+        assert!(newer.exists());
+        assert!(!older.exists() && !tiny.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_retention_skips_unrelated_files() {
+        let dir = retention_dir("fstdout_logger_retention_unrelated");
+        let hourly = old_rotated_file(&dir, "app-2000-01-03-13.log", 10, 3);
+        let counted = old_rotated_file(&dir, "app-2000-01-02.1.log", 10, 4);
+        let unrelated = [
+            old_rotated_file(&dir, "app-server.log", 10, 5),
+            old_rotated_file(&dir, "app-2000-01-01.txt", 10, 5),
+            old_rotated_file(&dir, "app-2000-01-01.old.log", 10, 5),
+            old_rotated_file(&dir, "myapp-2000-01-01.log", 10, 5),
+        ];
+
+        // Only the rotated files count against the limit and get deleted
+        rotate_with_retention(
+            &dir,
+            RetentionPolicy {
+                max_total_size: Some(15),
+                ..Default::default()
+            },
+        );
+
+        assert!(!hourly.exists() && !counted.exists());
+        assert!(unrelated.iter().all(|path| path.exists()));
+        assert_eq!(rotated_files(&dir.join("app.log")).unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_is_rotation_label() {
+        for label in [
+            "2024-06-01",
+            "2024-06-01-13",
+            "2024-06-01.2",
+            "2024-06-01-13.10",
+        ] {
+            assert!(is_rotation_label(label), "{label}");
+        }
+        for label in [
+            "server",
+            "2024-06-1",
+            "2024-06-01.",
+            "2024-06-01.x",
+            "2024_06_01",
+        ] {
+            assert!(!is_rotation_label(label), "{label}");
+        }
+    }
+
+    #[test]
+    fn test_retention_max_file_size() {
+        let dir = retention_dir("fstdout_logger_retention_file_size");
+        let path = dir.join("app.log");
+        let config = LoggerConfig::builder()
+            .retention(RetentionPolicy {
+                max_file_size: Some(16),
+                ..Default::default()
+            })
+            .build();
+        let mut sink = FileSink::new(File::create(&path).unwrap(), Some(path.clone()), &config);
+        let now = Timestamp::now();
+
+        sink.write_line("0123456789\n").unwrap();
+        assert!(!sink.rotate_if_needed(now).unwrap());
+        sink.write_line("0123456789\n").unwrap();
+
+        // Without a time-based policy, the size limit alone triggers rotation
+        assert!(sink.rotate_if_needed(now).unwrap());
+        sink.write_line("fresh\n").unwrap();
+
+        let rotated = rotated_files(&path).unwrap();
+        assert_eq!(rotated.len(), 1);
+        assert_eq!(rotated[0].2, 22);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fresh\n");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_never_rotates() {
+        let dir = std::env::temp_dir().join("fstdout_logger_no_rotation");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let opened: Timestamp = Local
+            .with_ymd_and_hms(2024, 6, 1, 23, 59, 0)
+            .unwrap()
+            .into();
+        let much_later: Timestamp = Local.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap().into();
+
+        let mut sink = open_sink(&path, RotationPolicy::Never, opened);
+        assert!(!sink.rotate_if_needed(much_later).unwrap());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(all(feature = "syslog", unix))]
+    #[test]
+    fn test_syslog_reconnects_after_send_failure() {
+        let dir = std::env::temp_dir().join(format!(
+            "fstdout_logger_syslog_reconnect_{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("syslog.sock");
+        let daemon = UnixDatagram::bind(&path).unwrap();
+        let mut sink = SyslogSink::new(&SyslogTarget {
+            facility: Default::default(),
+            app_name: "test".to_string(),
+            transport: SyslogTransport::Unix(path.clone()),
+        });
+        let mut buffer = [0; 64];
+
+        sink.send("before restart").unwrap();
+        let len = daemon.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"before restart");
+
+        // The daemon restarts, so the connected socket's peer is gone
+        drop(daemon);
+        fs::remove_file(&path).unwrap();
+        let daemon = UnixDatagram::bind(&path).unwrap();
+
+        sink.send("after restart").unwrap();
+        let len = daemon.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"after restart");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(all(feature = "syslog", unix))]
+    #[test]
+    fn test_syslog_full_daemon_doesnt_block() {
+        let dir =
+            std::env::temp_dir().join(format!("fstdout_logger_syslog_full_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("syslog.sock");
+        let daemon = UnixDatagram::bind(&path).unwrap();
+        let mut sink = SyslogSink::new(&SyslogTarget {
+            facility: Default::default(),
+            app_name: "test".to_string(),
+            transport: SyslogTransport::Unix(path.clone()),
+        });
+
+        // The daemon never reads, so its queue fills up and messages are
+        // dropped instead of blocking the sender
+        let error = (0..100_000)
+            .find_map(|_| sink.send("never read").err())
+            .expect("the daemon's queue never filled up");
+        assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+
+        // The socket is kept, so messages go through once the daemon catches up
+        daemon.set_nonblocking(true).unwrap();
+        let mut buffer = [0; 64];
+        while daemon.recv(&mut buffer).is_ok() {}
+        sink.send("caught up").unwrap();
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "syslog")]
+    #[test]
+    fn test_syslog_over_udp() {
+        let daemon = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut sink = SyslogSink::new(&SyslogTarget {
+            facility: Default::default(),
+            app_name: "test".to_string(),
+            transport: SyslogTransport::Udp(daemon.local_addr().unwrap()),
+        });
+        let mut buffer = [0; 64];
+
+        sink.send("hello").unwrap();
+        let len = daemon.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"hello");
+    }
+}
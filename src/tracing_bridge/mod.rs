@@ -0,0 +1,284 @@
+//! Bridge from the `tracing` ecosystem into the logger.
+//!
+//! Libraries built on `tracing` emit events instead of `log` records. The
+//! [`LogBridgeLayer`] in this module turns every event into a `log` record
+//! and passes it to the installed logger, so both kinds of diagnostics share
+//! the same filtering, formatting and files. This module is only available
+//! with the `tracing` feature.
+
+use std::fmt::{self, Write as _};
+use std::path::Path;
+
+use log::Record;
+use log::kv::Value;
+use tracing::field::{Field, Visit};
+use tracing::span::{self, Attributes, Id};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::{LogError, LoggerConfig, init_logger_with_config};
+
+/// A `tracing-subscriber` layer forwarding events to the installed `log` logger.
+///
+/// Events are dropped unless the logger accepts their level and target, so
+/// the logger's global and per-module levels apply to them as well. The
+/// layer needs a subscriber that stores span data, such as
+/// `tracing_subscriber::registry()`.
+///
+/// # Captured fields
+///
+/// - The event's `message` field becomes the record's message.
+/// - All other event fields are appended to the message as `name=value` and
+///   attached to the record as key-values, so they also appear in JSON and
+///   logfmt file output. Integers, floats and booleans keep their type, so
+///   they are written as JSON numbers and booleans rather than strings.
+/// - Fields of the enclosing spans, as given when each span was created or
+///   later recorded with `Span::record`, prefix the message in the style of
+///   `tracing-subscriber`: `outer{id=1}:inner: message`. They are not
+///   attached as key-values.
+/// - The event's level, target, module path, file and line are kept.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::{LogBridgeLayer, init_logger};
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// init_logger(None::<&str>).expect("Failed to initialize logger");
+///
+/// let subscriber = tracing_subscriber::registry().with(LogBridgeLayer);
+/// tracing::subscriber::with_default(subscriber, || {
+///     tracing::info!(user = "alice", "signed in");
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogBridgeLayer;
+
+impl<S> Layer<S> for LogBridgeLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        // Spans are always needed as context for the events inside them
+        !metadata.is_event() || log::logger().enabled(&log_metadata(metadata))
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = SpanFields::default();
+            attrs.record(&mut fields);
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(fields) = span.extensions_mut().get_mut::<SpanFields>()
+        {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let logger = log::logger();
+        let log_metadata = log_metadata(metadata);
+        if !logger.enabled(&log_metadata) {
+            return;
+        }
+
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+
+        let mut message = String::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                message.push_str(span.name());
+                if let Some(span_fields) = span.extensions().get::<SpanFields>()
+                    && !span_fields.0.is_empty()
+                {
+                    let _ = write!(message, "{{{}}}", span_fields.0);
+                }
+                message.push(':');
+            }
+            message.push(' ');
+        }
+        message.push_str(&fields.message);
+        for (name, value) in &fields.values {
+            let _ = write!(message, " {name}={value}");
+        }
+
+        let key_values: Vec<(&str, Value)> = fields
+            .values
+            .iter()
+            .map(|(name, value)| (*name, value.to_value()))
+            .collect();
+        logger.log(
+            &Record::builder()
+                .metadata(log_metadata)
+                .args(format_args!("{message}"))
+                .module_path(metadata.module_path())
+                .file(metadata.file())
+                .line(metadata.line())
+                .key_values(&key_values.as_slice())
+                .build(),
+        );
+    }
+}
+
+/// Initialize a logger with custom configuration that also receives `tracing` events.
+///
+/// This works like [`init_logger_with_config`] and additionally installs a
+/// global `tracing` subscriber that forwards events through
+/// [`LogBridgeLayer`]. See its documentation for the fields that are
+/// captured.
+///
+/// # Arguments
+///
+/// * `file_path` - Optional path to a log file. If `None`, logs will only go to stdout.
+/// * `config` - Configuration options for the logger.
+///
+/// # Returns
+///
+/// `Ok(())` if initialization succeeded, or an error if a logger or a global
+/// `tracing` subscriber was already installed. If a subscriber was already
+/// installed, the logger isn't installed either.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::{LoggerConfig, init_logger_with_tracing};
+///
+/// init_logger_with_tracing(Some("tracing.log"), LoggerConfig::default())
+///     .expect("Failed to initialize logger");
+///
+/// tracing::info!("from tracing");
+/// log::info!("from log");
+/// ```
+pub fn init_logger_with_tracing<P: AsRef<Path>>(
+    file_path: Option<P>,
+    config: LoggerConfig,
+) -> Result<(), LogError> {
+    // Fail before installing the logger, which can't be undone
+    if tracing::dispatcher::has_been_set() {
+        return Err(LogError::Logger);
+    }
+    init_logger_with_config(file_path, config)?;
+    let subscriber = tracing_subscriber::registry().with(LogBridgeLayer);
+    tracing::subscriber::set_global_default(subscriber).map_err(|_| LogError::Logger)
+}
+
+/// Build the `log` metadata corresponding to a `tracing` callsite.
+fn log_metadata<'a>(metadata: &Metadata<'a>) -> log::Metadata<'a> {
+    let level = match *metadata.level() {
+        tracing::Level::ERROR => log::Level::Error,
+        tracing::Level::WARN => log::Level::Warn,
+        tracing::Level::INFO => log::Level::Info,
+        tracing::Level::DEBUG => log::Level::Debug,
+        tracing::Level::TRACE => log::Level::Trace,
+    };
+    log::Metadata::builder()
+        .level(level)
+        .target(metadata.target())
+        .build()
+}
+
+/// Fields of a span, rendered as `name=value` pairs separated by spaces.
+#[derive(Default)]
+struct SpanFields(String);
+
+impl Visit for SpanFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        let _ = write!(self.0, "{}={value:?}", field.name());
+    }
+}
+
+/// The message and the other fields of an event.
+#[derive(Default)]
+struct EventFields {
+    /// The `message` field
+    message: String,
+
+    /// All other fields as `(name, value)` pairs
+    values: Vec<(&'static str, FieldValue)>,
+}
+
+/// The value of an event field, keeping the type of primitive values.
+enum FieldValue {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl FieldValue {
+    /// Borrow the value as a key-value of a `log` record.
+    fn to_value(&self) -> Value<'_> {
+        match self {
+            Self::I64(value) => Value::from(*value),
+            Self::U64(value) => Value::from(*value),
+            Self::F64(value) => Value::from(*value),
+            Self::Bool(value) => Value::from(*value),
+            Self::Text(value) => Value::from(value.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::I64(value) => value.fmt(f),
+            Self::U64(value) => value.fmt(f),
+            Self::F64(value) => value.fmt(f),
+            Self::Bool(value) => value.fmt(f),
+            Self::Text(value) => value.fmt(f),
+        }
+    }
+}
+
+impl EventFields {
+    /// Store a field, unless it is the message.
+    fn push(&mut self, field: &Field, value: FieldValue) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.values.push((field.name(), value));
+        }
+    }
+}
+
+impl Visit for EventFields {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, FieldValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, FieldValue::U64(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field, FieldValue::F64(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, FieldValue::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, FieldValue::Text(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, FieldValue::Text(format!("{value:?}")));
+    }
+}
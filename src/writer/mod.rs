@@ -0,0 +1,138 @@
+//! An `io::Write` adapter that turns written text into log records.
+//!
+//! This module lets output that only knows how to write to a stream, such as
+//! a child process or a library's progress reporting, end up in the log.
+
+use log::{Level, Log, Record};
+use std::fmt;
+use std::io::{self, Write};
+
+/// Log each line written to it as a record through the installed logger.
+///
+/// Text is buffered until a newline; a trailing line without one is logged
+/// when the writer is dropped. Line endings (`\n` or `\r\n`) are stripped and
+/// invalid UTF-8 is replaced, so every line becomes one readable record.
+/// A partial line longer than 8 KiB is logged as soon as it reaches that
+/// length, so a stream without newlines can't grow the buffer forever.
+///
+/// # Example
+///
+/// ```rust
+/// use fstdout_logger::LogWriter;
+/// use log::Level;
+/// use std::io::Write;
+///
+/// let mut writer = LogWriter::new(Level::Info).target("child");
+/// writeln!(writer, "build finished in {}s", 12).unwrap();
+/// ```
+pub struct LogWriter {
+    /// Level of the logged records
+    level: Level,
+
+    /// Target of the logged records
+    target: String,
+
+    /// Bytes written since the last complete line
+    buffer: Vec<u8>,
+
+    /// Logger the records are passed to
+    logger: &'static dyn Log,
+}
+
+/// Longest partial line kept in the buffer before it is logged on its own.
+const MAX_PARTIAL_LINE: usize = 8 * 1024;
+
+impl LogWriter {
+    /// Create a writer that logs lines at the given level.
+    ///
+    /// Records use the `log_writer` target unless another one is set with
+    /// [`target`](LogWriter::target).
+    pub fn new(level: Level) -> Self {
+        Self::with_logger(level, log::logger())
+    }
+
+    /// Create a writer that logs lines at the given level through `logger`.
+    pub(crate) fn with_logger(level: Level, logger: &'static dyn Log) -> Self {
+        Self {
+            level,
+            target: "log_writer".to_string(),
+            buffer: Vec::new(),
+            logger,
+        }
+    }
+
+    /// Set the target of the logged records, e.g. the name of a child process.
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    /// Log one line, without its line ending, as a record.
+    ///
+    /// Nothing is logged if the level is above the global maximum level. A
+    /// trailing `\r` is stripped and invalid UTF-8 is replaced.
+    fn log_line(&self, line: &[u8]) {
+        if self.level > log::max_level() {
+            return;
+        }
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let text = String::from_utf8_lossy(line);
+        self.logger.log(
+            &Record::builder()
+                .args(format_args!("{text}"))
+                .level(self.level)
+                .target(&self.target)
+                .build(),
+        );
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if let Some(end) = self.buffer.iter().rposition(|&byte| byte == b'\n') {
+            let complete: Vec<u8> = self.buffer.drain(..=end).collect();
+            for line in complete[..end].split(|&byte| byte == b'\n') {
+                self.log_line(line);
+            }
+        }
+        while self.buffer.len() >= MAX_PARTIAL_LINE {
+            // Don't split a UTF-8 character between two records
+            let mut end = MAX_PARTIAL_LINE;
+            while end > MAX_PARTIAL_LINE - 4
+                && self.buffer.get(end).is_some_and(|byte| byte & 0xC0 == 0x80)
+            {
+                end -= 1;
+            }
+            let partial: Vec<u8> = self.buffer.drain(..end).collect();
+            self.log_line(&partial);
+        }
+        Ok(buf.len())
+    }
+
+    /// Flush the logger. A partial line stays buffered until it is completed
+    /// or the writer is dropped, so it isn't split into two records.
+    fn flush(&mut self) -> io::Result<()> {
+        self.logger.flush();
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LogWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogWriter")
+            .field("level", &self.level)
+            .field("target", &self.target)
+            .field("buffer", &self.buffer)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            self.log_line(&rest);
+        }
+    }
+}
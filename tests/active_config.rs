@@ -0,0 +1,21 @@
+use fstdout_logger::{FStdoutLogger, LoggerConfig, active_config};
+use log::LevelFilter;
+
+#[test]
+fn test_active_config() {
+    assert!(active_config().is_none());
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Info)
+        .show_file_info(false)
+        .build();
+    FStdoutLogger::with_config(None::<&str>, config)
+        .expect("Failed to create logger")
+        .init_with_level(LevelFilter::Trace)
+        .expect("Failed to initialize logger");
+
+    let config = active_config().expect("No active config");
+    // The level passed at init wins over the builder's
+    assert_eq!(config.level, LevelFilter::Trace);
+    assert!(!config.show_file_info);
+}
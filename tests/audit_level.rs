@@ -0,0 +1,45 @@
+use fstdout_logger::{LoggerConfig, audit, init_logger_with_config};
+use log::{LevelFilter, info};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_audit_events_bypass_level() {
+    let log_file = "test_audit_level_main.log";
+    let audit_file = "test_audit_level_trail.log";
+    let _ = fs::remove_file(log_file);
+    let _ = fs::remove_file(audit_file);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Error)
+        .module_level("audit", LevelFilter::Off)
+        .use_colors(false)
+        .dedup_window(Some(std::time::Duration::from_secs(60)))
+        .audit_file(Some(PathBuf::from(audit_file)))
+        .build();
+    init_logger_with_config(Some(log_file), config).expect("Failed to initialize logger");
+
+    info!("filtered out");
+    for _ in 0..2 {
+        audit!(
+            actor = "alice",
+            action = "login",
+            resource = "console",
+            outcome = "success"
+        );
+    }
+    log::logger().flush();
+
+    // Neither the level nor the module level nor repeat collapsing drop audit events
+    let audit_contents = fs::read_to_string(audit_file).unwrap();
+    assert_eq!(audit_contents.lines().count(), 2, "{audit_contents}");
+    assert!(audit_contents.contains("actor=alice action=login"));
+    assert!(
+        !fs::read_to_string(log_file)
+            .unwrap()
+            .contains("filtered out")
+    );
+
+    let _ = fs::remove_file(log_file);
+    let _ = fs::remove_file(audit_file);
+}
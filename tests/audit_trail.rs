@@ -0,0 +1,54 @@
+use fstdout_logger::{FileFormat, LoggerConfig, audit, init_logger_with_config};
+use log::{LevelFilter, info};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_audit_events_go_to_audit_file() {
+    let log_file = "test_audit_main.log";
+    let audit_file = "test_audit_trail.log";
+    let _ = fs::remove_file(log_file);
+    let _ = fs::remove_file(audit_file);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Info)
+        .use_colors(false)
+        .file_format(FileFormat::Json)
+        .audit_file(Some(PathBuf::from(audit_file)))
+        .build();
+    init_logger_with_config(Some(log_file), config).expect("Failed to initialize logger");
+
+    let user = String::from("alice");
+    info!("regular record");
+    audit!(
+        actor = user,
+        action = "delete",
+        resource = "invoice/42",
+        outcome = "denied"
+    );
+    log::logger().flush();
+
+    let audit_contents = fs::read_to_string(audit_file).unwrap();
+    let lines: Vec<&str> = audit_contents.lines().collect();
+    assert_eq!(lines.len(), 1);
+    let event: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(event["type"], "audit");
+    assert_eq!(event["target"], "audit");
+    assert_eq!(event["fields"]["actor"], "alice");
+    assert_eq!(event["fields"]["action"], "delete");
+    assert_eq!(event["fields"]["resource"], "invoice/42");
+    assert_eq!(event["fields"]["outcome"], "denied");
+    assert_eq!(
+        event["message"],
+        "actor=alice action=delete resource=invoice/42 outcome=denied"
+    );
+    assert!(chrono::DateTime::parse_from_rfc3339(event["timestamp"].as_str().unwrap()).is_ok());
+
+    // Only regular records end up in the log file
+    let log_contents = fs::read_to_string(log_file).unwrap();
+    assert_eq!(log_contents.lines().count(), 1);
+    assert!(log_contents.contains("regular record"));
+
+    let _ = fs::remove_file(log_file);
+    let _ = fs::remove_file(audit_file);
+}
@@ -0,0 +1,37 @@
+use fstdout_logger::{LoggerConfig, init_logger_with_config, set_buffered};
+use log::{LevelFilter, info};
+use std::fs;
+
+#[test]
+fn test_toggle_buffering() {
+    let test_file = "test_buffered.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Info)
+        .use_colors(false)
+        .build();
+    init_logger_with_config(Some(test_file), config).expect("Failed to initialize logger");
+
+    // Direct mode: the line is on disk right away
+    info!("direct line");
+    let contents = fs::read_to_string(test_file).unwrap();
+    assert!(contents.contains("direct line"));
+
+    // Buffered mode: the line stays in memory until flushed
+    set_buffered(true).unwrap();
+    info!("buffered line");
+    let contents = fs::read_to_string(test_file).unwrap();
+    assert!(!contents.contains("buffered line"));
+
+    // Turning buffering off flushes the pending line
+    set_buffered(false).unwrap();
+    let contents = fs::read_to_string(test_file).unwrap();
+    assert!(contents.contains("buffered line"));
+
+    info!("direct again");
+    let contents = fs::read_to_string(test_file).unwrap();
+    assert!(contents.contains("direct again"));
+
+    let _ = fs::remove_file(test_file);
+}
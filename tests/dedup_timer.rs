@@ -0,0 +1,46 @@
+mod common;
+
+use common::SharedBuffer;
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{LevelFilter, info};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_dedup_turned_on_later_summarizes_after_window() {
+    let output = SharedBuffer::default();
+    let config = || {
+        LoggerConfig::builder()
+            .use_colors(false)
+            .show_file_info(false)
+    };
+    let handle = FStdoutLogger::with_writer(output.clone(), config().build())
+        .expect("Failed to create logger")
+        .init_with_handle(LevelFilter::Info)
+        .expect("Failed to initialize logger");
+
+    // Collapsing is only turned on after the logger was installed
+    handle
+        .set_config(
+            config()
+                .dedup_window(Some(Duration::from_millis(50)))
+                .build(),
+        )
+        .expect("Failed to replace the configuration");
+    for _ in 0..3 {
+        info!("polling");
+    }
+
+    // The summary shows up once the window closes, without a flush
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while output.lines().len() < 2 {
+        assert!(Instant::now() < deadline, "no summary was written");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let lines = output.lines();
+    assert!(lines[0].ends_with("] polling"), "{}", lines[0]);
+    assert!(
+        lines[1].ends_with("] polling (repeated 2 times)"),
+        "{}",
+        lines[1]
+    );
+}
@@ -0,0 +1,48 @@
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{LevelFilter, error, trace, warn};
+use std::env;
+use std::process::Command;
+
+/// Environment variable telling the re-executed test binary to act as the child.
+const CHILD_ENV: &str = "FSTDOUT_ENV_LEVEL_CHILD";
+
+#[test]
+fn test_env_level_cap() {
+    if env::var(CHILD_ENV).is_ok() {
+        let config = LoggerConfig::builder()
+            .module_level("myapp", LevelFilter::Trace)
+            .show_file_info(false)
+            .use_colors(false)
+            .respect_env_level(true)
+            .build();
+        FStdoutLogger::with_config(None::<&str>, config)
+            .expect("Failed to create logger")
+            .init_with_level(LevelFilter::Debug)
+            .expect("Failed to initialize logger");
+
+        warn!(target: "other", "WARN from other");
+        trace!(target: "myapp::db", "TRACE from myapp::db");
+        error!(target: "other", "ERROR from other");
+        log::logger().flush();
+        return;
+    }
+
+    // The variable is read by the logger, so it is set for a child process
+    // running this same test instead of for the whole test binary
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "test_env_level_cap", "--nocapture"])
+        .env(CHILD_ENV, "1")
+        .env("FSTDOUT_MAX_LEVEL", "error")
+        .output()
+        .expect("Failed to run child process");
+    assert!(output.status.success());
+
+    // Both the global and the module level are capped
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.contains(" from "))
+        .collect();
+    assert_eq!(lines.len(), 1, "{stdout}");
+    assert!(lines[0].ends_with(" ERROR] ERROR from other"));
+}
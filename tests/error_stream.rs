@@ -0,0 +1,48 @@
+use fstdout_logger::{ErrorStream, LoggerConfig, init_stdout_logger};
+use log::{LevelFilter, error, info, warn};
+use std::env;
+use std::process::Command;
+
+/// Environment variable telling the re-executed test binary to act as the child.
+const CHILD_ENV: &str = "FSTDOUT_ERROR_STREAM_CHILD";
+
+#[test]
+fn test_warnings_and_errors_go_to_stderr() {
+    if env::var(CHILD_ENV).is_ok() {
+        let config = LoggerConfig::builder()
+            .level(LevelFilter::Info)
+            .use_colors(false)
+            .error_stream(ErrorStream::Stderr)
+            .build();
+        init_stdout_logger(config).expect("Failed to initialize logger");
+
+        info!("info on stdout");
+        warn!("warn on stderr");
+        error!("error on stderr");
+        log::logger().flush();
+        return;
+    }
+
+    // Run this same test in a child process so its streams can be captured separately
+    let output = Command::new(env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "test_warnings_and_errors_go_to_stderr",
+            "--nocapture",
+        ])
+        .env(CHILD_ENV, "1")
+        .output()
+        .expect("Failed to run child process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(stdout.contains("info on stdout"));
+    assert!(!stdout.contains("warn on stderr"));
+    assert!(!stdout.contains("error on stderr"));
+
+    assert!(stderr.contains("warn on stderr"));
+    assert!(stderr.contains("error on stderr"));
+    assert!(!stderr.contains("info on stdout"));
+}
@@ -0,0 +1,38 @@
+use fstdout_logger::{LoggerConfig, function_name, info_fn, init_logger_with_config, warn_fn};
+use log::LevelFilter;
+use std::fs;
+
+fn handle_request(id: u32) {
+    info_fn!("handling request {id}");
+}
+
+fn retry() {
+    let attempt = || warn_fn!("retrying");
+    attempt();
+}
+
+#[test]
+fn test_fn_macros_log_function_name() {
+    let test_file = "test_function_name.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Info)
+        .use_colors(false)
+        .build();
+    init_logger_with_config(Some(test_file), config).expect("Failed to initialize logger");
+
+    handle_request(7);
+    retry();
+
+    let contents = fs::read_to_string(test_file).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("] handle_request: handling request 7"));
+    // Closures report the function they are defined in
+    assert!(lines[1].ends_with("] retry: retrying"));
+
+    assert_eq!(function_name!(), "test_fn_macros_log_function_name");
+
+    let _ = fs::remove_file(test_file);
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use common::SharedBuffer;
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{LevelFilter, info};
+
+#[test]
+fn test_global_fields() {
+    let output = SharedBuffer::default();
+    let config = LoggerConfig::builder()
+        .use_colors(false)
+        .add_global_field("service", "checkout")
+        .add_global_field("env", "prod")
+        .build();
+    FStdoutLogger::with_writer(output.clone(), config)
+        .expect("Failed to create logger")
+        .init_with_level(LevelFilter::Info)
+        .expect("Failed to initialize logger");
+
+    // A call site that knows nothing about the fields still gets them
+    info!("order placed");
+
+    let content = output.contents();
+    assert!(content.ends_with("] order placed service=checkout env=prod\n"));
+}
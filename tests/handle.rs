@@ -0,0 +1,47 @@
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{LevelFilter, info};
+use std::fs;
+
+#[test]
+fn test_handle_flush_and_shutdown() {
+    let test_file = "test_handle.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder().buffered(true).build();
+    let handle = FStdoutLogger::with_config(Some(test_file), config)
+        .expect("Failed to create logger")
+        .init_with_handle(LevelFilter::Info)
+        .expect("Failed to initialize logger");
+
+    info!("buffered before flush");
+    assert!(
+        !fs::read_to_string(test_file)
+            .unwrap()
+            .contains("buffered before flush")
+    );
+
+    handle.flush();
+    assert!(
+        fs::read_to_string(test_file)
+            .unwrap()
+            .contains("buffered before flush")
+    );
+
+    info!("written at shutdown");
+    handle.shutdown();
+    assert!(
+        fs::read_to_string(test_file)
+            .unwrap()
+            .contains("written at shutdown")
+    );
+
+    // The file is closed, later records only go to stdout
+    info!("after shutdown");
+    assert!(
+        !fs::read_to_string(test_file)
+            .unwrap()
+            .contains("after shutdown")
+    );
+
+    let _ = fs::remove_file(test_file);
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::SharedBuffer;
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{LevelFilter, info};
+
+#[test]
+fn test_init_banner_stdout() {
+    // A stdout-only logger only prints the banner when asked to
+    let output = SharedBuffer::default();
+    let config = LoggerConfig::builder()
+        .use_colors(false)
+        .with_banner("=== started at level {level} ===")
+        .init_banner_stdout(true)
+        .build();
+    FStdoutLogger::with_writer(output.clone(), config)
+        .expect("Failed to create logger")
+        .init_with_level(LevelFilter::Info)
+        .expect("Failed to initialize logger");
+
+    info!("first record");
+
+    let content = output.contents();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "=== started at level INFO ===");
+    assert!(lines[1].contains("first record"));
+}
@@ -0,0 +1,17 @@
+use fstdout_logger::{LoggerConfig, init_logger_with_config};
+use log::{Level, LevelFilter, log_enabled};
+
+#[test]
+fn test_log_enabled_respects_module_levels() {
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Debug)
+        .module_level("noisy", LevelFilter::Warn)
+        .build();
+    init_logger_with_config(None::<&str>, config).expect("Failed to initialize logger");
+
+    assert!(log_enabled!(Level::Debug));
+    assert!(!log_enabled!(Level::Trace));
+    assert!(!log_enabled!(target: "noisy", Level::Debug));
+    assert!(!log_enabled!(target: "noisy::inner", Level::Info));
+    assert!(log_enabled!(target: "noisy", Level::Warn));
+}
@@ -0,0 +1,54 @@
+use fstdout_logger::{LogResultExt, LoggerConfig, init_logger_with_config, log_err};
+use log::{Level, LevelFilter};
+use std::fs;
+
+mod quiet {
+    use fstdout_logger::log_err;
+
+    pub fn fail() -> Result<u32, String> {
+        log_err!(Err("filtered out".to_string()))
+    }
+}
+
+#[test]
+fn test_log_err_only_logs_errors() {
+    let test_file = "test_log_result.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Info)
+        .module_level("log_result::quiet", LevelFilter::Off)
+        .show_target(true)
+        .use_colors(false)
+        .build();
+    init_logger_with_config(Some(test_file), config).expect("Failed to initialize logger");
+
+    let failed: Result<u32, String> = Err("disk on fire".to_string());
+    let passed: Result<u32, String> = Ok(7);
+
+    assert_eq!(log_err!(failed.clone(), Level::Error), failed);
+    assert_eq!(log_err!(passed.clone()), passed);
+
+    // The module level of the caller applies
+    assert!(quiet::fail().is_err());
+
+    // The method chains, under its own target
+    let chained: Result<u32, String> = Err("fan stalled".to_string());
+    assert_eq!(chained.clone().log_err(Level::Warn), chained);
+    assert_eq!(passed.clone().log_err(Level::Warn), passed);
+
+    let contents = fs::read_to_string(test_file).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("ERROR"));
+    assert!(lines[0].contains("disk on fire"));
+    // The record points at this module and file, not at the helper
+    assert!(lines[0].contains(" log_result "));
+    assert!(lines[0].contains("tests/log_result.rs"));
+    assert!(lines[1].contains("WARN"));
+    assert!(lines[1].contains(" log_err "));
+    assert!(lines[1].contains("tests/log_result.rs"));
+    assert!(lines[1].ends_with("fan stalled"));
+
+    let _ = fs::remove_file(test_file);
+}
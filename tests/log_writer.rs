@@ -0,0 +1,55 @@
+mod common;
+
+use common::SharedBuffer;
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{Level, LevelFilter};
+use std::io::Write;
+
+#[test]
+fn test_log_writer_lines() {
+    let output = SharedBuffer::default();
+    let config = LoggerConfig::builder()
+        .use_colors(false)
+        .show_file_info(false)
+        .build();
+    let handle = FStdoutLogger::with_writer(output.clone(), config)
+        .expect("Failed to create logger")
+        .init_with_handle(LevelFilter::Info)
+        .expect("Failed to initialize logger");
+
+    let mut writer = handle.writer(Level::Info);
+    writer.write_all(b"hello\nwor").unwrap();
+    writer.write_all(b"ld").unwrap();
+    writer.flush().unwrap();
+
+    // Only the complete line is logged; the rest waits for a newline
+    let lines = output.lines();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("INFO] hello"), "{}", lines[0]);
+
+    // Dropping the writer logs the trailing partial line
+    drop(writer);
+    let lines = output.lines();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].ends_with("INFO] world"), "{}", lines[1]);
+
+    // Below the logger's level, nothing is logged
+    let mut debug = handle.writer(Level::Debug);
+    debug.write_all(b"hidden\r\n").unwrap();
+    drop(debug);
+    assert_eq!(output.lines().len(), 2);
+
+    // A line that never ends is logged in pieces instead of piling up,
+    // without splitting characters
+    let mut endless = handle.writer(Level::Info);
+    for _ in 0..3000 {
+        endless.write_all("€".as_bytes()).unwrap();
+    }
+    let lines = output.lines();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[2].ends_with(&format!("INFO] {}", "€".repeat(2730))));
+    drop(endless);
+    let lines = output.lines();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[3].ends_with(&format!("INFO] {}", "€".repeat(270))));
+}
@@ -0,0 +1,59 @@
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{LevelFilter, error, info, warn};
+use std::fs;
+
+#[test]
+fn test_metadata_sidecar() {
+    let test_file = "test_metadata_sidecar.log";
+    let sidecar = "test_metadata_sidecar.log.meta.json";
+    let _ = fs::remove_file(test_file);
+    let _ = fs::remove_file(sidecar);
+
+    let config = LoggerConfig::builder()
+        .use_colors(false)
+        .level(LevelFilter::Error)
+        .module_level("hyper", LevelFilter::Warn)
+        .module_level("db", LevelFilter::Debug)
+        .write_metadata_sidecar(true)
+        .app_version(env!("CARGO_PKG_VERSION"))
+        .build();
+    let handle = FStdoutLogger::with_config(Some(test_file), config)
+        .expect("Failed to create logger")
+        .init_with_handle(LevelFilter::Info)
+        .expect("Failed to initialize logger");
+
+    // Written at init, before the session ends
+    let started: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(sidecar).unwrap()).unwrap();
+    assert_eq!(started["file"], test_file);
+    assert!(chrono::DateTime::parse_from_rfc3339(started["start_time"].as_str().unwrap()).is_ok());
+    assert!(started["end_time"].is_null());
+    assert!(!started["hostname"].as_str().unwrap().is_empty());
+    assert_eq!(started["app_version"], env!("CARGO_PKG_VERSION"));
+    // The level the logger was installed with, not the configured one
+    assert_eq!(started["config"]["level"], "INFO");
+    assert_eq!(started["config"]["max_level"], "DEBUG");
+    assert_eq!(started["config"]["module_levels"]["hyper"], "WARN");
+    assert_eq!(started["config"]["file_mode"], "Archive");
+    assert_eq!(started["records"]["info"], 0);
+
+    info!("first");
+    info!("second");
+    warn!("careful");
+    error!("failed");
+    handle.shutdown();
+
+    // Updated at shutdown with the end time and counts
+    let ended: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(sidecar).unwrap()).unwrap();
+    assert_eq!(ended["start_time"], started["start_time"]);
+    assert!(chrono::DateTime::parse_from_rfc3339(ended["end_time"].as_str().unwrap()).is_ok());
+    assert_eq!(ended["records"]["error"], 1);
+    assert_eq!(ended["records"]["warn"], 1);
+    assert_eq!(ended["records"]["info"], 2);
+    assert_eq!(ended["records"]["debug"], 0);
+    assert_eq!(ended["records"]["trace"], 0);
+
+    let _ = fs::remove_file(test_file);
+    let _ = fs::remove_file(sidecar);
+}
@@ -0,0 +1,48 @@
+use fstdout_logger::{LoggerConfig, init_logger_with_config};
+use log::LevelFilter;
+use std::fs;
+
+mod chatty {
+    pub fn work() {
+        log::debug!("chatty debug");
+        log::trace!("chatty trace");
+    }
+}
+
+mod noisy {
+    pub fn work() {
+        log::warn!("noisy warning");
+        log::error!("noisy error");
+    }
+}
+
+#[test]
+fn test_module_levels_override_global_level() {
+    let test_file = "test_module_levels.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Warn)
+        .use_colors(false)
+        .module_level("module_levels::chatty", LevelFilter::Debug)
+        .module_level("module_levels::noisy", LevelFilter::Error)
+        .build();
+    init_logger_with_config(Some(test_file), config).expect("Failed to initialize logger");
+    assert_eq!(log::max_level(), LevelFilter::Debug);
+
+    log::info!("global info");
+    log::warn!("global warning");
+    chatty::work();
+    noisy::work();
+
+    let contents = fs::read_to_string(test_file).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("] global warning"));
+    // More verbose than the global level
+    assert!(lines[1].ends_with("] chatty debug"));
+    // Quieter than the global level
+    assert!(lines[2].ends_with("] noisy error"));
+
+    let _ = fs::remove_file(test_file);
+}
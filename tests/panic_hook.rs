@@ -0,0 +1,43 @@
+use fstdout_logger::{LoggerConfig, init_logger_with_config, install_panic_logger};
+use log::LevelFilter;
+use std::{fs, thread};
+
+#[test]
+fn test_panics_are_logged() {
+    let test_file = "test_panic_hook.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Info)
+        .use_colors(false)
+        .build();
+    init_logger_with_config(Some(test_file), config).expect("Failed to initialize logger");
+    install_panic_logger(false);
+
+    let static_line = line!() + 4;
+    let result = thread::Builder::new()
+        .name("worker".to_string())
+        .spawn(|| {
+            panic!("static payload");
+        })
+        .unwrap()
+        .join();
+    assert!(result.is_err());
+
+    let code = 7;
+    let formatted_line = line!() + 1;
+    let result = thread::spawn(move || panic!("formatted payload {code}")).join();
+    assert!(result.is_err());
+
+    let contents = fs::read_to_string(test_file).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains(&format!(" ERROR tests/panic_hook.rs:{static_line}] ")));
+    assert!(lines[0].ends_with(&format!(
+        "thread 'worker' panicked at tests/panic_hook.rs:{static_line}:13: static payload"
+    )));
+    assert!(lines[1].contains(&format!("tests/panic_hook.rs:{formatted_line}")));
+    assert!(lines[1].ends_with("formatted payload 7"));
+
+    let _ = fs::remove_file(test_file);
+}
@@ -0,0 +1,30 @@
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{LevelFilter, debug, info, warn};
+
+#[test]
+fn test_recent_logs_ring_buffer() {
+    let config = LoggerConfig::builder()
+        .show_file_info(false)
+        .use_colors(false)
+        .file_template(Some("{level} {message}".to_string()))
+        .ring_buffer_capacity(Some(3))
+        .build();
+    let handle = FStdoutLogger::with_config(None::<&str>, config)
+        .expect("Failed to create logger")
+        .init_with_handle(LevelFilter::Info)
+        .expect("Failed to initialize logger");
+
+    assert!(handle.recent_logs().is_empty());
+
+    info!("one");
+    debug!("filtered out");
+    info!("two");
+    warn!("three");
+    info!("four");
+
+    // Only the last three records that passed the filters are kept
+    assert_eq!(
+        handle.recent_logs(),
+        ["INFO two", "WARN three", "INFO four"]
+    );
+}
@@ -0,0 +1,80 @@
+use fstdout_logger::{FStdoutLogger, LoggerConfig};
+use log::{LevelFilter, debug, info};
+use std::fs;
+use std::time::Duration;
+
+#[test]
+fn test_set_config_at_runtime() {
+    let test_file = "test_reconfigure.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder()
+        .show_file_info(false)
+        .use_colors(false)
+        .build();
+    let handle = FStdoutLogger::with_config(Some(test_file), config)
+        .expect("Failed to create logger")
+        .init_with_handle(LevelFilter::Info)
+        .expect("Failed to initialize logger");
+
+    debug!("hidden before reconfiguring");
+    info!("plain record");
+
+    let debug_config = LoggerConfig::builder()
+        .level(LevelFilter::Debug)
+        .show_file_info(true)
+        .use_colors(false)
+        .file_template(Some("{level} {file} {message}".to_string()))
+        .build();
+    handle
+        .set_config(debug_config)
+        .expect("Failed to replace the configuration");
+    assert_eq!(log::max_level(), LevelFilter::Debug);
+
+    debug!("shown after reconfiguring");
+
+    // Invalid configurations are rejected and leave the current one in place
+    let invalid = LoggerConfig::builder()
+        .file_template(Some("{unknown}".to_string()))
+        .build();
+    assert!(handle.set_config(invalid).is_err());
+    debug!("still debug");
+    handle.flush();
+
+    let content = fs::read_to_string(test_file).expect("Failed to read log file");
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains(" INFO ") && lines[0].ends_with("] plain record"));
+    assert_eq!(
+        lines[1],
+        "DEBUG tests/reconfigure.rs shown after reconfiguring"
+    );
+    assert_eq!(lines[2], "DEBUG tests/reconfigure.rs still debug");
+
+    // Repeats dropped under the old configuration are summarized, not lost
+    let dedup_config = || {
+        LoggerConfig::builder()
+            .show_file_info(false)
+            .use_colors(false)
+            .dedup_window(Some(Duration::from_secs(60)))
+            .build()
+    };
+    handle
+        .set_config(dedup_config())
+        .expect("Failed to replace the configuration");
+    for _ in 0..4 {
+        info!("burst");
+    }
+    handle
+        .set_config(dedup_config())
+        .expect("Failed to replace the configuration");
+    handle.flush();
+
+    let content = fs::read_to_string(test_file).expect("Failed to read log file");
+    let lines: Vec<&str> = content.lines().skip(3).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("] burst"));
+    assert!(lines[1].ends_with("] burst (repeated 3 times)"));
+
+    let _ = fs::remove_file(test_file);
+}
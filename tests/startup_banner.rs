@@ -0,0 +1,37 @@
+use fstdout_logger::{FStdoutLogger, FileFormat, LoggerConfig};
+use log::{LevelFilter, warn};
+use std::fs;
+
+#[test]
+fn test_startup_banner() {
+    let test_file = "test_startup_banner.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder()
+        .use_colors(false)
+        .file_format(FileFormat::Text)
+        .log_startup_banner(true)
+        .build();
+    let handle = FStdoutLogger::with_config(Some(test_file), config)
+        .expect("Failed to create logger")
+        .init_with_handle(LevelFilter::Warn)
+        .expect("Failed to initialize logger");
+
+    warn!("first record");
+    handle.flush();
+
+    let content = fs::read_to_string(test_file).expect("Failed to read log file");
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+
+    // The banner is written at Info even though the level is Warn
+    let banner = lines[0];
+    assert!(banner.contains("INFO"));
+    assert!(banner.contains("logger started level=WARN"));
+    assert!(banner.contains("format=Text"));
+    assert!(banner.contains("colors=false"));
+    assert!(banner.contains(&format!("destinations=console,{test_file}")));
+    assert!(lines[1].contains("first record"));
+
+    let _ = fs::remove_file(test_file);
+}
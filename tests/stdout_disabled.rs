@@ -0,0 +1,47 @@
+use fstdout_logger::{LoggerConfig, init_logger_with_config};
+use log::{error, info};
+use std::env;
+use std::fs;
+use std::process::Command;
+
+/// Environment variable telling the re-executed test binary to act as the child.
+const CHILD_ENV: &str = "FSTDOUT_STDOUT_DISABLED_CHILD";
+
+#[test]
+fn test_file_only_logging() {
+    let test_file = "test_stdout_disabled.log";
+
+    if env::var(CHILD_ENV).is_ok() {
+        let config = LoggerConfig::builder()
+            .use_colors(false)
+            .stdout_enabled(false)
+            .build();
+        init_logger_with_config(Some(test_file), config).expect("Failed to initialize logger");
+
+        info!("only in the file");
+        error!("errors too");
+        log::logger().flush();
+        return;
+    }
+
+    let _ = fs::remove_file(test_file);
+
+    // Run this same test in a child process so its console output can be captured
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "test_file_only_logging", "--nocapture"])
+        .env(CHILD_ENV, "1")
+        .output()
+        .expect("Failed to run child process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stdout.contains("only in the file") && !stdout.contains("errors too"));
+    assert!(!stderr.contains("only in the file") && !stderr.contains("errors too"));
+
+    let content = fs::read_to_string(test_file).expect("Failed to read log file");
+    assert!(content.contains("] only in the file"));
+    assert!(content.contains("] errors too"));
+
+    let _ = fs::remove_file(test_file);
+}
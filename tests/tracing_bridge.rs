@@ -0,0 +1,48 @@
+use fstdout_logger::{FileFormat, LoggerConfig, init_logger_with_tracing};
+use log::LevelFilter;
+use std::fs;
+
+#[test]
+fn test_tracing_events_are_logged() {
+    let test_file = "test_tracing_bridge.log";
+    let _ = fs::remove_file(test_file);
+
+    let config = LoggerConfig::builder()
+        .level(LevelFilter::Info)
+        .file_format(FileFormat::Json)
+        .build();
+    init_logger_with_tracing(Some(test_file), config).expect("Failed to initialize logger");
+
+    let span = tracing::info_span!("request", id = 7, path = "/login");
+    span.in_scope(|| {
+        tracing::info!(user = "alice", attempts = 2, admin = false, "signed in");
+        tracing::debug!("filtered out by the logger level");
+    });
+    tracing::warn!("outside any span");
+    log::info!("from log");
+
+    let lines: Vec<serde_json::Value> = fs::read_to_string(test_file)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+
+    assert_eq!(lines[0]["level"], "INFO");
+    assert_eq!(lines[0]["target"], "tracing_bridge");
+    assert_eq!(lines[0]["file"], "tests/tracing_bridge.rs");
+    assert_eq!(
+        lines[0]["message"],
+        "request{id=7 path=/login}: signed in user=alice attempts=2 admin=false"
+    );
+    // Field types are kept in JSON output
+    assert_eq!(lines[0]["fields"]["user"], "alice");
+    assert_eq!(lines[0]["fields"]["attempts"], 2);
+    assert_eq!(lines[0]["fields"]["admin"], false);
+
+    assert_eq!(lines[1]["level"], "WARN");
+    assert_eq!(lines[1]["message"], "outside any span");
+    assert_eq!(lines[2]["message"], "from log");
+
+    let _ = fs::remove_file(test_file);
+}
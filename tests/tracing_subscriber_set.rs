@@ -0,0 +1,12 @@
+use fstdout_logger::{LoggerConfig, init_logger, init_logger_with_tracing};
+
+#[test]
+fn test_existing_subscriber_leaves_logger_uninstalled() {
+    tracing::subscriber::set_global_default(tracing_subscriber::registry())
+        .expect("Failed to set the subscriber");
+
+    assert!(init_logger_with_tracing(None::<&str>, LoggerConfig::default()).is_err());
+
+    // The logger wasn't installed, so another one still can be
+    init_logger(None::<&str>).expect("A logger was installed anyway");
+}
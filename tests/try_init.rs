@@ -0,0 +1,18 @@
+use fstdout_logger::{LogError, init_logger, try_init, try_init_with_level};
+use log::LevelFilter;
+
+#[test]
+fn test_try_init() {
+    try_init(None::<&str>).expect("Failed to initialize logger");
+
+    // Initializing again is fine, and doesn't touch the file
+    let path = std::env::temp_dir().join("fstdout_logger_try_init.log");
+    std::fs::write(&path, "previous run\n").unwrap();
+    try_init(Some(&path)).expect("Second try_init failed");
+    try_init_with_level(Some(&path), LevelFilter::Debug).expect("try_init_with_level failed");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous run\n");
+    let _ = std::fs::remove_file(&path);
+
+    // The strict variant still reports the installed logger
+    assert!(matches!(init_logger(None::<&str>), Err(LogError::Logger)));
+}